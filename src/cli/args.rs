@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+
+use clap::{ArgGroup, Parser};
+
+// ============================================================================
+// COMMAND LINE
+// ============================================================================
+
+/// Clipboard history manager. Without a mode flag it runs the daemon that
+/// records the clipboard; `--ui` opens the picker.
+#[derive(Parser, Debug)]
+#[command(name = "clipboard-manager", version)]
+#[command(group(ArgGroup::new("mode").args([
+    "ui", "dmenu", "toggle_pause", "paste", "type_index", "clear", "remove",
+    "prune", "stats", "export", "import", "paste_stash",
+])))]
+pub struct Cli {
+    /// Open the picker UI
+    #[arg(long)]
+    pub ui: bool,
+
+    /// Draw the UI without colors (also set by NO_COLOR)
+    #[arg(long, requires = "ui")]
+    pub no_color: bool,
+
+    /// Only list the N newest entries, one line each
+    #[arg(long, value_name = "N", requires = "ui")]
+    pub recent: Option<usize>,
+
+    /// Show each result's fuzzy match score while searching
+    #[arg(long, requires = "ui")]
+    pub debug_scores: bool,
+
+    /// Offer the history to an external picker (e.g. `rofi -dmenu`) and copy
+    /// the chosen entry; without a picker the menu goes to stdout
+    #[arg(
+        long,
+        value_name = "PICKER",
+        num_args = 0..,
+        allow_hyphen_values = true
+    )]
+    pub dmenu: Option<Vec<String>>,
+
+    /// Pause or resume recording in the running daemon
+    #[arg(long)]
+    pub toggle_pause: bool,
+
+    /// Copy the stashed text entries (see `y` in the UI), joined with newlines
+    #[arg(long)]
+    pub paste_stash: bool,
+
+    /// Send the paste keystroke (run by the UI after it closes)
+    #[arg(long, hide = true)]
+    pub paste: bool,
+
+    /// Type the text entry at INDEX into the focused window
+    #[arg(long = "type", value_name = "INDEX")]
+    pub type_index: Option<usize>,
+
+    /// Delete all entries and images (pinned ones are kept unless
+    /// `clear_keeps_pinned` is off)
+    #[arg(long)]
+    pub clear: bool,
+
+    /// Make --clear delete pinned entries too
+    #[arg(long, requires = "clear")]
+    pub include_pinned: bool,
+
+    /// Delete the entry at INDEX (0 is the top of the list)
+    #[arg(long, value_name = "INDEX")]
+    pub remove: Option<usize>,
+
+    /// Delete unpinned entries older than AGE, e.g. 30m, 12h, 7d or 2w
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    pub prune: Option<i64>,
+
+    /// Print entry counts, image storage, text length and date range
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Print the stats as JSON
+    #[arg(long, requires = "stats")]
+    pub json: bool,
+
+    /// Bundle the history and its images into a .tar.gz
+    #[arg(long, value_name = "PATH")]
+    pub export: Option<PathBuf>,
+
+    /// Merge a .tar.gz made by --export into this history
+    #[arg(long, value_name = "PATH")]
+    pub import: Option<PathBuf>,
+
+    /// Run the daemon without the banner, setup hints or heartbeat
+    #[arg(long, conflicts_with = "mode")]
+    pub daemon_quiet: bool,
+
+    /// Record the clipboard in memory only: the history file, images and
+    /// archive log are never written
+    #[arg(long, conflicts_with = "mode")]
+    pub read_only: bool,
+
+    /// Keep the history, images and PID file in PATH instead of the
+    /// platform's data directory (also set by CLIPBOARD_MANAGER_DATA_DIR)
+    #[arg(long, value_name = "PATH")]
+    pub data_dir: Option<PathBuf>,
+
+    /// Clipboard backend to use instead of detecting one
+    #[arg(long, value_parser = ["wl", "xclip", "arboard"])]
+    pub backend: Option<String>,
+
+    /// Only log errors
+    #[arg(long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Log debug messages
+    #[arg(long)]
+    pub verbose: bool,
+}
+
+impl Cli {
+    /// Whether a one-shot command was given rather than the daemon or UI,
+    /// so logging defaults to warnings only.
+    pub fn is_command(&self) -> bool {
+        self.dmenu.is_some()
+            || self.toggle_pause
+            || self.type_index.is_some()
+            || self.clear
+            || self.remove.is_some()
+            || self.prune.is_some()
+            || self.stats
+            || self.export.is_some()
+            || self.import.is_some()
+            || self.paste_stash
+    }
+}
+
+/// Parse an age such as `45s`, `30m`, `12h`, `7d` or `2w` into seconds.
+fn parse_age(text: &str) -> Result<i64, String> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: i64 = number
+        .parse()
+        .map_err(|_| format!("expected a number and a unit, e.g. 7d (got {:?})", text))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown unit {:?} (use s, m, h, d or w)", unit)),
+    };
+    number
+        .checked_mul(unit_secs)
+        .ok_or_else(|| format!("{:?} is too long", text))
+}
@@ -1,29 +1,271 @@
 use std::env;
+use std::path::Path;
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use arboard::Clipboard;
+use log::{debug, warn};
+
+use crate::config::config;
+use crate::models::{ClipboardEntry, Selection};
+use crate::utils::{BACKEND_ENV_VAR, format_size, hash_bytes};
 
 // ============================================================================
 // CLIPBOARD BACKEND
 // ============================================================================
 
+/// Vector source some apps offer alongside a rendered image, kept with it
+const SVG_MIME_TYPE: &str = "image/svg+xml";
+
+/// Image types to fetch, in order of preference. GIF/WEBP come first since apps
+/// offering them alongside PNG are usually serving the original (possibly animated) file.
+const IMAGE_MIME_TYPES: &[&str] = &[
+    "image/gif",
+    "image/webp",
+    "image/png",
+    "image/jpeg",
+    "image/jpg",
+    "image/bmp",
+];
+
+/// The tool used to read and write the system clipboard.
 #[derive(Debug, Clone, Copy)]
 pub enum ClipboardBackend {
     WlClipboard,
+    XClip,
     Arboard,
 }
 
+impl ClipboardBackend {
+    /// Name accepted by `--backend` and `CLIPBOARD_MANAGER_BACKEND`
+    pub fn name(self) -> &'static str {
+        match self {
+            ClipboardBackend::WlClipboard => "wl",
+            ClipboardBackend::XClip => "xclip",
+            ClipboardBackend::Arboard => "arboard",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "wl" | "wayland" | "wl-clipboard" => Some(ClipboardBackend::WlClipboard),
+            "xclip" | "x11" => Some(ClipboardBackend::XClip),
+            "arboard" => Some(ClipboardBackend::Arboard),
+            _ => None,
+        }
+    }
+
+    /// Whether the tool this backend shells out to is installed
+    fn is_available(self) -> bool {
+        match self {
+            ClipboardBackend::WlClipboard => {
+                Command::new("wl-paste").arg("--version").output().is_ok()
+            }
+            ClipboardBackend::XClip => Command::new("xclip").arg("-version").output().is_ok(),
+            ClipboardBackend::Arboard => true,
+        }
+    }
+
+    /// Whether the display server this backend talks to is still there.
+    /// Only local sockets can be checked; anything else counts as present.
+    pub fn session_available(self) -> bool {
+        match self {
+            ClipboardBackend::WlClipboard => {
+                let display =
+                    env::var("WAYLAND_DISPLAY").unwrap_or_else(|_| "wayland-0".to_string());
+                let socket = Path::new(&display);
+                if socket.is_absolute() {
+                    return socket.exists();
+                }
+                env::var_os("XDG_RUNTIME_DIR")
+                    .is_none_or(|dir| Path::new(&dir).join(socket).exists())
+            }
+            // `:1` or `:1.0` is /tmp/.X11-unix/X1; `host:1` goes over TCP
+            ClipboardBackend::XClip => match env::var("DISPLAY") {
+                Ok(display) if display.starts_with(':') => {
+                    let number = display[1..].split('.').next().unwrap_or_default();
+                    Path::new("/tmp/.X11-unix")
+                        .join(format!("X{}", number))
+                        .exists()
+                }
+                _ => true,
+            },
+            ClipboardBackend::Arboard => true,
+        }
+    }
+}
+
+/// Use the backend named by `--backend` (or else `CLIPBOARD_MANAGER_BACKEND`),
+/// falling back to detection when none is given, the name is unknown, or
+/// its tool isn't installed.
+pub fn select_clipboard_backend(requested: Option<&str>) -> ClipboardBackend {
+    let requested = requested.map(String::from).or_else(|| {
+        env::var(BACKEND_ENV_VAR)
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+    });
+    let Some(name) = requested else {
+        return detect_clipboard_backend();
+    };
+
+    match ClipboardBackend::from_name(&name) {
+        Some(backend) if backend.is_available() => backend,
+        Some(backend) => {
+            warn!(
+                "Backend {} requested but not available, detecting instead",
+                backend.name()
+            );
+            detect_clipboard_backend()
+        }
+        None => {
+            warn!(
+                "Unknown backend {:?} (expected wl, xclip or arboard), detecting instead",
+                name
+            );
+            detect_clipboard_backend()
+        }
+    }
+}
+
+/// The backend for the current session: wl-clipboard on Wayland, xclip on
+/// X11, arboard otherwise.
 pub fn detect_clipboard_backend() -> ClipboardBackend {
     if (env::var("WAYLAND_DISPLAY").is_ok()
         || env::var("XDG_SESSION_TYPE").map_or(false, |v| v == "wayland"))
         && Command::new("wl-paste").arg("--version").output().is_ok()
     {
         ClipboardBackend::WlClipboard
+    } else if env::var("XDG_SESSION_TYPE").is_ok_and(|v| v == "x11")
+        && Command::new("xclip").arg("-version").output().is_ok()
+    {
+        ClipboardBackend::XClip
     } else {
         ClipboardBackend::Arboard
     }
 }
 
+/// Run `xclip -selection clipboard -o` with optional extra args and return stdout on success.
+fn xclip_output(target: Option<&str>) -> Option<Vec<u8>> {
+    xclip_command(target)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| output.stdout)
+}
+
+/// `xclip -selection clipboard -o`, reading `target` if given
+fn xclip_command(target: Option<&str>) -> Command {
+    let mut cmd = Command::new("xclip");
+    cmd.arg("-selection").arg("clipboard").arg("-o");
+    if let Some(target) = target {
+        cmd.arg("-t").arg(target);
+    }
+    cmd
+}
+
+/// Run `cmd` for its output, unless it fails or writes more than `limit`
+/// bytes. The read stops at the limit, so e.g. a huge image is never
+/// buffered in full only to be thrown away.
+fn capped_output(cmd: &mut Command, limit: u64) -> Option<Vec<u8>> {
+    use std::io::Read;
+
+    let mut child = cmd
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut data = Vec::new();
+    let read = match child.stdout.take() {
+        Some(stdout) => stdout.take(limit.saturating_add(1)).read_to_end(&mut data),
+        None => Ok(0),
+    };
+    if read.is_err() || data.len() as u64 > limit {
+        if read.is_ok() {
+            debug!("Skipped clipboard data over {}", format_size(limit));
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+        return None;
+    }
+    child.wait().ok().filter(|status| status.success())?;
+    Some(data)
+}
+
+/// Image data offered as `mime_type`, read with `capped_output` up to
+/// `max_image_bytes`.
+fn read_image_type(backend: ClipboardBackend, mime_type: &str) -> Option<Vec<u8>> {
+    let limit = config().max_image_bytes;
+    let data = match backend {
+        ClipboardBackend::WlClipboard => {
+            capped_output(Command::new("wl-paste").arg("--type").arg(mime_type), limit)
+        }
+        ClipboardBackend::XClip => capped_output(&mut xclip_command(Some(mime_type)), limit),
+        ClipboardBackend::Arboard => None,
+    };
+    data.filter(|data| !data.is_empty())
+}
+
+/// Pipe `data` into `wl-copy [--primary] --type <mime_type>`.
+/// wl-copy forks into the background to serve the selection, so waiting returns promptly.
+fn wl_copy_input(selection: Selection, data: &[u8], mime_type: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    let mut child = Command::new("wl-copy")
+        .args(wl_copy_selection_args(selection))
+        .arg("--type")
+        .arg(mime_type)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn wl-copy: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(data)
+            .map_err(|e| format!("Failed to write to wl-copy: {}", e))?;
+    }
+
+    let status = child.wait().map_err(|e| format!("wl-copy failed: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("wl-copy exited with status: {}", status))
+    }
+}
+
+/// Pipe `data` into `xclip -selection clipboard|primary -t <target>`.
+/// xclip forks into the background to serve the selection, so waiting returns promptly.
+fn xclip_input(selection: Selection, data: &[u8], target: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    let selection = match selection {
+        Selection::Clipboard => "clipboard",
+        Selection::Primary => "primary",
+    };
+    let mut child = Command::new("xclip")
+        .arg("-selection")
+        .arg(selection)
+        .arg("-t")
+        .arg(target)
+        .arg("-i")
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to spawn xclip: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(data)
+            .map_err(|e| format!("Failed to write to xclip: {}", e))?;
+    }
+
+    let status = child.wait().map_err(|e| format!("xclip failed: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("xclip exited with status: {}", status))
+    }
+}
+
 pub fn get_clipboard_types(backend: ClipboardBackend) -> Vec<String> {
     match backend {
         ClipboardBackend::WlClipboard => Command::new("wl-paste")
@@ -38,10 +280,31 @@ pub fn get_clipboard_types(backend: ClipboardBackend) -> Vec<String> {
                     .collect()
             })
             .unwrap_or_default(),
-        ClipboardBackend::Arboard => Vec::new(),
+        ClipboardBackend::XClip => xclip_output(Some("TARGETS"))
+            .map(|stdout| {
+                String::from_utf8_lossy(&stdout)
+                    .lines()
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        // arboard can't list types, so report the ones it can read
+        ClipboardBackend::Arboard => Clipboard::new()
+            .map(|mut cb| {
+                let mut types = Vec::new();
+                if cb.get_image().is_ok() {
+                    types.push("image/png".to_string());
+                }
+                if cb.get_text().is_ok() {
+                    types.push("text/plain".to_string());
+                }
+                types
+            })
+            .unwrap_or_default(),
     }
 }
 
+/// The clipboard's text, if it holds any.
 pub fn get_clipboard_text(backend: ClipboardBackend) -> Option<String> {
     match backend {
         ClipboardBackend::WlClipboard => Command::new("wl-paste")
@@ -51,6 +314,9 @@ pub fn get_clipboard_text(backend: ClipboardBackend) -> Option<String> {
             .filter(|output| output.status.success())
             .and_then(|output| String::from_utf8(output.stdout).ok())
             .filter(|s| !s.trim().is_empty()),
+        ClipboardBackend::XClip => xclip_output(None)
+            .and_then(|stdout| String::from_utf8(stdout).ok())
+            .filter(|s| !s.trim().is_empty()),
         ClipboardBackend::Arboard => Clipboard::new()
             .ok()
             .and_then(|mut cb| cb.get_text().ok())
@@ -58,22 +324,176 @@ pub fn get_clipboard_text(backend: ClipboardBackend) -> Option<String> {
     }
 }
 
-pub fn get_clipboard_image(backend: ClipboardBackend) -> Option<Vec<u8>> {
+/// Read the `text/html` representation of the clipboard, if one is offered.
+pub fn get_clipboard_html(backend: ClipboardBackend, types: &[String]) -> Option<String> {
+    let has_html = types.iter().any(|t| t == "text/html");
     match backend {
-        ClipboardBackend::WlClipboard => {
-            for mime_type in &["image/png", "image/jpeg", "image/jpg", "image/bmp"] {
-                if let Ok(output) = Command::new("wl-paste")
-                    .arg("--type")
-                    .arg(mime_type)
-                    .output()
-                {
-                    if output.status.success() && !output.stdout.is_empty() {
-                        return Some(output.stdout);
-                    }
-                }
-            }
-            None
+        ClipboardBackend::WlClipboard if has_html => Command::new("wl-paste")
+            .arg("--type")
+            .arg("text/html")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok()),
+        ClipboardBackend::XClip if has_html => {
+            xclip_output(Some("text/html")).and_then(|stdout| String::from_utf8(stdout).ok())
         }
+        ClipboardBackend::WlClipboard | ClipboardBackend::XClip => None,
+        ClipboardBackend::Arboard => Clipboard::new()
+            .ok()
+            .and_then(|mut cb| cb.get().html().ok()),
+    }
+    .filter(|s| !s.trim().is_empty())
+}
+
+/// Read the text of the middle-click PRIMARY selection.
+pub fn get_primary_text(backend: ClipboardBackend) -> Option<String> {
+    match backend {
+        ClipboardBackend::WlClipboard => Command::new("wl-paste")
+            .arg("--primary")
+            .arg("--no-newline")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok()),
+        ClipboardBackend::XClip => Command::new("xclip")
+            .arg("-selection")
+            .arg("primary")
+            .arg("-o")
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok()),
+        ClipboardBackend::Arboard => arboard_primary_text(),
+    }
+    .filter(|s| !s.trim().is_empty())
+}
+
+#[cfg(target_os = "linux")]
+fn arboard_primary_text() -> Option<String> {
+    use arboard::{GetExtLinux, LinuxClipboardKind};
+
+    Clipboard::new()
+        .ok()
+        .and_then(|mut cb| cb.get().clipboard(LinuxClipboardKind::Primary).text().ok())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn arboard_primary_text() -> Option<String> {
+    None
+}
+
+/// Read the primary selection's offered MIME types (only wl-paste can list them).
+pub fn get_primary_types(backend: ClipboardBackend) -> Vec<String> {
+    match backend {
+        ClipboardBackend::WlClipboard => Command::new("wl-paste")
+            .arg("--primary")
+            .arg("--list-types")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        ClipboardBackend::XClip | ClipboardBackend::Arboard => Vec::new(),
+    }
+}
+
+/// The most preferred image type among the offered `types`, if any.
+pub fn offered_image_type(types: &[String]) -> Option<&'static str> {
+    IMAGE_MIME_TYPES
+        .iter()
+        .copied()
+        .find(|mime| types.iter().any(|t| t == mime))
+}
+
+/// Hash of the last data offered as an image that wasn't one
+static REJECTED_IMAGE_HASH: AtomicU64 = AtomicU64::new(0);
+
+/// Whether `data` fetched for an offered image type is really an image.
+/// Some compositors list `image/png` for a file reference and the like;
+/// the monitors capture the text instead. The last rejected data is
+/// remembered, so it's reported once and not checked again on every poll.
+pub fn is_image_data(data: &[u8]) -> bool {
+    let hash = hash_bytes(data);
+    if REJECTED_IMAGE_HASH.load(Ordering::Relaxed) == hash {
+        return false;
+    }
+    if image::guess_format(data).is_ok() {
+        return true;
+    }
+    REJECTED_IMAGE_HASH.store(hash, Ordering::Relaxed);
+    warn!(
+        "Clipboard offers an image type but holds {} of something else; storing its text instead",
+        format_size(data.len() as u64)
+    );
+    false
+}
+
+/// Read the `image/svg+xml` representation of the clipboard, if one is offered.
+pub fn get_clipboard_svg(backend: ClipboardBackend, types: &[String]) -> Option<String> {
+    if !types.iter().any(|t| t == SVG_MIME_TYPE) {
+        return None;
+    }
+    let data = match backend {
+        ClipboardBackend::WlClipboard => Command::new("wl-paste")
+            .arg("--type")
+            .arg(SVG_MIME_TYPE)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| output.stdout),
+        ClipboardBackend::XClip => xclip_output(Some(SVG_MIME_TYPE)),
+        ClipboardBackend::Arboard => None,
+    };
+    data.and_then(|data| String::from_utf8(data).ok())
+        .filter(|svg| !svg.trim().is_empty())
+}
+
+/// Read an image when the offered `types` are already known, fetching only
+/// the preferred type instead of probing each one.
+pub fn get_offered_image(backend: ClipboardBackend, types: &[String]) -> Option<Vec<u8>> {
+    match backend {
+        ClipboardBackend::WlClipboard => read_image_type(backend, offered_image_type(types)?),
+        ClipboardBackend::XClip | ClipboardBackend::Arboard => get_clipboard_image(backend),
+    }
+}
+
+/// The first of the offered `types` listed in `binary_mime_types`, if any.
+pub fn offered_binary_type(types: &[String]) -> Option<&str> {
+    types
+        .iter()
+        .find(|t| config().binary_mime_types.contains(t))
+        .map(String::as_str)
+}
+
+/// Read the clipboard's data offered as `mime_type` (not available with arboard).
+pub fn get_clipboard_data(backend: ClipboardBackend, mime_type: &str) -> Option<Vec<u8>> {
+    match backend {
+        ClipboardBackend::WlClipboard => Command::new("wl-paste")
+            .arg("--type")
+            .arg(mime_type)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| output.stdout),
+        ClipboardBackend::XClip => xclip_output(Some(mime_type)),
+        ClipboardBackend::Arboard => None,
+    }
+    .filter(|data| !data.is_empty())
+}
+
+/// The clipboard's image data, if it holds an image.
+pub fn get_clipboard_image(backend: ClipboardBackend) -> Option<Vec<u8>> {
+    match backend {
+        ClipboardBackend::WlClipboard | ClipboardBackend::XClip => IMAGE_MIME_TYPES
+            .iter()
+            .find_map(|mime_type| read_image_type(backend, mime_type)),
         ClipboardBackend::Arboard => Clipboard::new()
             .ok()
             .and_then(|mut cb| cb.get_image().ok())
@@ -81,8 +501,14 @@ pub fn get_clipboard_image(backend: ClipboardBackend) -> Option<Vec<u8>> {
                 use image::{ImageBuffer, RgbaImage};
                 use std::io::Cursor;
 
+                let (width, height) = (img.width as u32, img.height as u32);
+                if config().exceeds_pixel_budget(width, height) {
+                    warn!("Skipped {}×{} image over max_image_pixels", width, height);
+                    return None;
+                }
+                // Takes the pixels over instead of copying them when arboard owns them
                 let img_buffer: RgbaImage =
-                    ImageBuffer::from_raw(img.width as u32, img.height as u32, img.bytes.to_vec())?;
+                    ImageBuffer::from_raw(width, height, img.bytes.into_owned())?;
 
                 let mut png_data = Vec::new();
                 img_buffer
@@ -94,12 +520,45 @@ pub fn get_clipboard_image(backend: ClipboardBackend) -> Option<Vec<u8>> {
     }
 }
 
+/// Empty the regular clipboard.
+pub fn clear_clipboard(backend: ClipboardBackend) -> Result<(), String> {
+    match backend {
+        ClipboardBackend::WlClipboard => {
+            let status = Command::new("wl-copy")
+                .arg("--clear")
+                .status()
+                .map_err(|e| format!("Failed to spawn wl-copy: {}", e))?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("wl-copy exited with status: {}", status))
+            }
+        }
+        // xclip can't drop the selection; owning it with no data is the closest
+        ClipboardBackend::XClip => xclip_input(Selection::Clipboard, b"", "UTF8_STRING"),
+        ClipboardBackend::Arboard => Clipboard::new()
+            .and_then(|mut cb| cb.clear())
+            .map_err(|e| format!("Failed to clear clipboard: {}", e)),
+    }
+}
+
+/// Put `content` on the clipboard.
 pub fn set_clipboard_text(content: &str, backend: ClipboardBackend) -> Result<(), String> {
+    set_text(content, Selection::Clipboard, backend)
+}
+
+/// Put text in the PRIMARY selection, so a middle-click pastes it.
+pub fn set_primary_text(content: &str, backend: ClipboardBackend) -> Result<(), String> {
+    set_text(content, Selection::Primary, backend)
+}
+
+fn set_text(content: &str, selection: Selection, backend: ClipboardBackend) -> Result<(), String> {
     match backend {
         ClipboardBackend::WlClipboard => {
             // Use spawn() and wait() to avoid hanging on pipes if wl-copy backgrounds
             // We do NOT capture stdout/stderr to avoid blocking
             let mut child = Command::new("wl-copy")
+                .args(wl_copy_selection_args(selection))
                 .arg("--")
                 .arg(content)
                 .spawn()
@@ -115,47 +574,149 @@ pub fn set_clipboard_text(content: &str, backend: ClipboardBackend) -> Result<()
                     }
                 })
         }
+        ClipboardBackend::XClip => xclip_input(selection, content.as_bytes(), "UTF8_STRING"),
         ClipboardBackend::Arboard => Clipboard::new()
-            .and_then(|mut cb| cb.set_text(content))
+            .and_then(|mut cb| arboard_target(&mut cb, selection).text(content))
             .map_err(|e| format!("Failed to set text: {}", e)),
     }
 }
 
-pub fn set_clipboard_image(
-    image_path: &std::path::PathBuf,
+/// Extra wl-copy arguments for writing to `selection`.
+fn wl_copy_selection_args(selection: Selection) -> &'static [&'static str] {
+    match selection {
+        Selection::Clipboard => &[],
+        Selection::Primary => &["--primary"],
+    }
+}
+
+/// arboard setter for `selection`.
+#[cfg(target_os = "linux")]
+fn arboard_target(clipboard: &mut Clipboard, selection: Selection) -> arboard::Set<'_> {
+    use arboard::{LinuxClipboardKind, SetExtLinux};
+
+    let kind = match selection {
+        Selection::Clipboard => LinuxClipboardKind::Clipboard,
+        Selection::Primary => LinuxClipboardKind::Primary,
+    };
+    clipboard.set().clipboard(kind)
+}
+
+/// Only Linux has a PRIMARY selection; elsewhere the clipboard is used.
+#[cfg(not(target_os = "linux"))]
+fn arboard_target(clipboard: &mut Clipboard, selection: Selection) -> arboard::Set<'_> {
+    if selection == Selection::Primary {
+        warn!("There is no primary selection on this platform; using the clipboard");
+    }
+    clipboard.set()
+}
+
+/// Set HTML content along with its plain-text fallback.
+/// Arboard offers both `text/html` and `text/plain`; wl-copy and xclip can only
+/// serve a single payload, so they offer the HTML as `text/html` alone.
+pub fn set_clipboard_html(
+    html: &str,
+    plain: &str,
     backend: ClipboardBackend,
 ) -> Result<(), String> {
-    use std::fs;
+    match backend {
+        ClipboardBackend::WlClipboard => {
+            wl_copy_input(Selection::Clipboard, html.as_bytes(), "text/html")
+        }
+        ClipboardBackend::XClip => xclip_input(Selection::Clipboard, html.as_bytes(), "text/html"),
+        ClipboardBackend::Arboard => Clipboard::new()
+            .and_then(|mut cb| cb.set_html(html, Some(plain)))
+            .map_err(|e| format!("Failed to set html: {}", e)),
+    }
+}
+
+/// Copy a file as a reference rather than its contents. wl-copy and xclip
+/// offer a `file://` URI as `text/uri-list`, which file managers and chat apps
+/// take as an attachment; arboard sets the absolute path as plain text.
+pub fn set_clipboard_file(path: &Path, backend: ClipboardBackend) -> Result<(), String> {
+    let path = std::path::absolute(path).map_err(|e| format!("Failed to resolve path: {}", e))?;
+    let uri_list = format!("{}\r\n", file_uri(&path));
 
     match backend {
         ClipboardBackend::WlClipboard => {
-            let image_data =
-                fs::read(image_path).map_err(|e| format!("Failed to read image: {}", e))?;
+            wl_copy_input(Selection::Clipboard, uri_list.as_bytes(), "text/uri-list")
+        }
+        ClipboardBackend::XClip => {
+            xclip_input(Selection::Clipboard, uri_list.as_bytes(), "text/uri-list")
+        }
+        ClipboardBackend::Arboard => Clipboard::new()
+            .and_then(|mut cb| cb.set_text(path.to_string_lossy()))
+            .map_err(|e| format!("Failed to set text: {}", e)),
+    }
+}
 
-            let mime_type = match image_path.extension().and_then(|s| s.to_str()) {
-                Some("png") => "image/png",
-                Some("jpg") | Some("jpeg") => "image/jpeg",
-                Some("bmp") => "image/bmp",
-                _ => "image/png",
-            };
+/// `file://` URI for an absolute path, percent-encoding everything but
+/// unreserved characters and separators.
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for &byte in path.as_os_str().as_encoded_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
 
-            let mut child = Command::new("wl-copy")
-                .arg("--type")
-                .arg(mime_type)
-                .stdin(std::process::Stdio::piped())
-                .spawn()
-                .map_err(|e| format!("Failed to spawn wl-copy: {}", e))?;
+pub fn set_clipboard_image(image_path: &Path, backend: ClipboardBackend) -> Result<(), String> {
+    set_image(image_path, Selection::Clipboard, backend)
+}
 
-            if let Some(mut stdin) = child.stdin.take() {
-                use std::io::Write;
-                stdin
-                    .write_all(&image_data)
-                    .map_err(|e| format!("Failed to write to wl-copy: {}", e))?;
-            }
+/// Restore an image entry: its SVG when one was captured (arboard can't offer
+/// SVG, so it gets the stored raster), otherwise the image file.
+pub fn set_clipboard_image_entry(
+    entry: &ClipboardEntry,
+    images_dir: &Path,
+    backend: ClipboardBackend,
+) -> Result<(), String> {
+    match (&entry.svg_content, backend) {
+        (Some(svg), ClipboardBackend::WlClipboard) => {
+            wl_copy_input(Selection::Clipboard, svg.as_bytes(), SVG_MIME_TYPE)
+        }
+        (Some(svg), ClipboardBackend::XClip) => {
+            xclip_input(Selection::Clipboard, svg.as_bytes(), SVG_MIME_TYPE)
+        }
+        _ => set_clipboard_image(&images_dir.join(&entry.content), backend),
+    }
+}
 
-            child.wait().map_err(|e| format!("wl-copy failed: {}", e))?;
+/// Put an image in the PRIMARY selection, so a middle-click pastes it.
+pub fn set_primary_image(image_path: &Path, backend: ClipboardBackend) -> Result<(), String> {
+    set_image(image_path, Selection::Primary, backend)
+}
 
-            Ok(())
+fn set_image(
+    image_path: &Path,
+    selection: Selection,
+    backend: ClipboardBackend,
+) -> Result<(), String> {
+    use std::fs;
+
+    let mime_type = match image_path.extension().and_then(|s| s.to_str()) {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("bmp") => "image/bmp",
+        _ => "image/png",
+    };
+
+    match backend {
+        ClipboardBackend::WlClipboard => {
+            let image_data =
+                fs::read(image_path).map_err(|e| format!("Failed to read image: {}", e))?;
+
+            wl_copy_input(selection, &image_data, mime_type)
+        }
+        ClipboardBackend::XClip => {
+            let image_data =
+                fs::read(image_path).map_err(|e| format!("Failed to read image: {}", e))?;
+            xclip_input(selection, &image_data, mime_type)
         }
         ClipboardBackend::Arboard => {
             use image::ImageReader;
@@ -175,8 +736,67 @@ pub fn set_clipboard_image(
             };
 
             Clipboard::new()
-                .and_then(|mut cb| cb.set_image(img_data))
+                .and_then(|mut cb| arboard_target(&mut cb, selection).image(img_data))
                 .map_err(|e| format!("Failed to set image: {}", e))
         }
     }
 }
+
+/// Offer the data stored at `path` as `mime_type`, as it was copied.
+/// Arboard can only set text and images, so it fails there.
+pub fn set_clipboard_binary(
+    path: &Path,
+    mime_type: &str,
+    backend: ClipboardBackend,
+) -> Result<(), String> {
+    set_binary(path, mime_type, Selection::Clipboard, backend)
+}
+
+/// Put binary data in the PRIMARY selection, so a middle-click pastes it.
+pub fn set_primary_binary(
+    path: &Path,
+    mime_type: &str,
+    backend: ClipboardBackend,
+) -> Result<(), String> {
+    set_binary(path, mime_type, Selection::Primary, backend)
+}
+
+fn set_binary(
+    path: &Path,
+    mime_type: &str,
+    selection: Selection,
+    backend: ClipboardBackend,
+) -> Result<(), String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read data: {}", e))?;
+
+    match backend {
+        ClipboardBackend::WlClipboard => wl_copy_input(selection, &data, mime_type),
+        ClipboardBackend::XClip => xclip_input(selection, &data, mime_type),
+        ClipboardBackend::Arboard => Err(format!("The arboard backend can't offer {}", mime_type)),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn zeros(count: usize) -> Command {
+        let mut cmd = Command::new("head");
+        cmd.arg("-c").arg(count.to_string()).arg("/dev/zero");
+        cmd
+    }
+
+    #[test]
+    fn output_within_the_limit_is_read() {
+        let data = capped_output(&mut zeros(2000), 2000);
+        assert_eq!(data.map(|d| d.len()), Some(2000));
+    }
+
+    #[test]
+    fn output_over_the_limit_is_dropped() {
+        assert_eq!(capped_output(&mut zeros(2001), 2000), None);
+        // Stops reading instead of buffering everything
+        let endless = capped_output(Command::new("cat").arg("/dev/zero"), 2000);
+        assert_eq!(endless, None);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod backend;
+pub mod provider;
 
 pub use backend::*;
+pub use provider::*;
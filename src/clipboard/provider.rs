@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use crate::clipboard::{
+    ClipboardBackend, get_clipboard_data, get_clipboard_html, get_clipboard_svg,
+    get_clipboard_text, get_clipboard_types, get_offered_image, get_primary_text,
+    get_primary_types, set_clipboard_image, set_clipboard_text,
+};
+
+// ============================================================================
+// CLIPBOARD PROVIDER
+// ============================================================================
+
+/// What the monitors read from (and the picker writes to) the clipboard.
+/// `ClipboardBackend` implements it by shelling out to wl-clipboard/xclip or
+/// through arboard; anything else (e.g. an in-memory fake) can stand in for
+/// it. Only the first five methods are required; the extra representations
+/// default to "not offered".
+pub trait ClipboardProvider: Send + Sync {
+    /// MIME types (or X11 targets) the clipboard currently offers
+    fn get_types(&self) -> Vec<String>;
+
+    fn get_text(&self) -> Option<String>;
+
+    /// Image data, fetched in the preferred of the offered `types`
+    fn get_image(&self, types: &[String]) -> Option<Vec<u8>>;
+
+    fn set_text(&self, content: &str) -> Result<(), String>;
+
+    fn set_image(&self, image_path: &Path) -> Result<(), String>;
+
+    /// The `text/html` representation, if offered
+    fn get_html(&self, _types: &[String]) -> Option<String> {
+        None
+    }
+
+    /// The `image/svg+xml` representation, if offered
+    fn get_svg(&self, _types: &[String]) -> Option<String> {
+        None
+    }
+
+    /// Raw data offered as `mime_type`
+    fn get_data(&self, _mime_type: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn get_primary_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn get_primary_text(&self) -> Option<String> {
+        None
+    }
+}
+
+impl ClipboardProvider for ClipboardBackend {
+    fn get_types(&self) -> Vec<String> {
+        get_clipboard_types(*self)
+    }
+
+    fn get_text(&self) -> Option<String> {
+        get_clipboard_text(*self)
+    }
+
+    fn get_image(&self, types: &[String]) -> Option<Vec<u8>> {
+        get_offered_image(*self, types)
+    }
+
+    fn set_text(&self, content: &str) -> Result<(), String> {
+        set_clipboard_text(content, *self)
+    }
+
+    fn set_image(&self, image_path: &Path) -> Result<(), String> {
+        set_clipboard_image(image_path, *self)
+    }
+
+    fn get_html(&self, types: &[String]) -> Option<String> {
+        get_clipboard_html(*self, types)
+    }
+
+    fn get_svg(&self, types: &[String]) -> Option<String> {
+        get_clipboard_svg(*self, types)
+    }
+
+    fn get_data(&self, mime_type: &str) -> Option<Vec<u8>> {
+        get_clipboard_data(*self, mime_type)
+    }
+
+    fn get_primary_types(&self) -> Vec<String> {
+        get_primary_types(*self)
+    }
+
+    fn get_primary_text(&self) -> Option<String> {
+        get_primary_text(*self)
+    }
+}
+
+// ============================================================================
+// IN-MEMORY PROVIDER
+// ============================================================================
+
+/// A clipboard kept in memory, so tests can drive the monitors without a
+/// display server. Holds one text or one image at a time, like the real one.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryClipboard {
+    content: std::sync::Mutex<Option<(String, Vec<u8>)>>,
+}
+
+#[cfg(test)]
+impl MemoryClipboard {
+    pub fn copy_text(&self, text: &str) {
+        *self.content.lock().unwrap() = Some(("text/plain".to_string(), text.as_bytes().to_vec()));
+    }
+
+    pub fn copy_image(&self, png: &[u8]) {
+        *self.content.lock().unwrap() = Some(("image/png".to_string(), png.to_vec()));
+    }
+
+    fn offered(&self, prefix: &str) -> Option<Vec<u8>> {
+        let content = self.content.lock().unwrap();
+        let (mime, data) = content.as_ref()?;
+        mime.starts_with(prefix).then(|| data.clone())
+    }
+}
+
+#[cfg(test)]
+impl ClipboardProvider for MemoryClipboard {
+    fn get_types(&self) -> Vec<String> {
+        let content = self.content.lock().unwrap();
+        content.iter().map(|(mime, _)| mime.clone()).collect()
+    }
+
+    fn get_text(&self) -> Option<String> {
+        String::from_utf8(self.offered("text/")?).ok()
+    }
+
+    fn get_image(&self, _types: &[String]) -> Option<Vec<u8>> {
+        self.offered("image/")
+    }
+
+    fn set_text(&self, content: &str) -> Result<(), String> {
+        self.copy_text(content);
+        Ok(())
+    }
+
+    fn set_image(&self, image_path: &Path) -> Result<(), String> {
+        let data = std::fs::read(image_path).map_err(|e| e.to_string())?;
+        self.copy_image(&data);
+        Ok(())
+    }
+}
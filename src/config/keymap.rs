@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
+use log::warn;
+use serde::Deserialize;
+
+// ============================================================================
+// KEYMAP
+// ============================================================================
+
+/// Remappable actions of the history list (and the matching detail view keys).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Delete,
+    Search,
+    Clear,
+    Quit,
+    Pin,
+    Copy,
+}
+
+impl Action {
+    const ALL: [Action; 8] = [
+        Action::Up,
+        Action::Down,
+        Action::Delete,
+        Action::Search,
+        Action::Clear,
+        Action::Quit,
+        Action::Pin,
+        Action::Copy,
+    ];
+
+    /// Name used for the action in the `[keys]` config table
+    fn name(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Delete => "delete",
+            Action::Search => "search",
+            Action::Clear => "clear",
+            Action::Quit => "quit",
+            Action::Pin => "pin",
+            Action::Copy => "copy",
+        }
+    }
+
+    fn default_keys(self) -> Vec<KeyCode> {
+        match self {
+            Action::Up => vec![KeyCode::Up, KeyCode::Char('k')],
+            Action::Down => vec![KeyCode::Down, KeyCode::Char('j')],
+            Action::Delete => vec![KeyCode::Char('d'), KeyCode::Char('D'), KeyCode::Delete],
+            Action::Search => vec![KeyCode::Char('s'), KeyCode::Char('/')],
+            Action::Clear => vec![KeyCode::Char('c'), KeyCode::Char('C')],
+            Action::Quit => vec![KeyCode::Char('q'), KeyCode::Esc],
+            Action::Pin => vec![KeyCode::Char('p'), KeyCode::Char('P')],
+            Action::Copy => vec![KeyCode::Enter],
+        }
+    }
+}
+
+/// One key name or a list of them, e.g. `quit = "x"` or `up = ["k", "Up"]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpec {
+    fn names(&self) -> &[String] {
+        match self {
+            KeySpec::One(name) => std::slice::from_ref(name),
+            KeySpec::Many(names) => names,
+        }
+    }
+}
+
+/// Maps pressed keys to actions.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<KeyCode>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .iter()
+                .map(|&action| (action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Build a keymap from the `[keys]` table. Unknown actions and invalid key
+    /// names are warned about; an action with any invalid key keeps its defaults.
+    pub fn from_config(keys: &HashMap<String, KeySpec>) -> Self {
+        let mut keymap = Self::default();
+
+        for (name, spec) in keys {
+            let Some(action) = Action::ALL.iter().copied().find(|a| a.name() == name) else {
+                warn!("Ignoring unknown key action: {}", name);
+                continue;
+            };
+
+            let parsed: Result<Vec<KeyCode>, String> =
+                spec.names().iter().map(|key| parse_key(key)).collect();
+            match parsed {
+                Ok(codes) if !codes.is_empty() => {
+                    if let Some((_, bound)) = keymap.bindings.iter_mut().find(|(a, _)| *a == action)
+                    {
+                        *bound = codes;
+                    }
+                }
+                Ok(_) => warn!("No keys given for {}, keeping the default", name),
+                Err(e) => warn!("{} for {}, keeping the default", e, name),
+            }
+        }
+
+        keymap
+    }
+
+    /// The action bound to `code`, if any.
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, codes)| codes.contains(&code))
+            .map(|(action, _)| *action)
+    }
+}
+
+/// Parse a key name: a single character (case-sensitive) or a named key such
+/// as `Enter`, `Esc`, `Up`, `PageDown`, `Space` or `F5` (case-insensitive).
+fn parse_key(name: &str) -> Result<KeyCode, String> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+
+    let code = match name.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" | "pgup" => KeyCode::PageUp,
+        "pagedown" | "pgdn" => KeyCode::PageDown,
+        "space" => KeyCode::Char(' '),
+        lower => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            Some(n @ 1..=12) => KeyCode::F(n),
+            _ => return Err(format!("Invalid key name {:?}", name)),
+        },
+    };
+    Ok(code)
+}
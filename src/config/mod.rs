@@ -0,0 +1,7 @@
+pub mod keymap;
+pub mod settings;
+pub mod theme;
+
+pub use keymap::*;
+pub use settings::*;
+pub use theme::*;
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use log::warn;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::config::{KeySpec, Keymap, Theme};
+use crate::utils::CONFIG_FILE;
+
+// ============================================================================
+// USER CONFIGURATION
+// ============================================================================
+
+/// User settings loaded from `~/.config/clipboard-manager/config.toml`.
+/// Every field is optional in the file; missing fields use the defaults below.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Clipboard content advertising any of these MIME types is never stored
+    /// (password managers mark their copies this way).
+    pub sensitive_mime_types: Vec<String>,
+    /// Text matching this regex is never stored. Useful on backends that
+    /// don't expose MIME types (arboard/X11).
+    pub sensitive_pattern: Option<String>,
+    /// Compiled form of `sensitive_pattern`
+    #[serde(skip)]
+    pub sensitive_regex: Option<Regex>,
+    /// Also record the middle-click PRIMARY selection
+    pub capture_primary: bool,
+    /// Restore the captured HTML (instead of plain text) when selecting a rich entry.
+    /// Off by default: wl-copy/xclip can't offer a plain-text fallback alongside it.
+    pub restore_rich_text: bool,
+    /// Remove non-pinned entries older than this many seconds (unset keeps them forever)
+    pub entry_ttl_secs: Option<i64>,
+    /// Text entries larger than this many bytes are truncated (or skipped)
+    pub max_entry_bytes: usize,
+    /// Truncate oversized text to `max_entry_bytes` instead of skipping it
+    pub truncate_large: bool,
+    /// Images larger than this many bytes are not stored
+    pub max_image_bytes: u64,
+    /// Images with more pixels than this (width × height, read from the
+    /// header) are not stored, so a huge image can't exhaust memory decoding
+    pub max_image_pixels: u64,
+    /// MIME types stored as raw data when offered (e.g. "text/csv"), for
+    /// content that isn't an image or plain text. Restoring offers the data
+    /// under the same type
+    pub binary_mime_types: Vec<String>,
+    /// Binary data larger than this many bytes is not stored
+    pub max_binary_bytes: u64,
+    /// Images wider or taller than this are downscaled (keeping the aspect
+    /// ratio) before being stored; unset stores them at full size
+    pub image_max_dimension: Option<u32>,
+    /// Re-encode stored PNGs losslessly; 0-100, higher compresses harder
+    pub image_reencode_quality: Option<u8>,
+    /// The UI warns once the history (images plus the history file) takes
+    /// more than this many bytes on disk, and X frees space down to it
+    pub disk_warn_bytes: Option<u64>,
+    /// A polled clipboard value is stored only after it stays unchanged for
+    /// this many consecutive polls (1 stores it immediately)
+    pub debounce_polls: u32,
+    /// Strip leading/trailing whitespace from text before storing it
+    pub trim_on_store: bool,
+    /// Treat text that differs only in whitespace as a duplicate (the first
+    /// copy's exact text is replaced by the newest one). Entries already in
+    /// the history keep the hash they were stored with
+    pub dedup_normalized: bool,
+    /// Append every captured entry to this file as a JSON line. It is never
+    /// read or rotated, so it keeps what falls out of the capped history
+    pub archive_log: Option<PathBuf>,
+    /// Reopen the UI on the entry that was selected when it was last closed
+    pub remember_cursor: bool,
+    /// Ask for confirmation before deleting entries
+    pub confirm_delete: bool,
+    /// Clearing the history (C, `--clear`) keeps pinned entries
+    pub clear_keeps_pinned: bool,
+    /// Empty the stash once it has been pasted with Y or `--paste-stash`
+    pub clear_stash_on_paste: bool,
+    /// Deleting the entry that is still on the clipboard also empties the
+    /// clipboard (daemon only)
+    pub clear_clipboard_on_delete: bool,
+    /// Show a preview pane in terminals without Kitty graphics, where image
+    /// entries are drawn in braille characters
+    pub preview: bool,
+    /// Lines of text shown for each entry in the list
+    pub preview_max_lines: usize,
+    /// One line per entry in the list (icon, text and time); `v` toggles it
+    pub compact_list: bool,
+    /// Up/down past the end of the list wraps around (false stops at the ends)
+    pub wrap_navigation: bool,
+    /// Record the focused window's class with each entry (Hyprland only)
+    pub track_source: bool,
+    /// Run the daemon without the banner, setup hints or heartbeat (for
+    /// systemd and other service managers); same as `--daemon-quiet`
+    pub daemon_quiet: bool,
+    /// Command that opens the picker in a terminal, written into trigger.sh
+    /// instead of the built-in list; `{binary}`, `{args}` and `{class}` are
+    /// filled in
+    pub terminal_command: Option<String>,
+    /// Address for the read-only HTTP endpoint, e.g. "127.0.0.1:8787"
+    /// (needs the `http` feature; unset = off)
+    pub http_bind: Option<String>,
+    /// Required as `Authorization: Bearer <token>` by the HTTP endpoint
+    pub http_token: Option<String>,
+    /// Key overrides from the `[keys]` table, by action name
+    pub keys: HashMap<String, KeySpec>,
+    /// Compiled form of `keys`, with defaults for actions not overridden
+    #[serde(skip)]
+    pub keymap: Keymap,
+    /// Color overrides from the `[theme]` table, by field name
+    #[serde(rename = "theme")]
+    pub theme_colors: HashMap<String, String>,
+    /// Compiled form of `theme_colors`
+    #[serde(skip)]
+    pub theme: Theme,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            sensitive_mime_types: vec![
+                "x-kde-passwordManagerHint".to_string(),
+                "application/x-nspasteboard-concealed-type".to_string(),
+            ],
+            sensitive_pattern: None,
+            sensitive_regex: None,
+            capture_primary: false,
+            restore_rich_text: false,
+            entry_ttl_secs: None,
+            max_entry_bytes: 1024 * 1024,
+            truncate_large: true,
+            max_image_bytes: 20 * 1024 * 1024,
+            max_image_pixels: 50_000_000,
+            binary_mime_types: Vec::new(),
+            max_binary_bytes: 20 * 1024 * 1024,
+            image_max_dimension: None,
+            image_reencode_quality: None,
+            disk_warn_bytes: None,
+            debounce_polls: 2,
+            trim_on_store: true,
+            dedup_normalized: false,
+            archive_log: None,
+            remember_cursor: false,
+            confirm_delete: false,
+            clear_keeps_pinned: true,
+            clear_stash_on_paste: true,
+            clear_clipboard_on_delete: false,
+            preview: false,
+            preview_max_lines: 3,
+            compact_list: false,
+            wrap_navigation: true,
+            track_source: false,
+            daemon_quiet: false,
+            terminal_command: None,
+            http_bind: None,
+            http_token: None,
+            keys: HashMap::new(),
+            keymap: Keymap::default(),
+            theme_colors: HashMap::new(),
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Load the config file, falling back to defaults if it is missing or invalid.
+    pub fn load() -> Self {
+        let path = Self::path();
+        let mut config = match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str::<Config>(&content).unwrap_or_else(|e| {
+                warn!("Invalid config {}: {}", path.display(), e);
+                Config::default()
+            }),
+            Err(_) => Config::default(),
+        };
+
+        if let Some(pattern) = &config.sensitive_pattern {
+            match Regex::new(pattern) {
+                Ok(re) => config.sensitive_regex = Some(re),
+                Err(e) => warn!("Ignoring invalid sensitive_pattern: {}", e),
+            }
+        }
+        config.keymap = Keymap::from_config(&config.keys);
+        config.theme = Theme::from_config(&config.theme_colors);
+
+        config
+    }
+
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("clipboard-manager")
+            .join(CONFIG_FILE)
+    }
+
+    /// Returns true if any of the offered MIME types marks the content as sensitive.
+    pub fn has_sensitive_type(&self, types: &[String]) -> bool {
+        types
+            .iter()
+            .any(|t| self.sensitive_mime_types.iter().any(|s| s == t))
+    }
+
+    /// Returns true if a `width` × `height` image is over `max_image_pixels`.
+    pub fn exceeds_pixel_budget(&self, width: u32, height: u32) -> bool {
+        u64::from(width) * u64::from(height) > self.max_image_pixels
+    }
+
+    /// Returns true if the text matches the user's `sensitive_pattern`.
+    pub fn matches_sensitive_pattern(&self, text: &str) -> bool {
+        self.sensitive_regex
+            .as_ref()
+            .is_some_and(|re| re.is_match(text))
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Global configuration, loaded from disk on first access. Unit tests get
+/// the defaults, so the user's own config can't change their outcome.
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        if cfg!(test) {
+            Config::default()
+        } else {
+            Config::load()
+        }
+    })
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use log::warn;
+use ratatui::style::Color;
+
+// ============================================================================
+// THEME
+// ============================================================================
+
+/// Colors used by the TUI. The defaults reproduce the built-in look.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Borders of the list, preview and detail panes
+    pub border: Color,
+    /// Header titles and key hints
+    pub title: Color,
+    /// Entry previews and body text
+    pub text: Color,
+    /// Footer labels, counters and modal messages
+    pub emphasis: Color,
+    /// Foreground of the selected entry
+    pub highlight_fg: Color,
+    /// Background of the selected entry
+    pub highlight_bg: Color,
+    /// Entry metadata, separators and hints
+    pub meta: Color,
+    /// Search input, matched characters and secret entries
+    pub accent: Color,
+    /// Errors, "no matches" and the clear confirmation
+    pub error: Color,
+    /// Emoji picker borders and keys
+    pub picker: Color,
+    /// Set by `Theme::plain`: selections are shown with reverse video since
+    /// there are no background colors
+    pub monochrome: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            border: Color::Cyan,
+            title: Color::Cyan,
+            text: Color::Gray,
+            emphasis: Color::White,
+            highlight_fg: Color::White,
+            highlight_bg: Color::Reset,
+            meta: Color::DarkGray,
+            accent: Color::Yellow,
+            error: Color::Red,
+            picker: Color::Magenta,
+            monochrome: false,
+        }
+    }
+}
+
+impl Theme {
+    /// Terminal default colors everywhere, for `NO_COLOR` / `--no-color`.
+    pub fn plain() -> Self {
+        Self {
+            border: Color::Reset,
+            title: Color::Reset,
+            text: Color::Reset,
+            emphasis: Color::Reset,
+            highlight_fg: Color::Reset,
+            highlight_bg: Color::Reset,
+            meta: Color::Reset,
+            accent: Color::Reset,
+            error: Color::Reset,
+            picker: Color::Reset,
+            monochrome: true,
+        }
+    }
+
+    /// Build a theme from the `[theme]` table. Values are color names
+    /// (`"cyan"`, `"light-blue"`), hex strings (`"#89b4fa"`) or 256-color
+    /// indexes; unknown fields and invalid colors warn and keep the default.
+    pub fn from_config(colors: &HashMap<String, String>) -> Self {
+        let mut theme = Self::default();
+
+        for (name, value) in colors {
+            let color = match Color::from_str(value) {
+                Ok(color) => color,
+                Err(_) => {
+                    warn!(
+                        "Invalid color {:?} for theme.{}, keeping the default",
+                        value, name
+                    );
+                    continue;
+                }
+            };
+            let slot = match name.as_str() {
+                "border" => &mut theme.border,
+                "title" => &mut theme.title,
+                "text" => &mut theme.text,
+                "emphasis" => &mut theme.emphasis,
+                "highlight_fg" => &mut theme.highlight_fg,
+                "highlight_bg" => &mut theme.highlight_bg,
+                "meta" => &mut theme.meta,
+                "accent" => &mut theme.accent,
+                "error" => &mut theme.error,
+                "picker" => &mut theme.picker,
+                _ => {
+                    warn!("Ignoring unknown theme color: {}", name);
+                    continue;
+                }
+            };
+            *slot = color;
+        }
+
+        theme
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+
+use crate::history::{ClipboardHistory, HistoryHeader};
+use crate::models::{ClipboardContentType, ClipboardEntry};
+use crate::utils::{HISTORY_FILE, HISTORY_VERSION};
+
+// ============================================================================
+// EXPORT / IMPORT ARCHIVE
+// ============================================================================
+//
+// A `.tar.gz` holding `clipboard_history.jsonl` plus `images/<file>` and
+// `blobs/<file>` for every image and binary entry, so a history can be moved
+// between machines.
+
+/// Write the history and its files to `path`.
+/// Returns the number of (entries, files) exported.
+pub fn export_archive(history: &ClipboardHistory, path: &Path) -> Result<(usize, usize), String> {
+    let history_path = history.data_dir().join(HISTORY_FILE);
+    if !history_path.exists() {
+        return Err("No history to export".to_string());
+    }
+
+    let file = fs::File::create(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    builder
+        .append_path_with_name(&history_path, HISTORY_FILE)
+        .map_err(|e| format!("Failed to archive history: {}", e))?;
+
+    let entries = history.get_all();
+    let mut files = 0;
+    for entry in &entries {
+        let (Some(dir), Some(path)) = (entry.file_dir(), history.entry_file(entry)) else {
+            continue;
+        };
+        if !path.exists() {
+            continue;
+        }
+        builder
+            .append_path_with_name(&path, format!("{}/{}", dir, entry.content))
+            .map_err(|e| format!("Failed to archive {}: {}", entry.content, e))?;
+        files += 1;
+    }
+
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    Ok((entries.len(), files))
+}
+
+/// Merge the history in the archive at `path` into `history`, copying image
+/// and binary files into the local data directory. Entries whose file is
+/// missing from the archive are skipped. Returns the number of (entries,
+/// files) imported.
+pub fn import_archive(history: &ClipboardHistory, path: &Path) -> Result<(usize, usize), String> {
+    let file =
+        fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+
+    let mut history_content = None;
+    // Keyed by path in the archive, e.g. "images/img_1.png"
+    let mut stored_files: HashMap<String, Vec<u8>> = HashMap::new();
+
+    let archive_entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+    for archive_entry in archive_entries {
+        let mut archive_entry =
+            archive_entry.map_err(|e| format!("Failed to read archive: {}", e))?;
+        let name = archive_entry
+            .path()
+            .map_err(|e| format!("Invalid path in archive: {}", e))?
+            .to_string_lossy()
+            .into_owned();
+
+        if name == HISTORY_FILE {
+            let mut content = String::new();
+            archive_entry
+                .read_to_string(&mut content)
+                .map_err(|e| format!("Failed to read {}: {}", HISTORY_FILE, e))?;
+            history_content = Some(content);
+        } else if name
+            .split_once('/')
+            .is_some_and(|(_, f)| !f.is_empty() && !f.contains('/') && !f.starts_with('.'))
+        {
+            let mut data = Vec::new();
+            archive_entry
+                .read_to_end(&mut data)
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+            stored_files.insert(name, data);
+        }
+    }
+
+    let history_content =
+        history_content.ok_or_else(|| format!("{} not found in archive", HISTORY_FILE))?;
+
+    // Hashes from an older format don't compare with ours
+    let version = history_content
+        .lines()
+        .next()
+        .and_then(|line| serde_json::from_str::<HistoryHeader>(line).ok())
+        .map_or(0, |header| header.version);
+
+    let mut imported = Vec::new();
+    let mut files = 0;
+    for line in history_content.lines().filter(|l| !l.trim().is_empty()) {
+        let Ok(mut entry) = serde_json::from_str::<ClipboardEntry>(line) else {
+            continue;
+        };
+
+        if let Some(dir) = entry.file_dir() {
+            let Some(data) = stored_files.get(&format!("{}/{}", dir, entry.content)) else {
+                continue;
+            };
+            let Some(mut path) = history.entry_file(&entry) else {
+                continue;
+            };
+            match fs::read(&path) {
+                Ok(existing) if existing == *data => {}
+                Ok(_) => {
+                    // A different file already uses this name locally
+                    let prefix = if entry.content_type == ClipboardContentType::Image {
+                        "img"
+                    } else {
+                        "blob"
+                    };
+                    let extension = Path::new(&entry.content)
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .unwrap_or("bin");
+                    entry.content =
+                        format!("{}_{}.{}", prefix, ClipboardEntry::next_id(), extension);
+                    path = history.entry_file(&entry).unwrap_or(path);
+                    fs::write(&path, data)
+                        .map_err(|e| format!("Failed to save {}: {}", entry.content, e))?;
+                    files += 1;
+                }
+                Err(_) => {
+                    fs::write(&path, data)
+                        .map_err(|e| format!("Failed to save {}: {}", entry.content, e))?;
+                    files += 1;
+                }
+            }
+            if version < HISTORY_VERSION {
+                entry.rehash_file(Some(data));
+            }
+        }
+
+        entry.ensure_dedup_hash();
+        entry.detect_kind();
+        imported.push(entry);
+    }
+
+    let added = history.merge_entries(imported)?;
+    Ok((added, files))
+}
@@ -1,68 +1,410 @@
-use std::collections::VecDeque;
+use std::cell::Cell;
+use std::collections::{HashSet, VecDeque};
+use std::env;
 use std::fs::{self, OpenOptions};
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-
-use crate::models::{ClipboardContentType, ClipboardEntry, ImageInfo};
-use crate::utils::{HISTORY_FILE, IMAGES_DIR, MAX_HISTORY, format_size};
+use std::io::{BufRead, BufReader, Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config::config;
+use crate::history::shrink_image;
+use crate::ipc::ControlClient;
+use crate::models::{BinaryInfo, ClipboardContentType, ClipboardEntry, ImageInfo, Selection};
+use crate::monitor::is_paused;
+use crate::utils::{
+    BLOBS_DIR, DATA_DIR_ENV_VAR, ECHO_WINDOW_MS, HISTORY_FILE, HISTORY_LOCK_FILE, HISTORY_VERSION,
+    IMAGES_DIR, MAX_HISTORY, MAX_UNDO, NOTHING_TO_UNDO, SAVE_DEBOUNCE_MS, TOMBSTONE_SECS,
+    format_size, hash_bytes, notify_desktop,
+};
 use chrono::Utc;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// DATA DIRECTORY
+// ============================================================================
+
+/// Directory given with `--data-dir` or `CLIPBOARD_MANAGER_DATA_DIR`
+static CUSTOM_DATA_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Keep everything in `dir` (`--data-dir`). Must be called before the
+/// first `ClipboardHistory::new`.
+pub fn set_custom_data_dir(dir: PathBuf) {
+    // Absolute, so trigger.sh and the systemd unit work from anywhere
+    let dir = std::path::absolute(&dir).unwrap_or(dir);
+    let _ = CUSTOM_DATA_DIR.set(Some(dir));
+}
+
+/// The data dir the user chose, if any (`--data-dir`, then the environment)
+pub fn custom_data_dir() -> Option<&'static Path> {
+    CUSTOM_DATA_DIR
+        .get_or_init(|| {
+            env::var_os(DATA_DIR_ENV_VAR)
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| std::path::absolute(&dir).unwrap_or_else(|_| PathBuf::from(dir)))
+        })
+        .as_deref()
+}
+
+/// Where the history, images, PID file and trigger script live. Without a
+/// custom dir this is the platform's data dir; when there is none (e.g. no
+/// home directory) `$XDG_DATA_HOME`, `~/.local/share` or the temp dir is used
+/// instead, with a warning.
+pub fn data_dir() -> &'static Path {
+    DATA_DIR.get_or_init(|| {
+        if let Some(dir) = custom_data_dir() {
+            return dir.to_path_buf();
+        }
+        if let Some(dir) = dirs::data_dir() {
+            return dir.join("clipboard-manager");
+        }
+        let non_empty = |name: &str| env::var_os(name).filter(|value| !value.is_empty());
+        let fallback = non_empty("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| non_empty("HOME").map(|home| Path::new(&home).join(".local/share")))
+            .unwrap_or_else(env::temp_dir)
+            .join("clipboard-manager");
+        warn!(
+            "No data directory found for this user; using {} (pass --data-dir to choose one)",
+            fallback.display()
+        );
+        fallback
+    })
+}
 
 // ============================================================================
 // CLIPBOARD HISTORY MANAGER
 // ============================================================================
 
+/// Set once the user has been notified that images can't be stored, so a
+/// full or read-only disk produces a single notification per run.
+static STORAGE_ERROR_NOTIFIED: AtomicBool = AtomicBool::new(false);
+
+/// Set once a failed write to `archive_log` has been reported
+static ARCHIVE_LOG_ERROR_LOGGED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Number of `HistoryLock`s held by this thread, so nested mutators
+    /// (e.g. `reload` rewriting from inside `add_text`) don't lock twice
+    static LOCK_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Exclusive advisory lock on `HISTORY_LOCK_FILE`, released on drop.
+/// Only the outermost guard on a thread holds the file.
+struct HistoryLock {
+    _file: Option<fs::File>,
+}
+
+impl Drop for HistoryLock {
+    fn drop(&mut self) {
+        LOCK_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// A deleted entry kept around so the delete can be undone.
+struct DeletedEntry {
+    entry: ClipboardEntry,
+    /// Position in the internal (newest-first) deque at the time of deletion
+    position: usize,
+    /// Contents of the stored file, for image and binary entries
+    file_data: Option<Vec<u8>>,
+}
+
+/// First line of the history file; the entries follow, one per line
+#[derive(Serialize, Deserialize)]
+pub(crate) struct HistoryHeader {
+    pub(crate) version: u32,
+}
+
+/// The clipboard history: entries in memory, the history file and the
+/// image and binary files in the data directory. Edits return an error when
+/// the file can't be written or the daemon rejects them.
 pub struct ClipboardHistory {
     entries: Arc<Mutex<VecDeque<ClipboardEntry>>>,
     data_dir: PathBuf,
     images_dir: PathBuf,
+    blobs_dir: PathBuf,
+    /// In-memory undo stack of recent deletes (not persisted)
+    deleted: Mutex<Vec<DeletedEntry>>,
+    /// Hashes of recently deleted entries with the time of the delete, so the
+    /// content still on the clipboard isn't captured again right away
+    tombstones: Mutex<Vec<(u64, Instant)>>,
+    /// Hashes of entries just put back on the clipboard by the picker, with
+    /// the time, so the monitors don't record the copy as a new one
+    echoes: Mutex<Vec<(u64, Instant)>>,
+    /// Set in UI/CLI processes when a daemon is running; edits go through it
+    control: Option<ControlClient>,
+    /// Wakes the daemon's save worker; without one, captures are written
+    /// straight away
+    save_worker: OnceLock<Sender<()>>,
+    /// Captures in memory that the save worker hasn't written yet
+    dirty: AtomicBool,
+    /// Never write the history, images or archive log (`--read-only`)
+    read_only: bool,
+    /// The history file was written by a newer version, so it is never
+    /// rewritten (which would drop what this version doesn't understand)
+    newer_format: AtomicBool,
+    /// Modification time and size of the history file at the last reload
+    loaded_stamp: Mutex<Option<(SystemTime, u64)>>,
 }
 
 impl ClipboardHistory {
+    /// Load the history from `data_dir()`. Edits write the file directly
+    /// until `with_control_socket` routes them through a running daemon.
     pub fn new() -> Self {
-        let data_dir = dirs::data_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("clipboard-manager");
+        Self::with_data_dir(data_dir().to_path_buf())
+    }
 
+    /// Load the history kept in `data_dir` instead of the global one.
+    pub fn with_data_dir(data_dir: PathBuf) -> Self {
         let images_dir = data_dir.join(IMAGES_DIR);
+        let blobs_dir = data_dir.join(BLOBS_DIR);
 
         fs::create_dir_all(&data_dir).ok();
         fs::create_dir_all(&images_dir).ok();
+        fs::create_dir_all(&blobs_dir).ok();
 
         let history = Self {
             entries: Arc::new(Mutex::new(VecDeque::with_capacity(MAX_HISTORY))),
             data_dir,
             images_dir,
+            blobs_dir,
+            deleted: Mutex::new(Vec::new()),
+            tombstones: Mutex::new(Vec::new()),
+            echoes: Mutex::new(Vec::new()),
+            control: None,
+            save_worker: OnceLock::new(),
+            dirty: AtomicBool::new(false),
+            read_only: false,
+            newer_format: AtomicBool::new(false),
+            loaded_stamp: Mutex::new(None),
         };
 
         history.reload();
         history
     }
 
+    /// Route edits (delete, pin, clear, ...) through the running daemon's
+    /// control socket, so only one process writes the history file.
+    /// Without a daemon, edits keep going straight to the file.
+    pub fn with_control_socket(mut self) -> Self {
+        self.control = ControlClient::connect(&self.data_dir);
+        // A read-only daemon's captures only exist in its memory
+        let daemon_read_only = self
+            .control
+            .as_ref()
+            .and_then(|control| control.request("MODE"))
+            .and_then(Result::ok)
+            .is_some_and(|reply| reply.iter().any(|line| line == "read-only"));
+        if daemon_read_only {
+            self.read_only = true;
+            self.reload();
+        }
+        self
+    }
+
+    /// Keep everything in memory: the history file, images and archive log
+    /// are never written or deleted. Captures last until the process exits.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Whether nothing is written to disk (`--read-only`, or a read-only daemon)
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Write captures from a background thread, so a slow disk doesn't hold
+    /// up the monitors. Bursts are coalesced into one rewrite once no new
+    /// capture has arrived for `SAVE_DEBOUNCE_MS`. Call `flush` on shutdown.
+    pub fn start_save_worker(self: &Arc<Self>) {
+        let (tx, rx) = mpsc::channel::<()>();
+        if self.save_worker.set(tx).is_err() {
+            return;
+        }
+        let history: Weak<Self> = Arc::downgrade(self);
+        thread::spawn(move || {
+            while rx.recv().is_ok() {
+                loop {
+                    match rx.recv_timeout(Duration::from_millis(SAVE_DEBOUNCE_MS)) {
+                        Ok(()) => continue,
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                let Some(history) = history.upgrade() else {
+                    return;
+                };
+                if let Err(e) = history.flush() {
+                    warn!("Failed to save captures: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Write captures still waiting for the save worker.
+    pub fn flush(&self) -> Result<(), String> {
+        if !self.dirty.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let _lock = self.lock_history();
+        self.rewrite_history()
+    }
+
+    /// Persist a capture: hand it to the save worker when there is one,
+    /// otherwise append it (or rewrite the file when entries were reordered
+    /// or dropped).
+    fn save_capture(&self, entry: &ClipboardEntry, rewrite: bool) -> Result<(), String> {
+        match self.save_worker.get() {
+            Some(worker) => {
+                self.dirty.store(true, Ordering::SeqCst);
+                let _ = worker.send(());
+                Ok(())
+            }
+            None if rewrite => self.rewrite_history(),
+            None => self.append_entry(entry),
+        }
+    }
+
+    /// Send `command` to the daemon, then reload the file it rewrote.
+    /// Returns None when there is no daemon to talk to.
+    fn forward(&self, command: &str) -> Option<Result<Vec<String>, String>> {
+        let result = self.control.as_ref()?.request(command)?;
+        if let Err(e) = &result {
+            debug!("Daemon rejected {}: {}", command, e);
+        }
+        self.reload();
+        Some(result)
+    }
+
+    /// Reload only if the history file changed since the last reload.
+    /// Returns true when it did.
+    pub fn check_and_reload(&self) -> bool {
+        // A read-only daemon's entries are only available over the socket
+        if self.read_only {
+            self.reload();
+            return self.control.is_some();
+        }
+        let stamp = self.history_stamp();
+        if stamp.is_none() || stamp == *self.loaded_stamp.lock().unwrap() {
+            return false;
+        }
+        self.reload();
+        true
+    }
+
+    fn history_stamp(&self) -> Option<(SystemTime, u64)> {
+        let metadata = fs::metadata(self.data_dir.join(HISTORY_FILE)).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    }
+
     /// Reload entries from disk to pick up changes made by other processes (e.g., TUI pinning an entry while daemon is running).
     pub fn reload(&self) {
+        // Unsaved captures are newer than the file; it's re-read once the
+        // save worker has written them
+        if self.dirty.load(Ordering::SeqCst) {
+            return;
+        }
+        // The file is stale in read-only mode: the daemon keeps what it has in
+        // memory, and a client mirrors the daemon
+        if self.read_only {
+            let listed = self
+                .control
+                .as_ref()
+                .and_then(|control| control.request("LIST"))
+                .and_then(Result::ok);
+            if let Some(lines) = listed {
+                let entries = lines
+                    .iter()
+                    .filter_map(|line| serde_json::from_str::<ClipboardEntry>(line).ok())
+                    .map(|mut entry| {
+                        entry.ensure_dedup_hash();
+                        entry.detect_kind();
+                        entry
+                    })
+                    .collect();
+                *self.entries.lock().unwrap() = entries;
+            }
+            return;
+        }
+        // Held across the read so a repair rewrite can't clobber another writer
+        let _lock = self.lock_history();
         let history_path = self.data_dir.join(HISTORY_FILE);
-        let mut loaded_entries: VecDeque<ClipboardEntry> = VecDeque::new();
+        *self.loaded_stamp.lock().unwrap() = self.history_stamp();
+        let (mut loaded_entries, invalid_lines, version) = Self::read_history_file(&history_path);
+        if version > HISTORY_VERSION {
+            if !self.newer_format.swap(true, Ordering::SeqCst) {
+                warn!(
+                    "History file has format {} but this version only knows {}; it won't be written until you upgrade",
+                    version, HISTORY_VERSION
+                );
+            }
+        } else {
+            self.newer_format.store(false, Ordering::SeqCst);
+        }
 
-        if let Ok(file) = fs::File::open(&history_path) {
-            let reader = BufReader::new(file);
-            for line in reader.lines() {
-                if let Ok(line) = line {
-                    if let Ok(mut entry) = serde_json::from_str::<ClipboardEntry>(&line) {
-                        entry.compute_hash();
-
-                        if let Some(pos) = loaded_entries
-                            .iter()
-                            .position(|e| e.content_hash == entry.content_hash)
-                        {
-                            loaded_entries.remove(pos);
-                        }
-
-                        loaded_entries.push_front(entry);
-                    }
+        // A corrupted file (e.g. the daemon was killed mid-write) falls back to the
+        // backup taken before the last rewrite, if that one holds more entries.
+        if invalid_lines > 0 {
+            let (backup_entries, _, _) = Self::read_history_file(&self.backup_path());
+            if backup_entries.len() > loaded_entries.len() {
+                warn!(
+                    "History file is corrupted ({} invalid lines), restoring from backup",
+                    invalid_lines
+                );
+                loaded_entries = backup_entries;
+            }
+        }
+
+        // Older history files have no ids; assign one so the UI can
+        // address entries independently of their display position.
+        let mut assigned_ids = false;
+        for entry in loaded_entries.iter_mut().filter(|e| e.id == 0) {
+            entry.id = ClipboardEntry::next_id();
+            assigned_ids = true;
+        }
+
+        // Older files have no file hashes, or ones from a hasher that
+        // changes between builds; derive them from the stored file so
+        // re-copies dedupe (unless the file was shrunk and no longer matches)
+        let mut assigned_hashes = false;
+        if version < HISTORY_VERSION {
+            for entry in loaded_entries.iter_mut() {
+                let data = self.entry_file(entry).and_then(|path| fs::read(path).ok());
+                if entry.rehash_file(data.as_deref()) {
+                    assigned_hashes = true;
                 }
             }
         }
+        if assigned_hashes {
+            // Copies of one image saved before hashes were stored collapse into
+            // the newest; their extra files are left for `gc_orphans`
+            let pinned: HashSet<u64> = loaded_entries
+                .iter()
+                .filter(|e| e.pinned)
+                .map(|e| e.dedup_hash)
+                .collect();
+            let mut seen = HashSet::new();
+            loaded_entries.retain(|e| seen.insert(e.dedup_hash));
+            for entry in loaded_entries.iter_mut() {
+                entry.pinned |= pinned.contains(&entry.dedup_hash);
+            }
+        }
+
+        // Drop image and binary entries whose file is gone, so the UI never
+        // shows broken rows
+        let before = loaded_entries.len();
+        loaded_entries.retain(|e| self.entry_file(e).is_none_or(|path| path.exists()));
+        let dropped_missing = before - loaded_entries.len();
+        if dropped_missing > 0 {
+            warn!("Dropped {} entries with missing files", dropped_missing);
+        }
 
         while loaded_entries.len() > MAX_HISTORY {
             loaded_entries.pop_back();
@@ -70,31 +412,111 @@ impl ClipboardHistory {
 
         *self.entries.lock().unwrap() = loaded_entries;
 
+        // Persist newly assigned ids so other processes see the same ones, and
+        // give older files the header
+        let upgraded = version < HISTORY_VERSION && history_path.exists();
+        if upgraded {
+            info!(
+                "Upgrading history file from format {} to {}",
+                version, HISTORY_VERSION
+            );
+        }
+        if upgraded || assigned_ids || assigned_hashes || dropped_missing > 0 {
+            if let Err(e) = self.rewrite_history() {
+                warn!("Failed to update the history file: {}", e);
+            }
+        }
+
         // Remove any expired secrets
         self.cleanup_expired();
     }
 
-    pub fn add_text(&self, content: String) {
-        let trimmed_content = content.trim().to_string();
-        if trimmed_content.is_empty() {
-            return;
+    /// Store copied text (and its HTML, if the app offered any). Copies that
+    /// are skipped (paused, empty, sensitive, ...) are not an error.
+    pub fn add_text(
+        &self,
+        content: String,
+        selection: Selection,
+        rich_content: Option<String>,
+    ) -> Result<(), String> {
+        // The monitors still remember what they saw, so resuming doesn't
+        // record what was copied while paused
+        if is_paused() {
+            return Ok(());
+        }
+        // Whitespace-only copies are never worth storing
+        if content.trim().is_empty() {
+            return Ok(());
+        }
+        let mut content = if config().trim_on_store {
+            content.trim().to_string()
+        } else {
+            content
+        };
+
+        let max_bytes = config().max_entry_bytes;
+        let truncated = content.len() > max_bytes;
+        if truncated {
+            if !config().truncate_large {
+                info!(
+                    "Skipped text larger than max_entry_bytes ({})",
+                    format_size(content.len() as u64)
+                );
+                return Ok(());
+            }
+            let mut cut = max_bytes;
+            while !content.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            content.truncate(cut);
+        }
+        // Markup for a truncated (or oversized) copy would no longer match the text
+        let rich_content = rich_content.filter(|html| !truncated && html.len() <= max_bytes);
+
+        if config().matches_sensitive_pattern(&content) {
+            debug!("Skipped text matching sensitive_pattern");
+            return Ok(());
+        }
+
+        let mut entry = ClipboardEntry::new_text(content.clone());
+        if self.is_tombstoned(entry.dedup_hash) {
+            debug!("Skipped text deleted moments ago");
+            return Ok(());
+        }
+        if self.is_echo(entry.dedup_hash) {
+            debug!("Skipped text copied back from the picker");
+            return Ok(());
         }
+        entry.selection = selection;
+        entry.rich_content = rich_content;
+        entry.truncated = truncated;
+        entry.source_app = source_app();
 
         // Reload from disk to pick up any changes made by TUI (e.g., pins)
+        let _lock = self.lock_history();
         self.reload();
 
-        let entry = ClipboardEntry::new_text(trimmed_content.clone());
         let mut entries = self.entries.lock().unwrap();
 
-        // Check for duplicate and remove if exists (move to top behavior)
+        // Check for duplicate and remove if exists (move to top behavior).
+        // Duplicates are matched across selections; text that was ever in the
+        // regular clipboard stays tagged as Clipboard.
         let mut rewrite = false;
         if let Some(pos) = entries
             .iter()
-            .position(|e| e.content_hash == entry.content_hash)
+            .position(|e| e.dedup_hash == entry.dedup_hash)
         {
-            entries.remove(pos);
+            if let Some(existing) = entries.remove(pos) {
+                if existing.selection == Selection::Clipboard {
+                    entry.selection = Selection::Clipboard;
+                }
+                // Re-copying shouldn't unpin or reset the count
+                entry.pinned = existing.pinned;
+                entry.copy_count = existing.copy_count;
+                entry.source_app = entry.source_app.or(existing.source_app);
+            }
             rewrite = true;
-            // println!("  ↻ Moving duplicate text to top");
+            debug!("Moving duplicate text to top");
         }
 
         entries.push_front(entry.clone());
@@ -104,82 +526,364 @@ impl ClipboardHistory {
 
         drop(entries); // unlock before I/O
 
-        println!("✓ Added text ({} chars)", trimmed_content.len());
-        if rewrite {
-            self.rewrite_history();
-        } else {
-            self.append_entry(&entry);
+        info!("Added text ({} chars)", content.len());
+        if !self.read_only {
+            append_archive_log(&entry);
         }
+        self.save_capture(&entry, rewrite)
     }
 
-    pub fn add_image(&self, image_data: Vec<u8>) -> Result<(), String> {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+    /// Store a copied image. `mime_types` are the types the app offered and
+    /// `svg` its SVG representation, if any (kept when within `max_entry_bytes`).
+    pub fn add_image(
+        self: &Arc<Self>,
+        image_data: Vec<u8>,
+        mime_types: Vec<String>,
+        svg: Option<String>,
+    ) -> Result<(), String> {
+        if is_paused() {
+            return Ok(());
+        }
+        if image_data.len() as u64 > config().max_image_bytes {
+            info!(
+                "Skipped image larger than max_image_bytes ({})",
+                format_size(image_data.len() as u64)
+            );
+            return Ok(());
+        }
 
-        let mut hasher = DefaultHasher::new();
-        image_data.hash(&mut hasher);
-        let hash = hasher.finish();
+        let hash = hash_bytes(&image_data);
+        if self.is_tombstoned(hash) {
+            debug!("Skipped image deleted moments ago");
+            return Ok(());
+        }
+        if self.is_echo(hash) {
+            debug!("Skipped image copied back from the picker");
+            return Ok(());
+        }
+        // Asked before taking the lock since it shells out
+        let source = source_app();
+        let svg = svg.filter(|svg| svg.len() <= config().max_entry_bytes);
 
         // Reload from disk to pick up any changes made by TUI (e.g., pins)
+        let _lock = self.lock_history();
         self.reload();
 
         let mut entries = self.entries.lock().unwrap();
 
-        let mut removed_existing = false;
-        // Check for duplicate images (move to top)
-        if let Some(pos) = entries.iter().position(|e| e.content_hash == hash) {
-            let existing_entry = entries.remove(pos).unwrap();
+        // Re-copying an image bumps the existing entry to the top with a fresh
+        // timestamp; the stored file is reused rather than written again
+        if let Some(pos) = entries.iter().position(|e| e.dedup_hash == hash) {
+            let mut existing_entry = entries.remove(pos).unwrap();
+            existing_entry.timestamp = chrono::Utc::now().timestamp();
+            if source.is_some() {
+                existing_entry.source_app = source;
+            }
+            if !mime_types.is_empty() {
+                existing_entry.mime_types = mime_types;
+                existing_entry.svg_content = svg;
+            }
+            if !self.read_only {
+                append_archive_log(&existing_entry);
+            }
+            entries.push_front(existing_entry.clone());
+            drop(entries);
 
-            // Update timestamp to now so it appears as new
-            // Note: We don't change the filename/content, just the metadata timestamp if possible.
-            // Since we append to file, we should probably append this 'renewed' entry.
-            // Ideally we'd update the timestamp in the struct, assuming it has one.
-            // If not, we just move it.
+            info!("Moved existing image to top");
+            return self.save_capture(&existing_entry, true);
+        }
 
-            entries.push_front(existing_entry.clone());
-            removed_existing = true;
+        // Keep the source format so pasted images are byte-identical to what was copied
+        let format = image::guess_format(&image_data)
+            .map_err(|e| format!("Failed to detect image format: {}", e))?;
+        let extension = format.extensions_str().first().copied().unwrap_or("png");
 
-            println!("✓ Moved existing image to top");
+        // Only the header is read for dimensions, so animated GIFs aren't flattened
+        let (width, height) = image::ImageReader::with_format(Cursor::new(&image_data), format)
+            .into_dimensions()
+            .map_err(|e| format!("Failed to load image: {}", e))?;
+        // Checked before anything decodes the pixels (shrinking, thumbnails)
+        if config().exceeds_pixel_budget(width, height) {
+            warn!(
+                "Skipped {}×{} image over max_image_pixels ({})",
+                width,
+                height,
+                config().max_image_pixels
+            );
+            return Ok(());
         }
 
-        let timestamp = chrono::Utc::now().timestamp();
-        let filename = format!("img_{}.png", timestamp);
-        let image_path = self.images_dir.join(&filename);
+        // Downscale / recompress when configured; the hash stays that of the
+        // copied data so re-copying the original is still recognised
+        let original_size = image_data.len() as u64;
+        let (image_data, width, height, original_size_bytes) =
+            match shrink_image(&image_data, format, width, height) {
+                Ok(Some(shrunk)) => (
+                    shrunk.data,
+                    shrunk.width,
+                    shrunk.height,
+                    Some(original_size),
+                ),
+                Ok(None) => (image_data, width, height, None),
+                Err(e) => {
+                    warn!("Storing image unchanged: {}", e);
+                    (image_data, width, height, None)
+                }
+            };
 
-        fs::write(&image_path, &image_data).map_err(|e| format!("Failed to save image: {}", e))?;
+        // Named by id like blobs, since two images can land within the same second
+        let filename = format!("img_{}.{}", ClipboardEntry::next_id(), extension);
+        let image_path = self.images_dir.join(&filename);
 
-        let img = image::load_from_memory(&image_data)
-            .map_err(|e| format!("Failed to load image: {}", e))?;
+        // In read-only mode the entry is listed but has no file to show or paste
+        let written = if self.read_only {
+            Ok(())
+        } else {
+            fs::write(&image_path, &image_data)
+        };
+        if let Err(e) = written {
+            // Don't leave a partial file behind (e.g. when the disk filled up mid-write)
+            let _ = fs::remove_file(&image_path);
+            if !STORAGE_ERROR_NOTIFIED.swap(true, Ordering::Relaxed) {
+                notify_desktop(
+                    "Clipboard images aren't being saved",
+                    &format!("{}: {}", self.images_dir.display(), e),
+                );
+            }
+            return Err(format!("Failed to save image: {}", e));
+        }
 
         let info = ImageInfo {
-            width: img.width(),
-            height: img.height(),
+            width,
+            height,
             size_bytes: image_data.len() as u64,
+            format: Some(extension.to_string()),
+            original_size_bytes,
         };
 
-        let entry = ClipboardEntry::new_image(filename, info, hash);
+        let mut entry = ClipboardEntry::new_image(filename, info, hash);
+        entry.source_app = source;
+        entry.mime_types = mime_types;
+        entry.svg_content = svg;
 
-        println!(
-            "✓ Added image {}×{} ({})",
+        info!(
+            "Added image {}×{} ({})",
             entry.image_info.as_ref().unwrap().width,
             entry.image_info.as_ref().unwrap().height,
             format_size(entry.image_info.as_ref().unwrap().size_bytes)
         );
 
-        if !removed_existing {
-            entries.push_front(entry.clone());
+        if !self.read_only {
+            append_archive_log(&entry);
         }
+        entries.push_front(entry.clone());
 
-        let rewrite = removed_existing || self.cleanup_old_entries(&mut entries);
+        let rewrite = self.cleanup_old_entries(&mut entries);
 
         drop(entries);
 
-        if rewrite {
-            self.rewrite_history();
+        self.save_capture(&entry, rewrite)?;
+
+        #[cfg(feature = "ocr")]
+        crate::history::spawn_ocr(Arc::clone(self), entry.id, image_path);
+
+        Ok(())
+    }
+
+    /// Store clipboard data of a `binary_mime_types` type as a file in the
+    /// blobs directory.
+    pub fn add_binary(&self, data: Vec<u8>, mime: String) -> Result<(), String> {
+        if is_paused() {
+            return Ok(());
+        }
+        if data.len() as u64 > config().max_binary_bytes {
+            info!(
+                "Skipped {} data larger than max_binary_bytes ({})",
+                mime,
+                format_size(data.len() as u64)
+            );
+            return Ok(());
+        }
+
+        let hash = hash_bytes(&data);
+        if self.is_tombstoned(hash) {
+            debug!("Skipped data deleted moments ago");
+            return Ok(());
+        }
+        if self.is_echo(hash) {
+            debug!("Skipped data copied back from the picker");
+            return Ok(());
+        }
+        let source = source_app();
+
+        // Reload from disk to pick up any changes made by TUI (e.g., pins)
+        let _lock = self.lock_history();
+        self.reload();
+
+        let mut entries = self.entries.lock().unwrap();
+
+        // Same as images: a re-copy moves the existing entry and reuses its file
+        if let Some(pos) = entries.iter().position(|e| e.dedup_hash == hash) {
+            let mut existing_entry = entries.remove(pos).unwrap();
+            existing_entry.timestamp = chrono::Utc::now().timestamp();
+            if source.is_some() {
+                existing_entry.source_app = source;
+            }
+            if !self.read_only {
+                append_archive_log(&existing_entry);
+            }
+            entries.push_front(existing_entry.clone());
+            drop(entries);
+
+            info!("Moved existing {} data to top", mime);
+            return self.save_capture(&existing_entry, true);
+        }
+
+        // Named by id, since two copies can land within the same second
+        let filename = format!(
+            "blob_{}.{}",
+            ClipboardEntry::next_id(),
+            blob_extension(&mime)
+        );
+        let path = self.blobs_dir.join(&filename);
+        let written = if self.read_only {
+            Ok(())
         } else {
-            self.append_entry(&entry);
+            fs::write(&path, &data)
+        };
+        if let Err(e) = written {
+            let _ = fs::remove_file(&path);
+            return Err(format!("Failed to save {} data: {}", mime, e));
         }
-        Ok(())
+
+        let info = BinaryInfo {
+            mime,
+            size_bytes: data.len() as u64,
+        };
+        info!(
+            "Added {} data ({})",
+            info.mime,
+            format_size(info.size_bytes)
+        );
+
+        let mut entry = ClipboardEntry::new_binary(filename, info, hash);
+        entry.source_app = source;
+
+        if !self.read_only {
+            append_archive_log(&entry);
+        }
+        entries.push_front(entry.clone());
+
+        let rewrite = self.cleanup_old_entries(&mut entries);
+
+        drop(entries);
+
+        self.save_capture(&entry, rewrite)
+    }
+
+    /// Attach text recognized in an image entry (by the `ocr` feature).
+    #[cfg_attr(not(feature = "ocr"), allow(dead_code))]
+    pub fn set_ocr_text(&self, id: u64, text: String) -> Result<(), String> {
+        // The entry may have been pinned or moved since it was captured
+        let _lock = self.lock_history();
+        self.reload();
+
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.iter_mut().find(|e| e.id == id) else {
+            return Ok(());
+        };
+        debug!(
+            "Recognized {} chars of text in {}",
+            text.chars().count(),
+            entry.content
+        );
+        entry.ocr_text = Some(text);
+        drop(entries);
+        self.rewrite_history()
+    }
+
+    /// Bytes the history takes on disk: the stored images and binary data
+    /// plus the history file.
+    pub fn disk_usage(&self) -> u64 {
+        let files: u64 = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .map(ClipboardEntry::file_bytes)
+            .sum();
+        let history_file = fs::metadata(self.data_dir.join(HISTORY_FILE)).map_or(0, |m| m.len());
+        files + history_file
+    }
+
+    /// Delete the oldest unpinned images and binary entries until the history
+    /// fits in `limit` bytes, then remove orphaned files. Returns how many
+    /// entries were removed.
+    pub fn free_space(&self, limit: u64) -> Result<usize, String> {
+        if let Some(result) = self.forward(&format!("FREE {}", limit)) {
+            return forwarded_count(result);
+        }
+
+        let _lock = self.lock_history();
+        self.reload();
+
+        let mut usage = self.disk_usage();
+        let mut entries = self.entries.lock().unwrap();
+        let mut removed = 0;
+        while usage > limit {
+            let Some(pos) = entries
+                .iter()
+                .rposition(|e| !e.pinned && e.file_dir().is_some())
+            else {
+                break;
+            };
+            let old_entry = entries.remove(pos).unwrap();
+            usage = usage.saturating_sub(old_entry.file_bytes());
+            self.remove_entry_file(&old_entry);
+            removed += 1;
+        }
+        drop(entries);
+
+        if removed > 0 {
+            self.rewrite_history()?;
+            info!("Removed {} old entries to free disk space", removed);
+        }
+        self.gc_orphans();
+        Ok(removed)
+    }
+
+    /// Delete files in the images and blobs directories that no entry
+    /// references, e.g. left behind by a crash during eviction or a
+    /// hand-edited history file. Returns the number of files removed.
+    pub fn gc_orphans(&self) -> usize {
+        if self.read_only {
+            return 0;
+        }
+        let referenced: HashSet<PathBuf> = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|e| self.entry_file(e))
+            .collect();
+
+        let mut removed = 0;
+        for dir in [&self.images_dir, &self.blobs_dir] {
+            let Ok(dir) = fs::read_dir(dir) else {
+                continue;
+            };
+            for file in dir.filter_map(Result::ok) {
+                let path = file.path();
+                if path.is_file() && !referenced.contains(&path) && fs::remove_file(&path).is_ok() {
+                    removed += 1;
+                }
+            }
+        }
+
+        if removed > 0 {
+            info!("Removed {} orphaned files", removed);
+        }
+        removed
     }
 
     fn cleanup_old_entries(&self, entries: &mut VecDeque<ClipboardEntry>) -> bool {
@@ -196,9 +900,7 @@ impl ClipboardHistory {
             if let Some(pos) = entries.iter().rposition(|e| !e.pinned) {
                 let old_entry = entries.remove(pos).unwrap();
                 cleaned = true;
-                if old_entry.content_type == ClipboardContentType::Image {
-                    let _ = fs::remove_file(self.images_dir.join(&old_entry.content));
-                }
+                self.remove_entry_file(&old_entry);
                 to_remove -= 1;
             } else {
                 break;
@@ -207,6 +909,7 @@ impl ClipboardHistory {
         cleaned
     }
 
+    /// All entries in display order: pinned first, then newest first.
     pub fn get_all(&self) -> Vec<ClipboardEntry> {
         let entries = self.entries.lock().unwrap();
         let mut result: Vec<ClipboardEntry> = entries.iter().cloned().collect();
@@ -218,6 +921,7 @@ impl ClipboardHistory {
     /// Remove entries whose secret expiry has passed.
     /// Called automatically during reload() and can be called periodically.
     pub fn cleanup_expired(&self) {
+        let _lock = self.lock_history();
         let mut entries = self.entries.lock().unwrap();
         let now = Utc::now().timestamp();
 
@@ -234,161 +938,542 @@ impl ClipboardHistory {
             return;
         }
 
-        // Collect image filenames of expired entries before removing
-        let expired_images: Vec<String> = entries
-            .iter()
-            .filter(|e| {
-                if let Some(ref info) = e.secret_info {
-                    if let Some(expires_at) = info.expires_at {
-                        return now >= expires_at;
-                    }
-                }
-                false
-            })
-            .filter(|e| e.content_type == ClipboardContentType::Image)
-            .map(|e| e.content.clone())
-            .collect();
-
-        // Remove expired entries
+        // Remove expired entries and their files
         entries.retain(|e| {
-            if let Some(ref info) = e.secret_info {
-                if let Some(expires_at) = info.expires_at {
-                    return now < expires_at;
-                }
+            let expired = e
+                .secret_info
+                .as_ref()
+                .and_then(|info| info.expires_at)
+                .is_some_and(|expires_at| now >= expires_at);
+            if expired {
+                self.remove_entry_file(e);
             }
-            true
+            !expired
         });
 
-        // Clean up image files
-        for filename in &expired_images {
-            let _ = std::fs::remove_file(self.images_dir.join(filename));
+        drop(entries);
+
+        if had_expired {
+            match self.rewrite_history() {
+                Ok(()) => info!("Cleaned up expired secrets"),
+                Err(e) => warn!("Failed to remove expired secrets: {}", e),
+            }
+        }
+    }
+
+    /// Remove non-pinned entries older than `ttl_secs`, deleting their image files.
+    /// The history file is only rewritten if something was removed.
+    pub fn cleanup_stale(&self, ttl_secs: i64) {
+        match self.clear_older_than(Utc::now().timestamp() - ttl_secs) {
+            Ok(0) => {}
+            Ok(removed) => info!("Expired {} old entries", removed),
+            Err(e) => warn!("Failed to expire old entries: {}", e),
+        }
+    }
+
+    /// Delete the non-pinned entries copied before `cutoff_ts` (a Unix
+    /// timestamp), with their image files. Returns the number removed.
+    pub fn clear_older_than(&self, cutoff_ts: i64) -> Result<usize, String> {
+        if let Some(result) = self.forward(&format!("PRUNE {}", cutoff_ts)) {
+            return forwarded_count(result);
         }
 
+        // Reload from disk to ensure we have the latest state
+        let _lock = self.lock_history();
+        self.reload();
+
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+
+        entries.retain(|e| {
+            if e.pinned || e.timestamp >= cutoff_ts {
+                return true;
+            }
+            self.remove_entry_file(e);
+            false
+        });
+
+        let removed = before - entries.len();
         drop(entries);
 
-        if had_expired {
-            self.rewrite_history();
-            println!("✓ Cleaned up expired secrets");
+        if removed > 0 {
+            self.rewrite_history()?;
         }
+        Ok(removed)
     }
 
     /// Stop the auto-expiry timer on a secret entry (makes it permanent).
-    /// `index` is the position in the sorted (pinned-first) view returned by get_all().
-    pub fn stop_expiry(&self, index: usize) {
-        self.reload();
-        let sorted = self.get_all();
-        if index >= sorted.len() {
-            return;
+    pub fn stop_expiry(&self, id: u64) -> Result<(), String> {
+        if let Some(result) = self.forward(&format!("KEEP {}", id)) {
+            return result.map(|_| ());
         }
-        let target_hash = sorted[index].content_hash;
+        let _lock = self.lock_history();
+        self.reload();
 
         let mut entries = self.entries.lock().unwrap();
-        if let Some(entry) = entries.iter_mut().find(|e| e.content_hash == target_hash) {
+        if let Some(entry) = entries.iter_mut().find(|e| e.id == id) {
             if let Some(ref mut info) = entry.secret_info {
                 info.expires_at = None;
             }
         }
         drop(entries);
-        self.rewrite_history();
+        self.rewrite_history()
     }
 
-    pub fn toggle_pin(&self, index: usize) {
-        // Reload from disk to ensure we have the latest state
+    /// Move an entry to the top of its group (pinned or unpinned) with a fresh
+    /// timestamp, without going through the system clipboard.
+    pub fn move_to_front(&self, id: u64) -> Result<(), String> {
+        if let Some(result) = self.forward(&format!("FRONT {}", id)) {
+            return result.map(|_| ());
+        }
+
+        let _lock = self.lock_history();
         self.reload();
-        // `index` is the position in the sorted (pinned-first) view returned by get_all().
-        // We need to find the matching entry in the internal deque by content_hash.
-        let sorted = self.get_all();
-        if index >= sorted.len() {
-            return;
+
+        let mut entries = self.entries.lock().unwrap();
+        let Some(pos) = entries.iter().position(|e| e.id == id) else {
+            return Ok(());
+        };
+        let mut entry = entries.remove(pos).unwrap();
+        entry.timestamp = Utc::now().timestamp();
+        entries.push_front(entry);
+        drop(entries);
+        self.rewrite_history()
+    }
+
+    /// Count a copy of the entry from the UI (for the most-copied sort).
+    pub fn record_copy(&self, id: u64) -> Result<(), String> {
+        if let Some(result) = self.forward(&format!("COUNT {}", id)) {
+            return result.map(|_| ());
         }
-        let target_hash = sorted[index].content_hash;
+
+        let _lock = self.lock_history();
+        self.reload();
 
         let mut entries = self.entries.lock().unwrap();
-        if let Some(entry) = entries.iter_mut().find(|e| e.content_hash == target_hash) {
-            entry.pinned = !entry.pinned;
+        if let Some(entry) = entries.iter_mut().find(|e| e.id == id) {
+            entry.copy_count = entry.copy_count.saturating_add(1);
         }
         drop(entries);
-        self.rewrite_history();
+        self.rewrite_history()
     }
 
-    pub fn clear(&self) {
+    /// Replace the entry's tags.
+    pub fn set_tags(&self, id: u64, tags: Vec<String>) -> Result<(), String> {
+        if let Some(result) = self.forward(&format!("TAGS {} {}", id, tags.join(" "))) {
+            return result.map(|_| ());
+        }
+
+        let _lock = self.lock_history();
+        self.reload();
+
         let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.iter_mut().find(|e| e.id == id) {
+            entry.tags = tags;
+        }
+        drop(entries);
+        self.rewrite_history()
+    }
 
-        // Remove all image files
-        for entry in entries.iter() {
-            if entry.content_type == ClipboardContentType::Image {
-                let _ = fs::remove_file(self.images_dir.join(&entry.content));
-            }
+    /// Pin the entry, or unpin it when it's pinned.
+    pub fn toggle_pin(&self, id: u64) -> Result<(), String> {
+        if let Some(result) = self.forward(&format!("PIN {}", id)) {
+            return result.map(|_| ());
         }
 
-        entries.clear();
+        // Reload from disk to ensure we have the latest state
+        let _lock = self.lock_history();
+        self.reload();
+
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.iter_mut().find(|e| e.id == id) {
+            entry.pinned = !entry.pinned;
+        }
         drop(entries);
+        self.rewrite_history()
+    }
 
-        // Truncate file
-        let history_path = self.data_dir.join(HISTORY_FILE);
-        let _ = fs::File::create(history_path); // Create truncates
+    /// Delete every entry, except pinned ones when `clear_keeps_pinned` is
+    /// set. Returns how many were removed.
+    pub fn clear(&self) -> Result<usize, String> {
+        self.clear_entries("CLEAR", config().clear_keeps_pinned)
+    }
 
-        println!("✓ Cleared all history");
+    /// Delete every entry, pinned ones included. Returns how many were removed.
+    pub fn clear_all(&self) -> Result<usize, String> {
+        self.clear_entries("CLEAR ALL", false)
     }
 
-    fn append_entry(&self, entry: &ClipboardEntry) {
+    fn clear_entries(&self, command: &str, keep_pinned: bool) -> Result<usize, String> {
+        if let Some(result) = self.forward(command) {
+            return forwarded_count(result);
+        }
+
+        // Reload so images of entries added by another process are removed too
+        let _lock = self.lock_history();
+        self.reload();
+        // Keep the files of a history this version can't write
+        self.check_writable()?;
+
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|e| {
+            if keep_pinned && e.pinned {
+                return true;
+            }
+            self.remove_entry_file(e);
+            false
+        });
+        let removed = before - entries.len();
+        drop(entries);
+
+        self.rewrite_history()?;
+        // The backup holds what was just cleared; corruption must not bring it back
+        let _ = fs::remove_file(self.backup_path());
+
+        info!("Cleared {} entries", removed);
+        Ok(removed)
+    }
+
+    fn append_entry(&self, entry: &ClipboardEntry) -> Result<(), String> {
+        if !self.check_writable()? {
+            return Ok(());
+        }
         let history_path = self.data_dir.join(HISTORY_FILE);
-        if let Ok(mut file) = OpenOptions::new()
+        let json = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        OpenOptions::new()
             .create(true)
             .append(true)
-            .open(history_path)
-        {
-            if let Ok(json) = serde_json::to_string(entry) {
-                let _ = writeln!(file, "{}", json);
+            .open(&history_path)
+            .and_then(|mut file| {
+                // A new (or cleared-by-hand) file starts with the header
+                if file.metadata()?.len() == 0 {
+                    writeln!(file, "{}", history_header())?;
+                }
+                writeln!(file, "{}", json)
+            })
+            .map_err(|e| format!("Failed to write {}: {}", history_path.display(), e))
+    }
+
+    /// Add entries from another history (e.g. an imported archive), skipping
+    /// ones already present by hash. Returns how many were added and are still
+    /// there once the oldest past `MAX_HISTORY` are dropped.
+    pub fn merge_entries(&self, imported: Vec<ClipboardEntry>) -> Result<usize, String> {
+        if self.control.is_some() {
+            let json = serde_json::to_string(&imported)
+                .map_err(|e| format!("Failed to serialize entries: {}", e))?;
+            if let Some(result) = self.forward(&format!("MERGE {}", json)) {
+                return forwarded_count(result);
             }
         }
+
+        let _lock = self.lock_history();
+        self.reload();
+
+        let mut entries = self.entries.lock().unwrap();
+        let mut added = Vec::new();
+        for mut entry in imported {
+            if entries.iter().any(|e| e.dedup_hash == entry.dedup_hash) {
+                continue;
+            }
+            if entry.id == 0 || entries.iter().any(|e| e.id == entry.id) {
+                entry.id = ClipboardEntry::next_id();
+            }
+            added.push(entry.id);
+            entries.push_back(entry);
+        }
+
+        // Interleave with the existing entries by copy time, newest first
+        entries
+            .make_contiguous()
+            .sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+        self.cleanup_old_entries(&mut entries);
+        let kept = entries.iter().filter(|e| added.contains(&e.id)).count();
+        drop(entries);
+
+        if !added.is_empty() {
+            self.rewrite_history()?;
+        }
+        Ok(kept)
     }
 
-    // Helper to delete specific entry (used by UI)
-    // `index` is the position in the sorted (pinned-first) view returned by get_all().
-    pub fn delete_entry(&self, index: usize) {
-        // Reload from disk to ensure we have the latest state
+    /// Delete the entry at `index` in display order (as returned by `get_all`).
+    pub fn delete_entry(&self, index: usize) -> Result<ClipboardEntry, String> {
+        // Resolve the index against the current file, not a stale view
         self.reload();
-        let sorted = self.get_all();
-        if index >= sorted.len() {
-            return;
+        let entries = self.get_all();
+        let entry = entries.get(index).ok_or_else(|| {
+            format!(
+                "Index {} out of range ({} entr{})",
+                index,
+                entries.len(),
+                if entries.len() == 1 { "y" } else { "ies" }
+            )
+        })?;
+        self.delete_by_id(entry.id)?;
+        Ok(entry.clone())
+    }
+
+    /// Delete the entry with the given id (used by UI).
+    /// Unlike `delete_entry`, this is independent of any filtering applied to the view.
+    pub fn delete_by_id(&self, id: u64) -> Result<(), String> {
+        if let Some(result) = self.forward(&format!("DELETE {}", id)) {
+            return result.map(|_| ());
         }
-        let target_hash = sorted[index].content_hash;
+
+        // Reload from disk to ensure we have the latest state
+        let _lock = self.lock_history();
+        self.reload();
 
         let mut entries = self.entries.lock().unwrap();
-        if let Some(pos) = entries.iter().position(|e| e.content_hash == target_hash) {
+        if let Some(pos) = entries.iter().position(|e| e.id == id) {
             if let Some(removed) = entries.remove(pos) {
-                if removed.content_type == ClipboardContentType::Image {
-                    let _ = fs::remove_file(self.images_dir.join(&removed.content));
+                self.tombstones
+                    .lock()
+                    .unwrap()
+                    .push((removed.dedup_hash, Instant::now()));
+
+                let file_data = self
+                    .entry_file(&removed)
+                    .and_then(|path| fs::read(path).ok());
+                self.remove_entry_file(&removed);
+
+                let mut deleted = self.deleted.lock().unwrap();
+                if deleted.len() >= MAX_UNDO {
+                    deleted.remove(0);
                 }
+                deleted.push(DeletedEntry {
+                    entry: removed,
+                    position: pos,
+                    file_data,
+                });
             }
         }
 
         drop(entries);
         // Rewriting the file is necessary when deleting from middle, sadly.
         // But deletes are rare compared to appends.
-        self.rewrite_history();
+        self.rewrite_history()
+    }
+
+    /// Restore the most recently deleted entry at its original position
+    /// (or the front if that position no longer exists).
+    /// Returns false if there is nothing to undo.
+    pub fn undo_delete(&self) -> Result<bool, String> {
+        if let Some(result) = self.forward("UNDO") {
+            return match result {
+                Ok(_) => Ok(true),
+                Err(e) if e == NOTHING_TO_UNDO => Ok(false),
+                Err(e) => Err(e),
+            };
+        }
+
+        let Some(record) = self.deleted.lock().unwrap().pop() else {
+            return Ok(false);
+        };
+        self.tombstones
+            .lock()
+            .unwrap()
+            .retain(|(hash, _)| *hash != record.entry.dedup_hash);
+
+        // Reload from disk to ensure we have the latest state
+        let _lock = self.lock_history();
+        self.reload();
+
+        if let (Some(data), Some(path)) = (&record.file_data, self.entry_file(&record.entry)) {
+            if !path.exists() {
+                let _ = fs::write(&path, data);
+            }
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if !entries.iter().any(|e| e.id == record.entry.id) {
+            if record.position <= entries.len() {
+                entries.insert(record.position, record.entry);
+            } else {
+                entries.push_front(record.entry);
+            }
+        }
+        drop(entries);
+
+        self.rewrite_history()?;
+        Ok(true)
     }
 
-    fn rewrite_history(&self) {
+    /// Parse a history file, returning its entries (newest first), the number of
+    /// lines that failed to parse and its format version (0 without a header).
+    fn read_history_file(path: &Path) -> (VecDeque<ClipboardEntry>, usize, u32) {
+        let mut loaded_entries: VecDeque<ClipboardEntry> = VecDeque::new();
+        let mut invalid_lines = 0;
+        let mut version = 0;
+
+        let Ok(file) = fs::File::open(path) else {
+            return (loaded_entries, invalid_lines, version);
+        };
+
+        let reader = BufReader::new(file);
+        for (index, line) in reader.lines().map_while(Result::ok).enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let header = (index == 0).then(|| serde_json::from_str::<HistoryHeader>(&line));
+            if let Some(Ok(header)) = header {
+                version = header.version;
+                continue;
+            }
+            let Ok(mut entry) = serde_json::from_str::<ClipboardEntry>(&line) else {
+                invalid_lines += 1;
+                continue;
+            };
+            entry.ensure_dedup_hash();
+            entry.detect_kind();
+
+            if let Some(pos) = loaded_entries
+                .iter()
+                .position(|e| e.dedup_hash == entry.dedup_hash)
+            {
+                loaded_entries.remove(pos);
+            }
+
+            loaded_entries.push_front(entry);
+        }
+
+        (loaded_entries, invalid_lines, version)
+    }
+
+    /// Whether an entry with this hash was deleted within `TOMBSTONE_SECS`.
+    /// Expired tombstones are dropped on the way.
+    fn is_tombstoned(&self, hash: u64) -> bool {
+        let mut tombstones = self.tombstones.lock().unwrap();
+        tombstones
+            .retain(|(_, deleted_at)| deleted_at.elapsed() < Duration::from_secs(TOMBSTONE_SECS));
+        tombstones.iter().any(|(h, _)| *h == hash)
+    }
+
+    /// Note that the entry is about to go back on the clipboard, so the daemon
+    /// ignores the copy for `ECHO_WINDOW_MS` instead of recording it as new
+    /// (which would move it to the top with a fresh timestamp).
+    pub fn expect_echo(&self, id: u64) -> Result<(), String> {
+        if let Some(result) = self.forward(&format!("ECHO {}", id)) {
+            return result.map(|_| ());
+        }
         let entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.iter().find(|e| e.id == id) else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let mut echoes = self.echoes.lock().unwrap();
+        echoes.push((entry.dedup_hash, now));
+        // A shrunk image goes back as the stored file, which hashes differently
+        if let Some(data) = self.entry_file(entry).and_then(|path| fs::read(path).ok()) {
+            echoes.push((hash_bytes(&data), now));
+        }
+        Ok(())
+    }
+
+    /// Whether an entry with this hash was put back on the clipboard within
+    /// `ECHO_WINDOW_MS`. Expired echoes are dropped on the way.
+    fn is_echo(&self, hash: u64) -> bool {
+        let mut echoes = self.echoes.lock().unwrap();
+        echoes.retain(|(_, at)| at.elapsed() < Duration::from_millis(ECHO_WINDOW_MS));
+        echoes.iter().any(|(h, _)| *h == hash)
+    }
+
+    /// Serialize writers across processes (daemon, UI, CLI) and threads.
+    /// Mutators take this before re-reading the file, so each one applies its
+    /// change to the latest contents instead of overwriting another writer's.
+    /// If the lock file can't be used, writes go ahead unlocked.
+    fn lock_history(&self) -> HistoryLock {
+        let outermost = LOCK_DEPTH.with(|depth| {
+            depth.set(depth.get() + 1);
+            depth.get() == 1
+        });
+        // Read-only mode doesn't even create the lock file
+        if !outermost || self.read_only {
+            return HistoryLock { _file: None };
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(self.data_dir.join(HISTORY_LOCK_FILE))
+            .and_then(|file| file.lock().map(|_| file));
+        match file {
+            Ok(file) => HistoryLock { _file: Some(file) },
+            Err(e) => {
+                warn!("Failed to lock the history file: {}", e);
+                HistoryLock { _file: None }
+            }
+        }
+    }
+
+    /// The file holding an image or binary entry's data.
+    pub fn entry_file(&self, entry: &ClipboardEntry) -> Option<PathBuf> {
+        entry
+            .file_dir()
+            .map(|dir| self.data_dir.join(dir).join(&entry.content))
+    }
+
+    /// Delete the entry's stored file, if it has one (kept in read-only mode).
+    fn remove_entry_file(&self, entry: &ClipboardEntry) {
+        if let Some(path) = self.entry_file(entry).filter(|_| !self.read_only) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    fn backup_path(&self) -> PathBuf {
+        self.data_dir.join(format!("{}.bak", HISTORY_FILE))
+    }
+
+    /// Whether the history file may be written: false in read-only mode, and
+    /// an error when it has a newer format than this version writes.
+    fn check_writable(&self) -> Result<bool, String> {
+        if self.newer_format.load(Ordering::SeqCst) {
+            return Err(
+                "The history file is from a newer version of clipboard-manager; upgrade to change it"
+                    .to_string(),
+            );
+        }
+        Ok(!self.read_only)
+    }
+
+    fn rewrite_history(&self) -> Result<(), String> {
+        let entries = self.entries.lock().unwrap();
+        // Everything in memory goes out now, pending captures included
+        self.dirty.store(false, Ordering::SeqCst);
+        if !self.check_writable()? {
+            return Ok(());
+        }
         let history_path = self.data_dir.join(HISTORY_FILE);
-        if let Ok(mut file) = fs::File::create(&history_path) {
-            // Write in reverse order (oldest to newest) or keep order?
-            // load() reads line by line and pushes front... wait.
-            // If we write current deque (newest first) to file, then load() reads first line (newest) and pushes front.
-            // So deque becomes [newest, 2nd newest..].
-            // If we append:
-            // File: [Old1, Old2, New3]
-            // Load: reads Old1 -> Entry is [Old1]. reads Old2 -> Entry is [Old2, Old1]. reads New3 -> Entry is [New3, Old2, Old1].
-            // Correct.
-            // So when rewriting, we should write from Oldest to Newest (back to front).
-            for entry in entries.iter().rev() {
-                if let Ok(json) = serde_json::to_string(entry) {
-                    let _ = writeln!(file, "{}", json);
-                }
+        let tmp_path = self.data_dir.join(format!("{}.tmp", HISTORY_FILE));
+        let write_error = |e: std::io::Error| {
+            let _ = fs::remove_file(&tmp_path);
+            format!("Failed to write {}: {}", tmp_path.display(), e)
+        };
+
+        // Write to a temporary file and rename it over the real one, which is atomic
+        // on the same filesystem, so a crash mid-write can't truncate the history.
+        let mut file = fs::File::create(&tmp_path).map_err(write_error)?;
+        writeln!(file, "{}", history_header()).map_err(write_error)?;
+
+        // The file runs oldest to newest so captures can be appended; reload
+        // pushes each line to the front, which restores the newest-first deque
+        for entry in entries.iter().rev() {
+            if let Ok(json) = serde_json::to_string(entry) {
+                writeln!(file, "{}", json).map_err(write_error)?;
             }
         }
+
+        file.sync_all().map_err(write_error)?;
+        drop(file);
+
+        // Keep the previous version around in case the new one turns out corrupted
+        let _ = fs::copy(&history_path, self.backup_path());
+        fs::rename(&tmp_path, &history_path).map_err(|e| {
+            let _ = fs::remove_file(&tmp_path);
+            format!("Failed to replace {}: {}", history_path.display(), e)
+        })
     }
 
     pub fn data_dir(&self) -> &PathBuf {
@@ -398,4 +1483,275 @@ impl ClipboardHistory {
     pub fn images_dir(&self) -> &PathBuf {
         &self.images_dir
     }
+
+    pub fn blobs_dir(&self) -> &PathBuf {
+        &self.blobs_dir
+    }
+}
+
+impl Default for ClipboardHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The header line written at the top of the history file
+fn history_header() -> String {
+    serde_json::to_string(&HistoryHeader {
+        version: HISTORY_VERSION,
+    })
+    .unwrap_or_default()
+}
+
+/// The count the daemon replied with to FREE, PRUNE or CLEAR
+fn forwarded_count(result: Result<Vec<String>, String>) -> Result<usize, String> {
+    let lines = result?;
+    lines
+        .first()
+        .and_then(|line| line.parse().ok())
+        .ok_or_else(|| format!("Unexpected reply from the daemon: {:?}", lines))
+}
+
+/// Append a captured entry to `archive_log`, if one is set. Secrets are left
+/// out since they would outlive their expiry there.
+fn append_archive_log(entry: &ClipboardEntry) {
+    let Some(path) = &config().archive_log else {
+        return;
+    };
+    if entry.is_secret() {
+        return;
+    }
+
+    let kind = match entry.content_type {
+        ClipboardContentType::Text => "text",
+        ClipboardContentType::Image => "image",
+        ClipboardContentType::Binary => "binary",
+    };
+    let line = serde_json::json!({
+        "timestamp": entry.timestamp,
+        "type": kind,
+        "content": entry.content,
+    });
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line));
+    match result {
+        Err(e) if !ARCHIVE_LOG_ERROR_LOGGED.swap(true, Ordering::Relaxed) => {
+            warn!("Failed to write archive log {}: {}", path.display(), e);
+        }
+        _ => {}
+    }
+}
+
+/// Class of the focused window when `track_source` is on. Best-effort:
+/// only Hyprland can be asked, and failures give None.
+fn source_app() -> Option<String> {
+    if !config().track_source {
+        return None;
+    }
+    #[cfg(target_os = "linux")]
+    return crate::monitor::hyprland::active_window_class();
+    #[cfg(not(target_os = "linux"))]
+    return None;
+}
+
+/// File extension for stored data of type `mime`: its subtype when that
+/// reads like one (application/pdf → pdf), "bin" otherwise.
+fn blob_extension(mime: &str) -> &str {
+    let essence = mime.split(';').next().unwrap_or_default();
+    let subtype = essence.rsplit('/').next().unwrap_or_default().trim();
+    let subtype = subtype.strip_prefix("x-").unwrap_or(subtype);
+    if !subtype.is_empty()
+        && subtype.len() <= 8
+        && subtype.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        subtype
+    } else {
+        "bin"
+    }
+}
+
+/// An empty history in its own directory under the system temp dir, for tests.
+#[cfg(test)]
+pub(crate) fn scratch_history(name: &str) -> ClipboardHistory {
+    let dir = env::temp_dir().join(format!("clipboard-manager-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    ClipboardHistory::with_data_dir(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2×2 PNG filled with `shade`, so different shades hash differently
+    fn png(shade: u8) -> Vec<u8> {
+        let image = image::RgbImage::from_pixel(2, 2, image::Rgb([shade; 3]));
+        let mut data = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn recapture_after_reload_is_deduplicated() {
+        let history = Arc::new(scratch_history("dedup-reload"));
+        history
+            .add_text("some text".to_string(), Selection::Clipboard, None)
+            .unwrap();
+        history.add_image(png(10), Vec::new(), None).unwrap();
+        history.flush().unwrap();
+        let count = history.get_all().len();
+        assert_eq!(count, 2);
+
+        let reloaded = Arc::new(ClipboardHistory::with_data_dir(history.data_dir().clone()));
+        reloaded
+            .add_text("some text".to_string(), Selection::Clipboard, None)
+            .unwrap();
+        reloaded.add_image(png(10), Vec::new(), None).unwrap();
+        assert_eq!(reloaded.get_all().len(), count);
+    }
+
+    #[test]
+    fn blobs_copied_in_the_same_second_get_their_own_files() {
+        let history = scratch_history("blob-names");
+        for data in [b"first", b"other"] {
+            history
+                .add_binary(data.to_vec(), "application/pdf".to_string())
+                .unwrap();
+        }
+        let entries = history.get_all();
+        assert_eq!(entries.len(), 2);
+        assert_ne!(entries[0].content, entries[1].content);
+        assert!(entries[0].content.ends_with(".pdf"));
+        assert_eq!(
+            fs::read(history.entry_file(&entries[0]).unwrap()).unwrap(),
+            b"other"
+        );
+        assert_eq!(
+            fs::read(history.entry_file(&entries[1]).unwrap()).unwrap(),
+            b"first"
+        );
+    }
+
+    #[test]
+    fn recopied_text_moves_to_the_top() {
+        let history = scratch_history("reorder-text");
+        for content in ["A", "B", "A"] {
+            history
+                .add_text(content.to_string(), Selection::Clipboard, None)
+                .unwrap();
+        }
+        let contents: Vec<_> = history.get_all().into_iter().map(|e| e.content).collect();
+        assert_eq!(contents, ["A", "B"]);
+    }
+
+    #[test]
+    fn recopied_image_moves_to_the_top() {
+        let history = Arc::new(scratch_history("reorder-image"));
+        for shade in [10, 20, 10] {
+            history.add_image(png(shade), Vec::new(), None).unwrap();
+        }
+        let hashes: Vec<_> = history.get_all().iter().map(|e| e.dedup_hash).collect();
+        assert_eq!(hashes, [hash_bytes(&png(10)), hash_bytes(&png(20))]);
+    }
+
+    #[test]
+    fn hashes_from_an_older_format_are_recomputed() {
+        let dir = scratch_history("rehash").data_dir().clone();
+        let data = png(30);
+        fs::write(dir.join(IMAGES_DIR).join("img_1.png"), &data).unwrap();
+        let info = ImageInfo {
+            width: 2,
+            height: 2,
+            size_bytes: data.len() as u64,
+            format: Some("png".to_string()),
+            original_size_bytes: None,
+        };
+        // Hashes as an older build's `DefaultHasher` might have left them
+        let mut image = ClipboardEntry::new_image("img_1.png".to_string(), info, 1);
+        let mut text = ClipboardEntry::new_text("some text".to_string());
+        text.dedup_hash = 2;
+        image.timestamp -= 1;
+        let lines: Vec<String> = [r#"{"version":1}"#.to_string()]
+            .into_iter()
+            .chain([&image, &text].map(|e| serde_json::to_string(e).unwrap()))
+            .collect();
+        fs::write(dir.join(HISTORY_FILE), lines.join("\n")).unwrap();
+
+        let history = Arc::new(ClipboardHistory::with_data_dir(dir));
+        assert_eq!(history.get_all().len(), 2);
+        history
+            .add_text("some text".to_string(), Selection::Clipboard, None)
+            .unwrap();
+        history.add_image(data, Vec::new(), None).unwrap();
+        assert_eq!(history.get_all().len(), 2);
+    }
+
+    #[test]
+    fn hash_bytes_is_fnv1a() {
+        assert_eq!(hash_bytes(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash_bytes(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn merged_entries_past_the_limit_are_not_counted() {
+        let history = scratch_history("merge-count");
+        history
+            .add_text("local".to_string(), Selection::Clipboard, None)
+            .unwrap();
+        let imported = (0..MAX_HISTORY)
+            .map(|i| {
+                let mut entry = ClipboardEntry::new_text(format!("imported {}", i));
+                // Older than the local entry, so the oldest imports are dropped
+                entry.timestamp -= 1000 + i as i64;
+                entry
+            })
+            .collect();
+        let kept = history.merge_entries(imported).unwrap();
+        assert_eq!(kept, MAX_HISTORY - 1);
+        assert_eq!(history.get_all().len(), MAX_HISTORY);
+        assert_eq!(history.merge_entries(Vec::new()).unwrap(), 0);
+    }
+
+    #[test]
+    fn freeing_space_evicts_the_oldest_binary_entry_too() {
+        let history = Arc::new(scratch_history("free-blobs"));
+        history
+            .add_binary(vec![7; 4096], "application/pdf".to_string())
+            .unwrap();
+        history.add_image(png(60), Vec::new(), None).unwrap();
+        history.flush().unwrap();
+        let blob = history.get_all().pop().unwrap();
+        assert_eq!(blob.content_type, ClipboardContentType::Binary);
+
+        let removed = history.free_space(history.disk_usage() - 1).unwrap();
+        assert_eq!(removed, 1);
+        let entries = history.get_all();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content_type, ClipboardContentType::Image);
+        assert!(!history.entry_file(&blob).unwrap().exists());
+    }
+
+    #[test]
+    fn images_copied_in_the_same_second_get_their_own_files() {
+        let history = Arc::new(scratch_history("image-names"));
+        for shade in [40, 50] {
+            history.add_image(png(shade), Vec::new(), None).unwrap();
+        }
+        let entries = history.get_all();
+        assert_eq!(entries.len(), 2);
+        assert_ne!(entries[0].content, entries[1].content);
+        assert!(entries[0].content.ends_with(".png"));
+        assert_eq!(
+            fs::read(history.entry_file(&entries[0]).unwrap()).unwrap(),
+            png(50)
+        );
+        assert_eq!(
+            fs::read(history.entry_file(&entries[1]).unwrap()).unwrap(),
+            png(40)
+        );
+    }
 }
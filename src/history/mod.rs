@@ -1,3 +1,15 @@
+pub mod archive;
 pub mod manager;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+pub mod shrink;
+pub mod stash;
+pub mod stats;
 
+pub use archive::*;
 pub use manager::*;
+#[cfg(feature = "ocr")]
+pub use ocr::*;
+pub use shrink::*;
+pub use stash::*;
+pub use stats::*;
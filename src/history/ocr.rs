@@ -0,0 +1,64 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use log::{debug, warn};
+
+use crate::history::ClipboardHistory;
+
+// ============================================================================
+// IMAGE TEXT RECOGNITION (cargo feature "ocr")
+// ============================================================================
+
+/// Set once the user has been told tesseract is missing
+static TESSERACT_MISSING_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// Recognize the text in a stored image on a background thread and attach it
+/// to entry `id`, so capture isn't held up by tesseract.
+pub fn spawn_ocr(history: Arc<ClipboardHistory>, id: u64, image_path: PathBuf) {
+    thread::spawn(move || {
+        if let Some(text) = recognize_text(&image_path) {
+            if let Err(e) = history.set_ocr_text(id, text) {
+                warn!("Failed to save recognized text: {}", e);
+            }
+        }
+    });
+}
+
+/// Run `tesseract <image> stdout`. Returns None when it isn't installed,
+/// fails, or finds no text.
+fn recognize_text(image_path: &Path) -> Option<String> {
+    let output = match Command::new("tesseract")
+        .arg(image_path)
+        .arg("stdout")
+        .stderr(Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            if !TESSERACT_MISSING_LOGGED.swap(true, Ordering::Relaxed) {
+                warn!("tesseract is not installed; images won't be searchable by their text");
+            }
+            return None;
+        }
+        Err(e) => {
+            warn!("Failed to run tesseract: {}", e);
+            return None;
+        }
+    };
+
+    if !output.status.success() {
+        debug!(
+            "tesseract exited with {} for {}",
+            output.status,
+            image_path.display()
+        );
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
@@ -0,0 +1,83 @@
+use std::io::Cursor;
+
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::imageops::FilterType as ResizeFilter;
+use image::{DynamicImage, ImageFormat};
+
+use crate::config::config;
+
+// ============================================================================
+// IMAGE SHRINKING (image_max_dimension / image_reencode_quality)
+// ============================================================================
+
+/// An image after downscaling and/or re-encoding.
+pub struct ShrunkImage {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Downscale images larger than `image_max_dimension` and re-encode PNGs
+/// with the compression picked by `image_reencode_quality`.
+///
+/// Returns `Ok(None)` when both settings are off, the format can't be
+/// processed (GIFs are kept as-is so animations survive) or the result
+/// wouldn't be smaller than the original.
+pub fn shrink_image(
+    data: &[u8],
+    format: ImageFormat,
+    width: u32,
+    height: u32,
+) -> Result<Option<ShrunkImage>, String> {
+    let max_dimension = config().image_max_dimension.filter(|&max| max > 0);
+    let quality = config().image_reencode_quality;
+
+    let downscale = max_dimension.is_some_and(|max| width.max(height) > max);
+    let reencode = quality.is_some() && format == ImageFormat::Png;
+    if !(downscale || reencode) || !matches!(format, ImageFormat::Png | ImageFormat::Jpeg) {
+        return Ok(None);
+    }
+
+    let mut image = image::load_from_memory_with_format(data, format)
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    if let Some(max) = max_dimension.filter(|_| downscale) {
+        // `resize` keeps the aspect ratio within the max × max box
+        image = image.resize(max, max, ResizeFilter::Lanczos3);
+    }
+
+    let encoded = encode(&image, format, quality)?;
+    if encoded.len() >= data.len() {
+        return Ok(None);
+    }
+
+    Ok(Some(ShrunkImage {
+        data: encoded,
+        width: image.width(),
+        height: image.height(),
+    }))
+}
+
+fn encode(
+    image: &DynamicImage,
+    format: ImageFormat,
+    quality: Option<u8>,
+) -> Result<Vec<u8>, String> {
+    let mut out = Cursor::new(Vec::new());
+    let result = if format == ImageFormat::Png {
+        // PNG stays lossless; the quality only trades encoding time for size
+        let compression = match quality.unwrap_or(0) {
+            0..=33 => CompressionType::Fast,
+            34..=66 => CompressionType::Default,
+            _ => CompressionType::Best,
+        };
+        image.write_with_encoder(PngEncoder::new_with_quality(
+            &mut out,
+            compression,
+            FilterType::Adaptive,
+        ))
+    } else {
+        image.write_to(&mut out, format)
+    };
+    result.map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(out.into_inner())
+}
@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+
+use crate::models::{ClipboardContentType, ClipboardEntry};
+use crate::utils::STASH_FILE;
+
+// ============================================================================
+// STASH
+// ============================================================================
+
+/// Entries gathered with `y` to be pasted together (`Y` or `--paste-stash`).
+/// Kept as entry ids in `stash.json`, in the order they were stashed.
+pub struct Stash {
+    path: PathBuf,
+    ids: Vec<u64>,
+}
+
+impl Stash {
+    /// Read the stash from `data_dir`; a missing or unreadable file is empty.
+    pub fn load(data_dir: &Path) -> Self {
+        let path = data_dir.join(STASH_FILE);
+        let ids = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self { path, ids }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.ids.contains(&id)
+    }
+
+    /// Add the entry, or take it out when it's already stashed.
+    /// Returns true when it was added.
+    pub fn toggle(&mut self, id: u64) -> bool {
+        let added = match self.ids.iter().position(|&stashed| stashed == id) {
+            Some(index) => {
+                self.ids.remove(index);
+                false
+            }
+            None => {
+                self.ids.push(id);
+                true
+            }
+        };
+        self.save();
+        added
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+        self.save();
+    }
+
+    /// The stashed text entries still in `entries`, in stash order and
+    /// joined with newlines, and how many there were. Deleted entries and
+    /// non-text entries are skipped; None when nothing is left.
+    pub fn text(&self, entries: &[ClipboardEntry]) -> Option<(usize, String)> {
+        let texts: Vec<&str> = self
+            .ids
+            .iter()
+            .filter_map(|id| entries.iter().find(|e| e.id == *id))
+            .filter(|e| e.content_type == ClipboardContentType::Text)
+            .map(|e| e.content.as_str())
+            .collect();
+        (!texts.is_empty()).then(|| (texts.len(), texts.join("\n")))
+    }
+
+    fn save(&self) {
+        if self.ids.is_empty() {
+            let _ = fs::remove_file(&self.path);
+            return;
+        }
+        let result = serde_json::to_string(&self.ids)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&self.path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Failed to save {}: {}", self.path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use serde::Serialize;
+
+use crate::models::{ClipboardContentType, ClipboardEntry};
+use crate::utils::format_size;
+
+// ============================================================================
+// HISTORY STATISTICS
+// ============================================================================
+
+/// Summary of the stored history, printed by `--stats`.
+#[derive(Debug, Serialize)]
+pub struct HistoryStats {
+    pub total_entries: usize,
+    pub text_entries: usize,
+    pub image_entries: usize,
+    pub binary_entries: usize,
+    pub pinned_entries: usize,
+    /// Sum of the stored image sizes
+    pub image_bytes: u64,
+    /// Bytes saved by downscaling / re-encoding images
+    pub image_bytes_saved: u64,
+    /// Average text entry length in characters
+    pub average_text_chars: f64,
+    /// Unix timestamps of the oldest and newest entries
+    pub oldest: Option<i64>,
+    pub newest: Option<i64>,
+}
+
+impl HistoryStats {
+    pub fn from_entries(entries: &[ClipboardEntry]) -> Self {
+        let texts: Vec<&ClipboardEntry> = entries
+            .iter()
+            .filter(|e| e.content_type == ClipboardContentType::Text)
+            .collect();
+        let text_chars: usize = texts.iter().map(|e| e.content.chars().count()).sum();
+
+        Self {
+            total_entries: entries.len(),
+            text_entries: texts.len(),
+            image_entries: entries
+                .iter()
+                .filter(|e| e.content_type == ClipboardContentType::Image)
+                .count(),
+            binary_entries: entries
+                .iter()
+                .filter(|e| e.content_type == ClipboardContentType::Binary)
+                .count(),
+            pinned_entries: entries.iter().filter(|e| e.pinned).count(),
+            image_bytes: entries
+                .iter()
+                .filter_map(|e| e.image_info.as_ref())
+                .map(|info| info.size_bytes)
+                .sum(),
+            image_bytes_saved: entries
+                .iter()
+                .filter_map(|e| e.image_info.as_ref())
+                .filter_map(|info| {
+                    info.original_size_bytes
+                        .map(|original| original.saturating_sub(info.size_bytes))
+                })
+                .sum(),
+            average_text_chars: if texts.is_empty() {
+                0.0
+            } else {
+                text_chars as f64 / texts.len() as f64
+            },
+            oldest: entries.iter().map(|e| e.timestamp).min(),
+            newest: entries.iter().map(|e| e.timestamp).max(),
+        }
+    }
+
+    /// Human-readable table, one metric per line.
+    pub fn to_table(&self) -> String {
+        let time = |timestamp: Option<i64>| {
+            timestamp
+                .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                .map(|t| {
+                    t.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_else(|| "-".to_string())
+        };
+
+        let rows = [
+            ("Entries", self.total_entries.to_string()),
+            ("  Text", self.text_entries.to_string()),
+            ("  Images", self.image_entries.to_string()),
+            ("  Binary", self.binary_entries.to_string()),
+            ("  Pinned", self.pinned_entries.to_string()),
+            ("Image storage", format_size(self.image_bytes)),
+            ("  Saved by shrinking", format_size(self.image_bytes_saved)),
+            (
+                "Average text length",
+                format!("{:.1} chars", self.average_text_chars),
+            ),
+            ("Oldest entry", time(self.oldest)),
+            ("Newest entry", time(self.newest)),
+        ];
+
+        rows.iter()
+            .map(|(label, value)| format!("{:<20} {}", label, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::time::Duration;
+
+use crate::ipc::get_control_socket_path;
+#[cfg(unix)]
+use crate::utils::CONTROL_TIMEOUT_SECS;
+
+// ============================================================================
+// CONTROL SOCKET CLIENT
+// ============================================================================
+
+/// Connection details for a running daemon's control socket.
+pub struct ControlClient {
+    path: PathBuf,
+}
+
+impl ControlClient {
+    /// Returns a client if a daemon is listening on the control socket.
+    #[cfg(unix)]
+    pub fn connect(data_dir: &Path) -> Option<Self> {
+        let path = get_control_socket_path(data_dir);
+        UnixStream::connect(&path).ok()?;
+        Some(Self { path })
+    }
+
+    /// Without Unix sockets there is no daemon to talk to, so clients always
+    /// edit the history files directly.
+    #[cfg(not(unix))]
+    pub fn connect(_data_dir: &Path) -> Option<Self> {
+        None
+    }
+
+    /// Send one command. Returns None if the daemon can't be reached (so the
+    /// caller can fall back to direct file access), otherwise the data lines
+    /// on `OK` or the message on `ERR`. Once connected the daemon may have
+    /// acted on the command, so a missing reply is an error rather than None:
+    /// applying the edit again would repeat it (or undo a toggle).
+    #[cfg(unix)]
+    pub fn request(&self, command: &str) -> Option<Result<Vec<String>, String>> {
+        let stream = UnixStream::connect(&self.path).ok()?;
+        Some(Self::exchange(stream, command))
+    }
+
+    #[cfg(unix)]
+    fn exchange(mut stream: UnixStream, command: &str) -> Result<Vec<String>, String> {
+        let no_reply =
+            |e: std::io::Error| format!("No reply from the daemon to {}: {}", command, e);
+        let timeout = Some(Duration::from_secs(CONTROL_TIMEOUT_SECS));
+        stream.set_read_timeout(timeout).map_err(no_reply)?;
+        stream.set_write_timeout(timeout).map_err(no_reply)?;
+        writeln!(stream, "{}", command).map_err(no_reply)?;
+
+        let mut data = Vec::new();
+        for line in BufReader::new(stream).lines() {
+            let line = line.map_err(no_reply)?;
+            if line == "OK" {
+                return Ok(data);
+            }
+            if let Some(message) = line.strip_prefix("ERR ") {
+                return Err(message.to_string());
+            }
+            data.push(line);
+        }
+        Err(format!(
+            "The daemon closed the connection before answering {}",
+            command
+        ))
+    }
+
+    #[cfg(not(unix))]
+    pub fn request(&self, _command: &str) -> Option<Result<Vec<String>, String>> {
+        let _ = &self.path;
+        None
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    fn socket_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "clipboard-manager-{}-{}.sock",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn no_daemon_means_no_reply() {
+        let client = ControlClient {
+            path: socket_path("absent"),
+        };
+        assert!(client.request("PIN 1").is_none());
+    }
+
+    #[test]
+    fn a_daemon_that_hangs_up_is_an_error() {
+        let path = socket_path("hangup");
+        let listener = UnixListener::bind(&path).unwrap();
+        let daemon = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut command = String::new();
+            BufReader::new(stream).read_line(&mut command).unwrap();
+            command
+        });
+        let client = ControlClient { path };
+        assert!(matches!(client.request("PIN 1"), Some(Err(_))));
+        assert_eq!(daemon.join().unwrap(), "PIN 1\n");
+    }
+}
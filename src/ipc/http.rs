@@ -0,0 +1,228 @@
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use log::{debug, info, warn};
+
+use crate::history::ClipboardHistory;
+use crate::models::{ClipboardContentType, ClipboardEntry};
+use crate::utils::CONTROL_TIMEOUT_SECS;
+
+// ============================================================================
+// READ-ONLY HTTP ENDPOINT (cargo feature "http")
+// ============================================================================
+//
+//   GET /history           the history as a JSON array, in display order
+//                          (secrets are left out)
+//   GET /image/<filename>  the bytes of a stored image
+//
+// With `http_token` set, requests need `Authorization: Bearer <token>`.
+
+/// How often the accept loop checks for shutdown
+const ACCEPT_POLL_MS: u64 = 100;
+
+/// Serve the history on `bind` until `shutdown` is set. The returned handle
+/// finishes once the listener is closed.
+pub fn start_http_server(
+    history: Arc<ClipboardHistory>,
+    bind: &str,
+    token: Option<String>,
+    shutdown: Arc<AtomicBool>,
+) -> Result<JoinHandle<()>, String> {
+    let listener =
+        TcpListener::bind(bind).map_err(|e| format!("Failed to bind {}: {}", bind, e))?;
+    // Non-blocking so the loop can notice shutdown between connections
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to configure {}: {}", bind, e))?;
+
+    let loopback = listener.local_addr().is_ok_and(|a| a.ip().is_loopback());
+    if !loopback && token.is_none() {
+        warn!(
+            "HTTP endpoint on {} is reachable from the network without http_token",
+            bind
+        );
+    }
+    info!("HTTP: http://{}/history", bind);
+
+    Ok(thread::spawn(move || {
+        while !shutdown.load(Ordering::Relaxed) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let history = Arc::clone(&history);
+                    let token = token.clone();
+                    thread::spawn(move || handle_request(stream, &history, token.as_deref()));
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(ACCEPT_POLL_MS));
+                }
+                Err(e) => warn!("HTTP accept error: {}", e),
+            }
+        }
+        debug!("HTTP server stopped");
+    }))
+}
+
+fn handle_request(stream: TcpStream, history: &ClipboardHistory, token: Option<&str>) {
+    let timeout = Some(Duration::from_secs(CONTROL_TIMEOUT_SECS));
+    if stream.set_nonblocking(false).is_err()
+        || stream.set_read_timeout(timeout).is_err()
+        || stream.set_write_timeout(timeout).is_err()
+    {
+        return;
+    }
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+
+    // Request line, then headers up to the blank line
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut authorization = None;
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if header.trim().is_empty() => break,
+            Ok(_) => {}
+        }
+        match header.split_once(':') {
+            Some((name, value)) if name.trim().eq_ignore_ascii_case("authorization") => {
+                authorization = Some(value.trim().to_string());
+            }
+            _ => {}
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    debug!("HTTP {} {}", method, path);
+
+    let authorized = token.is_none_or(|token| {
+        let expected = format!("Bearer {}", token);
+        authorization.is_some_and(|given| constant_time_eq(given.as_bytes(), expected.as_bytes()))
+    });
+    let response = if method != "GET" {
+        Response::text(405, "Method Not Allowed")
+    } else if !authorized {
+        Response::text(401, "Unauthorized")
+    } else if path == "/history" {
+        history_response(history)
+    } else if let Some(filename) = path.strip_prefix("/image/") {
+        image_response(history, filename)
+    } else {
+        Response::text(404, "Not Found")
+    };
+
+    let _ = response.write_to(&mut writer);
+}
+
+/// Compare without stopping at the first differing byte, so response times
+/// don't reveal how much of a guessed token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn history_response(history: &ClipboardHistory) -> Response {
+    // Pick up entries written since the last capture
+    history.reload();
+    let entries: Vec<ClipboardEntry> = history
+        .get_all()
+        .into_iter()
+        .filter(|e| !e.is_secret())
+        .collect();
+    match serde_json::to_vec(&entries) {
+        Ok(body) => Response::new(200, "application/json", body),
+        Err(e) => Response::text(500, &e.to_string()),
+    }
+}
+
+/// Only files named by an image entry are served, so the path can't reach
+/// anything else in the data directory.
+fn image_response(history: &ClipboardHistory, filename: &str) -> Response {
+    // Images captured since the last reload are served too
+    history.reload();
+    let known = history
+        .get_all()
+        .iter()
+        .any(|e| e.content_type == ClipboardContentType::Image && e.content == filename);
+    if !known {
+        return Response::text(404, "Not Found");
+    }
+
+    let mime_type = match filename.rsplit('.').next() {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("bmp") => "image/bmp",
+        _ => "application/octet-stream",
+    };
+    match std::fs::read(history.images_dir().join(filename)) {
+        Ok(body) => Response::new(200, mime_type, body),
+        Err(_) => Response::text(404, "Not Found"),
+    }
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn new(status: u16, content_type: &'static str, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            content_type,
+            body,
+        }
+    }
+
+    fn text(status: u16, message: &str) -> Self {
+        Self::new(
+            status,
+            "text/plain; charset=utf-8",
+            message.as_bytes().to_vec(),
+        )
+    }
+
+    fn write_to(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        };
+        write!(
+            out,
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.status,
+            reason,
+            self.content_type,
+            self.body.len()
+        )?;
+        out.write_all(&self.body)?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_compare_by_every_byte() {
+        assert!(constant_time_eq(b"Bearer secret", b"Bearer secret"));
+        assert!(!constant_time_eq(b"Bearer secreT", b"Bearer secret"));
+        assert!(!constant_time_eq(b"Bearer secret!", b"Bearer secret"));
+        assert!(!constant_time_eq(b"", b"Bearer secret"));
+    }
+}
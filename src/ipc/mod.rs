@@ -0,0 +1,9 @@
+pub mod client;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod server;
+
+pub use client::*;
+#[cfg(feature = "http")]
+pub use http::*;
+pub use server::*;
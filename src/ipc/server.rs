@@ -0,0 +1,285 @@
+use std::fs;
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+#[cfg(unix)]
+use std::thread;
+
+#[cfg(unix)]
+use log::{debug, info, warn};
+
+use crate::clipboard::ClipboardBackend;
+#[cfg(unix)]
+use crate::clipboard::{
+    clear_clipboard, get_clipboard_data, get_clipboard_image, get_clipboard_text,
+    set_clipboard_binary, set_clipboard_image_entry, set_clipboard_text,
+};
+#[cfg(unix)]
+use crate::config::config;
+use crate::history::ClipboardHistory;
+#[cfg(unix)]
+use crate::models::{ClipboardContentType, ClipboardEntry};
+#[cfg(unix)]
+use crate::utils::hash_bytes;
+use crate::utils::{CONTROL_SOCKET, NOTHING_TO_UNDO};
+
+// ============================================================================
+// CONTROL SOCKET SERVER
+// ============================================================================
+//
+// Line-based protocol: the client sends one command per line and the daemon
+// answers with zero or more data lines followed by `OK` or `ERR <message>`.
+//
+//   LIST          one JSON entry per line, in display order
+//   MODE          "read-only" when the daemon runs with --read-only,
+//                 otherwise "read-write"
+//   COPY <id>     put the entry back on the clipboard
+//   ECHO <id>     the entry is about to be put back on the clipboard by a
+//                 client; don't record that copy as a new one
+//   DELETE <id>   delete the entry (undoable)
+//   PIN <id>      toggle pin
+//   FRONT <id>    move the entry to the top
+//   KEEP <id>     stop a secret's auto-expiry
+//   COUNT <id>    count a copy of the entry from the UI
+//   TAGS <id> [tag ...]  replace the entry's tags (none clears them)
+//   UNDO          restore the last deleted entry
+//   FREE <bytes>  delete the oldest unpinned images and binary entries until
+//                 the history fits; replies with the number removed
+//   PRUNE <ts>    delete unpinned entries copied before the Unix timestamp;
+//                 replies with the number removed
+//   MERGE <json>  add a JSON array of entries (an imported archive, whose
+//                 files are already in place), skipping ones already present;
+//                 replies with the number added and kept
+//   CLEAR [ALL]   delete all entries; pinned ones are kept (when
+//                 clear_keeps_pinned is set) unless ALL is given. Replies
+//                 with the number removed
+
+pub fn get_control_socket_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(CONTROL_SOCKET)
+}
+
+pub fn remove_control_socket(data_dir: &Path) {
+    let _ = fs::remove_file(get_control_socket_path(data_dir));
+}
+
+/// Listen on the control socket and serve each connection on its own thread.
+#[cfg(unix)]
+pub fn start_control_server(
+    history: Arc<ClipboardHistory>,
+    backend: ClipboardBackend,
+) -> Result<(), String> {
+    let path = get_control_socket_path(history.data_dir());
+
+    // Only one daemon runs at a time, so a leftover socket is stale
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)
+        .map_err(|e| format!("Failed to bind {}: {}", path.display(), e))?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict {}: {}", path.display(), e))?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let history = Arc::clone(&history);
+                    thread::spawn(move || handle_connection(stream, &history, backend));
+                }
+                Err(e) => warn!("Control socket error: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn start_control_server(
+    _history: Arc<ClipboardHistory>,
+    _backend: ClipboardBackend,
+) -> Result<(), String> {
+    Err("Control socket requires Unix domain sockets".to_string())
+}
+
+#[cfg(unix)]
+fn handle_connection(stream: UnixStream, history: &ClipboardHistory, backend: ClipboardBackend) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        debug!("Control command: {}", line);
+
+        let reply = match handle_command(line, history, backend) {
+            Ok(data) => data
+                .into_iter()
+                .chain(std::iter::once("OK".to_string()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Err(e) => format!("ERR {}", e.replace('\n', " ")),
+        };
+
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(unix)]
+fn handle_command(
+    line: &str,
+    history: &ClipboardHistory,
+    backend: ClipboardBackend,
+) -> Result<Vec<String>, String> {
+    let (command, arg) = line
+        .split_once(' ')
+        .map_or((line, ""), |(command, arg)| (command, arg.trim()));
+    let id = || {
+        arg.parse::<u64>()
+            .map_err(|_| format!("Invalid id: {:?}", arg))
+    };
+
+    // Pick up changes written directly by clients that couldn't reach us
+    history.reload();
+
+    match command.to_ascii_uppercase().as_str() {
+        "LIST" => history
+            .get_all()
+            .iter()
+            .map(|entry| serde_json::to_string(entry).map_err(|e| e.to_string()))
+            .collect(),
+        "MODE" => Ok(vec![
+            if history.is_read_only() {
+                "read-only"
+            } else {
+                "read-write"
+            }
+            .to_string(),
+        ]),
+        "COPY" => {
+            let entry = find_entry(history, id()?)?;
+            history.expect_echo(entry.id)?;
+            match entry.content_type {
+                ClipboardContentType::Text => set_clipboard_text(&entry.content, backend)?,
+                ClipboardContentType::Image => {
+                    set_clipboard_image_entry(&entry, history.images_dir(), backend)?
+                }
+                ClipboardContentType::Binary => {
+                    let path = history.blobs_dir().join(&entry.content);
+                    let mime = entry.binary_info.as_ref().map_or("", |i| i.mime.as_str());
+                    set_clipboard_binary(&path, mime, backend)?
+                }
+            }
+            Ok(Vec::new())
+        }
+        "ECHO" => {
+            history.expect_echo(find_entry(history, id()?)?.id)?;
+            Ok(Vec::new())
+        }
+        "DELETE" => {
+            let entry = find_entry(history, id()?)?;
+            history.delete_by_id(entry.id)?;
+            if config().clear_clipboard_on_delete {
+                clear_if_current(&entry, backend);
+            }
+            Ok(Vec::new())
+        }
+        "PIN" => {
+            history.toggle_pin(find_entry(history, id()?)?.id)?;
+            Ok(Vec::new())
+        }
+        "FRONT" => {
+            history.move_to_front(find_entry(history, id()?)?.id)?;
+            Ok(Vec::new())
+        }
+        "KEEP" => {
+            history.stop_expiry(find_entry(history, id()?)?.id)?;
+            Ok(Vec::new())
+        }
+        "COUNT" => {
+            history.record_copy(find_entry(history, id()?)?.id)?;
+            Ok(Vec::new())
+        }
+        "TAGS" => {
+            let mut words = arg.split_whitespace();
+            let id = words.next().unwrap_or_default();
+            let id = id
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid id: {:?}", id))?;
+            let tags = words.map(str::to_string).collect();
+            history.set_tags(find_entry(history, id)?.id, tags)?;
+            Ok(Vec::new())
+        }
+        "UNDO" => {
+            if history.undo_delete()? {
+                Ok(Vec::new())
+            } else {
+                Err(NOTHING_TO_UNDO.to_string())
+            }
+        }
+        "FREE" => {
+            let limit = arg
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid size: {:?}", arg))?;
+            Ok(vec![history.free_space(limit)?.to_string()])
+        }
+        "PRUNE" => {
+            let cutoff = arg
+                .parse::<i64>()
+                .map_err(|_| format!("Invalid timestamp: {:?}", arg))?;
+            Ok(vec![history.clear_older_than(cutoff)?.to_string()])
+        }
+        "MERGE" => {
+            let imported = serde_json::from_str::<Vec<ClipboardEntry>>(arg)
+                .map_err(|e| format!("Invalid entries: {}", e))?;
+            Ok(vec![history.merge_entries(imported)?.to_string()])
+        }
+        "CLEAR" if arg.eq_ignore_ascii_case("all") => Ok(vec![history.clear_all()?.to_string()]),
+        "CLEAR" => Ok(vec![history.clear()?.to_string()]),
+        _ => Err(format!("Unknown command: {}", command)),
+    }
+}
+
+/// Empty the clipboard if it still holds `entry`, so a deleted entry doesn't
+/// linger there (`clear_clipboard_on_delete`).
+#[cfg(unix)]
+fn clear_if_current(entry: &ClipboardEntry, backend: ClipboardBackend) {
+    let current = match entry.content_type {
+        // Stored text may have been trimmed (`trim_on_store`)
+        ClipboardContentType::Text => {
+            get_clipboard_text(backend).is_some_and(|text| text.trim() == entry.content.trim())
+        }
+        ClipboardContentType::Image => {
+            get_clipboard_image(backend).is_some_and(|data| entry.dedup_hash == hash_bytes(&data))
+        }
+        ClipboardContentType::Binary => entry
+            .binary_info
+            .as_ref()
+            .and_then(|info| get_clipboard_data(backend, &info.mime))
+            .is_some_and(|data| entry.dedup_hash == hash_bytes(&data)),
+    };
+    if !current {
+        return;
+    }
+    match clear_clipboard(backend) {
+        Ok(()) => info!("Cleared the clipboard holding the deleted entry"),
+        Err(e) => warn!("Failed to clear the clipboard: {}", e),
+    }
+}
+
+#[cfg(unix)]
+fn find_entry(history: &ClipboardHistory, id: u64) -> Result<ClipboardEntry, String> {
+    history
+        .get_all()
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| format!("No entry with id {}", id))
+}
@@ -0,0 +1,36 @@
+//! The core of clipboard-manager, for building other frontends on top of it.
+//!
+//! [`ClipboardHistory`] is the history on disk: it loads, captures and edits
+//! entries, and sends edits through the running daemon when there is one.
+//! [`ClipboardBackend`] reads and writes the system clipboard, and
+//! [`start_clipboard_monitor`] runs the daemon's capture threads. The
+//! `clipboard-manager` binary is a thin command line around these, and the
+//! TUI in [`ui`] is one frontend among possible others.
+//!
+//! ```no_run
+//! use clipboard_manager::{ClipboardHistory, detect_clipboard_backend, set_clipboard_text};
+//!
+//! let history = ClipboardHistory::new().with_control_socket();
+//! if let Some(entry) = history.get_all().first() {
+//!     set_clipboard_text(&entry.content, detect_clipboard_backend())?;
+//!     history.expect_echo(entry.id)?;
+//! }
+//! # Ok::<(), String>(())
+//! ```
+
+pub mod clipboard;
+pub mod config;
+pub mod history;
+pub mod ipc;
+pub mod models;
+pub mod monitor;
+pub mod ui;
+pub mod utils;
+
+pub use clipboard::{
+    ClipboardBackend, detect_clipboard_backend, get_clipboard_image, get_clipboard_text,
+    select_clipboard_backend, set_clipboard_text, set_primary_text,
+};
+pub use history::{ClipboardHistory, data_dir, set_custom_data_dir};
+pub use models::{ClipboardContentType, ClipboardEntry, Selection};
+pub use monitor::start_clipboard_monitor;
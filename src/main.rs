@@ -4,82 +4,410 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
 
-mod clipboard;
-mod history;
-mod models;
-mod monitor;
-mod ui;
-mod utils;
-
-use clipboard::detect_clipboard_backend;
-use history::ClipboardHistory;
+mod cli;
+
+use clap::Parser;
+use cli::Cli;
+use clipboard::{ClipboardBackend, select_clipboard_backend, set_clipboard_text};
+use clipboard_manager::{clipboard, config, history, ipc, models, monitor, ui, utils};
+use config::config;
+use history::{
+    ClipboardHistory, HistoryStats, Stash, export_archive, import_archive, set_custom_data_dir,
+};
+use ipc::{get_control_socket_path, remove_control_socket, start_control_server};
+use models::ClipboardContentType;
+#[cfg(target_os = "linux")]
+use monitor::{create_systemd_unit, create_trigger_script, get_trigger_script_path};
+use monitor::wayland::{reap_stale_watchers, stop_wayland_watchers};
 use monitor::{
-    create_trigger_script, get_trigger_script_path, remove_pid_file, start_clipboard_monitor,
-    start_signal_listener, write_pid_file,
+    clear_backend_file, clear_pause_file, join_threads, lock_daemon, remove_pid_file,
+    running_daemon_pid, start_backend_watch, start_clipboard_monitor, start_signal_listener,
 };
-use ui::show_ui;
+use log::{LevelFilter, error, info, warn};
+use ui::{run_dmenu, show_ui};
+use utils::{POLL_INTERVAL_MS, PREVIEW_WIDTH, SHUTDOWN_TIMEOUT_MS};
 
 // ============================================================================
 // MAIN
 // ============================================================================
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let backend = detect_clipboard_backend();
-
-    if args.len() > 1 {
-        if args[1] == "--ui" {
-            if let Err(e) = show_ui(backend) {
-                eprintln!("UI Error: {}", e);
-                std::process::exit(1);
-            }
-            std::process::exit(0);
-        } else if args[1] == "--paste" {
-            utils::helpers::perform_background_paste(backend);
-            std::process::exit(0);
-        }
-    }
+/// Set up logging. `RUST_LOG` is honored; `--quiet` and `--verbose` override it.
+/// The TUI logs nothing by default since output would corrupt the screen.
+fn init_logging(cli: &Cli) {
+    let default_level = if cli.ui {
+        "off"
+    } else if cli.is_command() {
+        "warn"
+    } else {
+        "info"
+    };
 
-    // Daemon mode
-    println!("╔════════════════════════════════════════╗");
-    println!("║     Clipboard Manager - Daemon Mode    ║");
-    println!("╚════════════════════════════════════════╝\n");
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_level));
 
-    let history = Arc::new(ClipboardHistory::new());
-    let data_dir = history.data_dir().clone();
+    if cli.quiet {
+        builder.filter_level(LevelFilter::Error);
+    } else if cli.verbose {
+        builder.filter_level(LevelFilter::Debug);
+    }
 
-    write_pid_file(&data_dir).ok();
+    builder.init();
+}
 
-    let binary_path = env::current_exe()
+fn current_binary() -> String {
+    env::current_exe()
         .ok()
         .and_then(|p| p.to_str().map(String::from))
-        .unwrap_or_else(|| String::from("clipboard-manager"));
-
-    create_trigger_script(&data_dir, &binary_path).ok();
-
-    let shutdown_trigger = Arc::new(AtomicBool::new(false));
-    start_signal_listener(Arc::clone(&shutdown_trigger));
-    start_clipboard_monitor(Arc::clone(&history), backend);
+        .unwrap_or_else(|| String::from("clipboard-manager"))
+}
 
-    println!("✓ Backend: {:?}", backend);
-    println!("✓ Data dir: {}", data_dir.display());
-    println!("✓ Trigger: {}\n", get_trigger_script_path(&data_dir).display());
-    
+/// Tell the user how to bind a key that opens the picker on this platform.
+#[cfg(target_os = "linux")]
+fn print_launch_guidance(data_dir: &std::path::PathBuf) {
     println!("ℹ Auto-configuration is active for Hyprland.");
     println!("  If the window doesn't float, add this rule to hyprland.conf:");
     println!("    windowrule = float on, match:class floating-clipboard");
     println!();
     println!(
         "  Bind key to open UI:\n    bind = SUPER, V, exec, {}",
-        get_trigger_script_path(&data_dir).display()
+        get_trigger_script_path(data_dir).display()
+    );
+    println!();
+
+    // systemd sets INVOCATION_ID for the processes it starts
+    if env::var_os("INVOCATION_ID").is_none() {
+        println!("  Start the daemon on login with systemd:");
+        println!("    systemctl --user enable --now clipboard-manager");
+        println!();
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn print_launch_guidance(_data_dir: &std::path::PathBuf) {
+    println!("ℹ Open the picker from a terminal:");
+    println!("    {} --ui", current_binary());
+    println!();
+    println!("  To bind a key, create a Shortcuts \"Run Shell Script\" action with:");
+    println!(
+        "    osascript -e 'tell application \"Terminal\" to do script \"{} --ui\"'",
+        current_binary()
+    );
+    println!();
+}
+
+#[cfg(target_os = "windows")]
+fn print_launch_guidance(_data_dir: &std::path::PathBuf) {
+    println!("ℹ Open the picker from a terminal:");
+    println!("    \"{}\" --ui", current_binary());
+    println!();
+    println!(
+        "  To bind a key, create a desktop shortcut with this target and set its Shortcut key:"
     );
+    println!("    wt.exe \"{}\" --ui", current_binary());
+    println!();
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn print_launch_guidance(_data_dir: &std::path::PathBuf) {
+    println!("ℹ Open the picker with: {} --ui", current_binary());
     println!();
+}
+
+/// Ask the running daemon to pause or resume capture (SIGUSR1).
+#[cfg(unix)]
+fn toggle_daemon_pause() -> Result<&'static str, String> {
+    let data_dir = history::data_dir();
+    let pid = running_daemon_pid(data_dir).ok_or("The daemon is not running")?;
+    let status = std::process::Command::new("kill")
+        .args(["-USR1", &pid.to_string()])
+        .status()
+        .map_err(|e| format!("Failed to signal the daemon: {}", e))?;
+    if !status.success() {
+        return Err(format!("Failed to signal the daemon (pid {})", pid));
+    }
+
+    // The daemon writes the status file once it has handled the signal
+    thread::sleep(Duration::from_millis(200));
+    if data_dir.join(utils::PAUSE_FILE).exists() {
+        Ok("⏸ Capture paused (run --toggle-pause again to resume)")
+    } else {
+        Ok("▶ Capture resumed")
+    }
+}
+
+#[cfg(not(unix))]
+fn toggle_daemon_pause() -> Result<&'static str, String> {
+    Err("--toggle-pause needs Unix signals".to_string())
+}
+
+/// Copy the stashed text entries to the clipboard, emptying the stash
+/// unless `clear_stash_on_paste` is off.
+fn paste_stash(backend: ClipboardBackend) -> Result<String, String> {
+    let history = ClipboardHistory::new();
+    let mut stash = Stash::load(history.data_dir());
+    let (count, text) = stash
+        .text(&history.get_all())
+        .ok_or_else(|| "The stash has no text entries".to_string())?;
+    set_clipboard_text(&text, backend)?;
+    if config().clear_stash_on_paste {
+        stash.clear();
+    }
+    Ok(format!("✓ Copied {} stashed entries", count))
+}
+
+/// Print the outcome of a one-shot command and exit with its status.
+fn exit_with_summary(result: Result<String, String>) -> ! {
+    match result {
+        Ok(summary) => {
+            println!("{}", summary);
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    init_logging(&cli);
+    if let Some(dir) = &cli.data_dir {
+        set_custom_data_dir(dir.clone());
+    }
+
+    let backend = select_clipboard_backend(cli.backend.as_deref());
+
+    if cli.ui {
+        if let Err(e) = show_ui(backend, cli.no_color, cli.recent, cli.debug_scores) {
+            eprintln!("UI Error: {}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    } else if let Some(picker) = &cli.dmenu {
+        if let Err(e) = run_dmenu(backend, picker) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    } else if cli.toggle_pause {
+        match toggle_daemon_pause() {
+            Ok(message) => println!("{}", message),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        std::process::exit(0);
+    } else if cli.paste_stash {
+        exit_with_summary(paste_stash(backend));
+    } else if cli.paste {
+        utils::helpers::perform_background_paste();
+        std::process::exit(0);
+    } else if let Some(index) = cli.type_index {
+        let entries = ClipboardHistory::new().get_all();
+        let result = match entries.get(index) {
+            None => Err(format!(
+                "Index {} out of range ({} entries)",
+                index,
+                entries.len()
+            )),
+            Some(entry) if entry.content_type != ClipboardContentType::Text => {
+                Err("Only text entries can be typed".to_string())
+            }
+            Some(entry) => utils::helpers::perform_background_type(&entry.content),
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    } else if cli.clear {
+        let history = ClipboardHistory::new().with_control_socket();
+        let result = if cli.include_pinned || !config().clear_keeps_pinned {
+            history
+                .clear_all()
+                .map(|removed| format!("✓ Cleared {} entries", removed))
+        } else {
+            history
+                .clear()
+                .map(|removed| format!("✓ Cleared {} entries (pinned entries are kept)", removed))
+        };
+        exit_with_summary(result);
+    } else if let Some(age) = cli.prune {
+        let cutoff = chrono::Utc::now().timestamp() - age;
+        let result = ClipboardHistory::new()
+            .with_control_socket()
+            .clear_older_than(cutoff)
+            .map(|removed| format!("✓ Removed {} entries (pinned entries are kept)", removed));
+        exit_with_summary(result);
+    } else if let Some(index) = cli.remove {
+        match ClipboardHistory::new()
+            .with_control_socket()
+            .delete_entry(index)
+        {
+            Ok(entry) => {
+                let preview = entry
+                    .preview_lines(PREVIEW_WIDTH)
+                    .into_iter()
+                    .next()
+                    .unwrap_or_default();
+                println!("✓ Removed entry {}: {}", index, preview.trim());
+                std::process::exit(0);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if cli.stats {
+        let stats = HistoryStats::from_entries(&ClipboardHistory::new().get_all());
+        if cli.json {
+            match serde_json::to_string_pretty(&stats) {
+                Ok(json) => println!("{}", json),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            println!("{}", stats.to_table());
+        }
+        std::process::exit(0);
+    } else if let Some(path) = &cli.export {
+        let result = export_archive(&ClipboardHistory::new(), path).map(|(entries, files)| {
+            format!(
+                "✓ Exported {} entries and {} files to {}",
+                entries,
+                files,
+                path.display()
+            )
+        });
+        exit_with_summary(result);
+    } else if let Some(path) = &cli.import {
+        // The daemon merges the entries, so its next save doesn't drop them
+        let result = import_archive(&ClipboardHistory::new().with_control_socket(), path)
+            .map(|(entries, files)| format!("✓ Imported {} entries and {} files", entries, files));
+        exit_with_summary(result);
+    }
+
+    // Daemon mode
+    let data_dir = history::data_dir().to_path_buf();
+
+    // Only one daemon may capture and write the history at a time, so the
+    // lock is taken before the history is even loaded and held until exit
+    let _daemon_lock = match lock_daemon(&data_dir) {
+        Ok(lock) => lock,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut history = ClipboardHistory::new();
+    if cli.read_only {
+        history = history.read_only();
+    }
+    let history = Arc::new(history);
+
+    // Under systemd the banner and hints would only clutter the journal
+    let quiet = cli.daemon_quiet || config().daemon_quiet;
+
+    if !quiet {
+        println!("╔════════════════════════════════════════╗");
+        println!("║     Clipboard Manager - Daemon Mode    ║");
+        println!("╚════════════════════════════════════════╝\n");
+    }
+    if cli.read_only {
+        warn!("READ-ONLY: history will not be saved");
+    }
+
+    clear_pause_file(&data_dir);
+    reap_stale_watchers(&data_dir);
+    history.gc_orphans();
+
+    #[cfg(target_os = "linux")]
+    if !cli.read_only {
+        // An explicitly chosen backend is passed on so the picker agrees with us
+        let ui_args = cli
+            .backend
+            .as_ref()
+            .map(|_| format!("--backend {}", backend.name()))
+            .unwrap_or_default();
+        create_trigger_script(&data_dir, &current_binary(), &ui_args).ok();
+        if let Err(e) = create_systemd_unit(&data_dir, &current_binary()) {
+            warn!("Failed to write systemd unit: {}", e);
+        }
+    }
+
+    let shutdown_trigger = Arc::new(AtomicBool::new(false));
+    // Joined on shutdown, before the last save
+    let mut threads = Vec::new();
+    threads.extend(start_signal_listener(
+        Arc::clone(&shutdown_trigger),
+        data_dir.clone(),
+    ));
+    history.start_save_worker();
+    // Attempt to configure Hyprland window rules automatically
+    #[cfg(target_os = "linux")]
+    monitor::hyprland::apply_hyprland_rules();
+    threads.extend(start_clipboard_monitor(
+        Arc::clone(&history),
+        Arc::new(backend),
+        matches!(backend, ClipboardBackend::WlClipboard),
+        quiet,
+        Arc::clone(&shutdown_trigger),
+    ));
+    threads.push(start_backend_watch(
+        backend,
+        data_dir.clone(),
+        Arc::clone(&shutdown_trigger),
+    ));
+    if let Err(e) = start_control_server(Arc::clone(&history), backend) {
+        warn!("Control socket unavailable: {}", e);
+    }
+    #[cfg(feature = "http")]
+    threads.extend(config().http_bind.as_deref().and_then(|bind| {
+        ipc::start_http_server(
+            Arc::clone(&history),
+            bind,
+            config().http_token.clone(),
+            Arc::clone(&shutdown_trigger),
+        )
+        .map_err(|e| warn!("HTTP endpoint unavailable: {}", e))
+        .ok()
+    }));
+
+    info!("Backend: {:?}", backend);
+    if matches!(backend, ClipboardBackend::WlClipboard) {
+        info!("Monitor: event-driven (wl-paste --watch), no CPU used while idle");
+    } else {
+        info!("Monitor: polling every {} ms", POLL_INTERVAL_MS);
+    }
+    info!("Data dir: {}", data_dir.display());
+    #[cfg(target_os = "linux")]
+    info!("Trigger: {}", get_trigger_script_path(&data_dir).display());
+    info!("Control: {}", get_control_socket_path(&data_dir).display());
+    if !quiet {
+        println!();
+        print_launch_guidance(&data_dir);
+    }
 
     while !shutdown_trigger.load(Ordering::Relaxed) {
         thread::sleep(Duration::from_millis(100));
     }
 
-    println!("\nShutting down...");
+    info!("Shutting down...");
 
+    // Killing the watchers ends their threads; the rest see the trigger
+    stop_wayland_watchers(&data_dir);
+    join_threads(threads, Duration::from_millis(SHUTDOWN_TIMEOUT_MS));
+    remove_control_socket(&data_dir);
+    // Captures from the last moments may still be waiting for the save worker
+    if let Err(e) = history.flush() {
+        error!("Failed to save the last captures: {}", e);
+    }
+    clear_pause_file(&data_dir);
+    clear_backend_file(&data_dir);
     remove_pid_file(&data_dir);
 }
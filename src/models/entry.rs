@@ -1,8 +1,12 @@
 use serde::{Deserialize, Serialize};
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
-use crate::utils::{SECRET_EXPIRY_SECS, format_size};
+use crate::config::config;
+use crate::utils::{
+    BLOB_MIN_CHARS, BLOBS_DIR, IMAGES_DIR, SECRET_EXPIRY_SECS, format_size, hash_bytes,
+};
 
 // ============================================================================
 // DATA STRUCTURES
@@ -12,6 +16,147 @@ use crate::utils::{SECRET_EXPIRY_SECS, format_size};
 pub enum ClipboardContentType {
     Text,
     Image,
+    /// Data of a MIME type listed in `binary_mime_types`, described by
+    /// `ClipboardEntry::binary_info`
+    Binary,
+}
+
+/// Which X11/Wayland selection an entry was captured from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Selection {
+    /// The regular Ctrl+C / Ctrl+V clipboard
+    #[default]
+    Clipboard,
+    /// The middle-click PRIMARY selection
+    Primary,
+}
+
+/// What a text entry looks like, derived from its content when the entry is
+/// created or loaded (never persisted).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DetectedKind {
+    Url,
+    Email,
+    HexColor,
+    /// `rgb()`/`hsl()` style color
+    Color,
+    Path,
+    Phone,
+    Code,
+    /// One very long line with hardly any whitespace (minified JSON, base64)
+    Blob,
+    #[default]
+    PlainText,
+}
+
+impl DetectedKind {
+    /// Classify text using simple prefix/character heuristics.
+    pub fn detect(text: &str) -> Self {
+        let trimmed = text.trim();
+
+        // URL detection
+        if trimmed.starts_with("http://")
+            || trimmed.starts_with("https://")
+            || trimmed.starts_with("ftp://")
+        {
+            return Self::Url;
+        }
+
+        // Blob detection: checked early, since a minified blob can contain
+        // '@' or start with '/' and would pass for an email or path
+        if Self::looks_like_blob(trimmed) {
+            return Self::Blob;
+        }
+
+        // Email detection: contains @ with text before and after, has a dot after @
+        if !trimmed.contains(' ') && trimmed.contains('@') {
+            if let Some(at_pos) = trimmed.find('@') {
+                let before = &trimmed[..at_pos];
+                let after = &trimmed[at_pos + 1..];
+                if !before.is_empty() && after.contains('.') && after.len() > 2 {
+                    return Self::Email;
+                }
+            }
+        }
+
+        // Hex color detection: #RGB, #RRGGBB, #RRGGBBAA
+        if trimmed.starts_with('#') && trimmed.len() >= 4 && trimmed.len() <= 9 {
+            let hex_part = &trimmed[1..];
+            if matches!(hex_part.len(), 3 | 4 | 6 | 8)
+                && hex_part.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return Self::HexColor;
+            }
+        }
+
+        // RGB/HSL color detection
+        if (trimmed.starts_with("rgb(")
+            || trimmed.starts_with("rgba(")
+            || trimmed.starts_with("hsl(")
+            || trimmed.starts_with("hsla("))
+            && trimmed.ends_with(')')
+        {
+            return Self::Color;
+        }
+
+        // File path detection
+        if trimmed.starts_with('/')
+            || trimmed.starts_with("~/")
+            || trimmed.starts_with("./")
+            || trimmed.starts_with("../")
+        {
+            // Make sure it looks like a path (has separators, no spaces at start)
+            if trimmed.contains('/') && !trimmed.contains("  ") {
+                return Self::Path;
+            }
+        }
+
+        // Phone number detection: starts with + or digits, mostly digits/spaces/dashes/parens
+        if trimmed.len() >= 7 && trimmed.len() <= 20 {
+            let first = trimmed.chars().next().unwrap_or(' ');
+            if first == '+' || first.is_ascii_digit() {
+                let digit_count = trimmed.chars().filter(|c| c.is_ascii_digit()).count();
+                let valid_chars = trimmed
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || " -+()".contains(c));
+                if valid_chars && digit_count >= 7 {
+                    return Self::Phone;
+                }
+            }
+        }
+
+        // Code snippet detection: look for common programming patterns
+        if ClipboardEntry::looks_like_code(trimmed) {
+            return Self::Code;
+        }
+
+        // Default: plain text
+        Self::PlainText
+    }
+
+    /// A single line over `BLOB_MIN_CHARS` that is less than 5% whitespace.
+    fn looks_like_blob(text: &str) -> bool {
+        if text.contains('\n') {
+            return false;
+        }
+        let length = text.chars().count();
+        let whitespace = text.chars().filter(|c| c.is_whitespace()).count();
+        length > BLOB_MIN_CHARS && whitespace * 20 < length
+    }
+
+    /// Icon and label shown in the list.
+    pub fn icon_label(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Url => ("🔗", "Link"),
+            Self::Email => ("📧", "Email"),
+            Self::HexColor | Self::Color => ("🎨", "Color"),
+            Self::Path => ("📁", "Path"),
+            Self::Phone => ("📞", "Phone"),
+            Self::Code => ("💻", "Code"),
+            Self::Blob => ("📦", "Blob"),
+            Self::PlainText => ("📝", "Text"),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -23,19 +168,58 @@ pub struct SecretInfo {
     pub expires_at: Option<i64>,
 }
 
+/// One copy in the history, stored as a line of JSON in the history file.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ClipboardEntry {
+    /// Stable unique identifier, assigned once at creation and persisted.
+    /// Entries from older history files have no id and get one assigned on load.
+    #[serde(default)]
+    pub id: u64,
     pub content_type: ClipboardContentType,
     pub content: String,
     pub timestamp: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub image_info: Option<ImageInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary_info: Option<BinaryInfo>,
+    /// Identifies duplicates: `hash_bytes` of the text, or of the copied
+    /// image or binary data. Stored for file entries, whose data isn't in
+    /// the history file; recomputed on load for text
+    #[serde(default)]
+    pub dedup_hash: u64,
     #[serde(default)]
     pub pinned: bool,
+    #[serde(default)]
+    pub selection: Selection,
+    /// HTML representation captured alongside the plain text, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_content: Option<String>,
+    /// MIME types offered when an image was copied, in the app's order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mime_types: Vec<String>,
+    /// SVG markup offered alongside a copied image; restored instead of the
+    /// stored raster so vector graphics round-trip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub svg_content: Option<String>,
+    /// Class of the window focused when the entry was copied (`track_source`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_app: Option<String>,
+    /// Text recognized in an image entry (with the `ocr` feature), used by search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr_text: Option<String>,
+    /// The text was cut to `max_entry_bytes`; the stored copy is incomplete
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// How often the entry was picked in the UI and copied back
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub copy_count: u32,
+    /// Free-form tags set in the UI (A), searchable as `tag:<name>`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub secret_info: Option<SecretInfo>,
     #[serde(skip)]
-    pub content_hash: u64,
+    pub detected_kind: DetectedKind,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -43,51 +227,162 @@ pub struct ImageInfo {
     pub width: u32,
     pub height: u32,
     pub size_bytes: u64,
+    /// File extension of the stored format (e.g. "png", "gif")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Size of the copied image before `image_max_dimension` /
+    /// `image_reencode_quality` shrank it (unset when stored as copied)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_size_bytes: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BinaryInfo {
+    /// The MIME type the data was copied as, offered again on restore
+    pub mime: String,
+    pub size_bytes: u64,
 }
 
 impl ClipboardEntry {
     pub fn new_text(content: String) -> Self {
-        let mut hasher = DefaultHasher::new();
-        content.hash(&mut hasher);
-        let content_hash = hasher.finish();
+        let dedup_hash = Self::text_hash(&content);
 
         let secret_info = Self::detect_secret(&content);
+        let detected_kind = DetectedKind::detect(&content);
 
         Self {
+            id: Self::next_id(),
             content_type: ClipboardContentType::Text,
             content,
             timestamp: chrono::Utc::now().timestamp(),
             image_info: None,
+            binary_info: None,
+            dedup_hash,
             pinned: false,
+            selection: Selection::Clipboard,
+            rich_content: None,
+            mime_types: Vec::new(),
+            svg_content: None,
+            source_app: None,
+            ocr_text: None,
+            truncated: false,
+            copy_count: 0,
+            tags: Vec::new(),
             secret_info,
-            content_hash,
+            detected_kind,
         }
     }
 
     pub fn new_image(filename: String, info: ImageInfo, hash: u64) -> Self {
         Self {
+            id: Self::next_id(),
             content_type: ClipboardContentType::Image,
             content: filename,
             timestamp: chrono::Utc::now().timestamp(),
             image_info: Some(info),
+            binary_info: None,
+            dedup_hash: hash,
             pinned: false,
+            selection: Selection::Clipboard,
+            rich_content: None,
+            mime_types: Vec::new(),
+            svg_content: None,
+            source_app: None,
+            ocr_text: None,
+            truncated: false,
+            copy_count: 0,
+            tags: Vec::new(),
             secret_info: None,
-            content_hash: hash,
+            detected_kind: DetectedKind::PlainText,
         }
     }
 
-    pub fn compute_hash(&mut self) {
-        let mut hasher = DefaultHasher::new();
-        match self.content_type {
-            ClipboardContentType::Text => {
-                self.content.hash(&mut hasher);
-            }
-            ClipboardContentType::Image => {
-                self.content.hash(&mut hasher);
-                self.timestamp.hash(&mut hasher);
+    pub fn new_binary(filename: String, info: BinaryInfo, hash: u64) -> Self {
+        Self {
+            id: Self::next_id(),
+            content_type: ClipboardContentType::Binary,
+            content: filename,
+            timestamp: chrono::Utc::now().timestamp(),
+            image_info: None,
+            binary_info: Some(info),
+            dedup_hash: hash,
+            pinned: false,
+            selection: Selection::Clipboard,
+            rich_content: None,
+            mime_types: Vec::new(),
+            svg_content: None,
+            source_app: None,
+            ocr_text: None,
+            truncated: false,
+            copy_count: 0,
+            tags: Vec::new(),
+            secret_info: None,
+            detected_kind: DetectedKind::PlainText,
+        }
+    }
+
+    /// Generate a new unique id. Based on the current time in nanoseconds so ids
+    /// stay unique across processes, and strictly increasing within a process.
+    pub fn next_id() -> u64 {
+        static LAST_ID: AtomicU64 = AtomicU64::new(0);
+
+        let now = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
+        let mut last = LAST_ID.load(Ordering::Relaxed);
+        loop {
+            let candidate = now.max(last + 1);
+            match LAST_ID.compare_exchange_weak(
+                last,
+                candidate,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return candidate,
+                Err(actual) => last = actual,
             }
         }
-        self.content_hash = hasher.finish();
+    }
+
+    /// Set `dedup_hash` after loading: text is always rehashed, so the hash
+    /// follows the current `dedup_normalized`. An image or binary entry saved
+    /// without one gets its filename's hash, so it only matches itself.
+    pub fn ensure_dedup_hash(&mut self) {
+        match self.content_type {
+            ClipboardContentType::Text => self.dedup_hash = Self::text_hash(&self.content),
+            _ if self.dedup_hash == 0 => self.dedup_hash = hash_bytes(self.content.as_bytes()),
+            _ => {}
+        }
+    }
+
+    /// Recompute the `dedup_hash` of an image or binary entry from its stored
+    /// `data`, for hashes saved by older versions. A shrunk image's file no
+    /// longer matches what was copied, so (like missing data) its filename
+    /// stands in. Returns whether the hash changed.
+    pub fn rehash_file(&mut self, data: Option<&[u8]>) -> bool {
+        if self.content_type == ClipboardContentType::Text {
+            return false;
+        }
+        let shrunk = self
+            .image_info
+            .as_ref()
+            .is_some_and(|i| i.original_size_bytes.is_some());
+        let hash = match data {
+            Some(data) if !shrunk => hash_bytes(data),
+            _ => hash_bytes(self.content.as_bytes()),
+        };
+        let changed = hash != self.dedup_hash;
+        self.dedup_hash = hash;
+        changed
+    }
+
+    /// Dedup hash of text. With `dedup_normalized`, text differing only in
+    /// whitespace (trailing newlines, indentation) hashes the same.
+    fn text_hash(content: &str) -> u64 {
+        if config().dedup_normalized {
+            let normalized: Vec<&str> = content.split_whitespace().collect();
+            hash_bytes(normalized.join(" ").as_bytes())
+        } else {
+            hash_bytes(content.as_bytes())
+        }
     }
 
     /// Returns true if this secret has expired.
@@ -134,6 +429,30 @@ impl ClipboardEntry {
         }
     }
 
+    /// How long ago the entry was copied: "just now", "5m ago", "3h ago", "2d ago".
+    pub fn relative_time(&self) -> String {
+        let elapsed = (chrono::Utc::now().timestamp() - self.timestamp).max(0);
+        match elapsed {
+            s if s < 60 => "just now".to_string(),
+            s if s < 3600 => format!("{}m ago", s / 60),
+            s if s < 86400 => format!("{}h ago", s / 3600),
+            s => format!("{}d ago", s / 86400),
+        }
+    }
+
+    /// Local time the entry was copied; entries from before today include the date.
+    pub fn absolute_time(&self) -> String {
+        let Some(time) = chrono::DateTime::from_timestamp(self.timestamp, 0) else {
+            return String::new();
+        };
+        let time = time.with_timezone(&chrono::Local);
+        if time.date_naive() == chrono::Local::now().date_naive() {
+            time.format("%H:%M:%S").to_string()
+        } else {
+            time.format("%Y-%m-%d %H:%M").to_string()
+        }
+    }
+
     /// Detect if the content is a secret/sensitive value.
     /// Returns Some(SecretInfo) with the provider name and an expiry timestamp if detected.
     fn detect_secret(content: &str) -> Option<SecretInfo> {
@@ -413,82 +732,32 @@ impl ClipboardEntry {
 
         match self.content_type {
             ClipboardContentType::Image => ("🖼️", "Image"),
-            ClipboardContentType::Text => {
-                let trimmed = self.content.trim();
-
-                // URL detection
-                if trimmed.starts_with("http://")
-                    || trimmed.starts_with("https://")
-                    || trimmed.starts_with("ftp://")
-                {
-                    return ("🔗", "Link");
-                }
-
-                // Email detection: contains @ with text before and after, has a dot after @
-                if !trimmed.contains(' ') && trimmed.contains('@') {
-                    if let Some(at_pos) = trimmed.find('@') {
-                        let before = &trimmed[..at_pos];
-                        let after = &trimmed[at_pos + 1..];
-                        if !before.is_empty() && after.contains('.') && after.len() > 2 {
-                            return ("📧", "Email");
-                        }
-                    }
-                }
-
-                // Hex color detection: #RGB, #RRGGBB, #RRGGBBAA
-                if trimmed.starts_with('#') && trimmed.len() >= 4 && trimmed.len() <= 9 {
-                    let hex_part = &trimmed[1..];
-                    if matches!(hex_part.len(), 3 | 4 | 6 | 8)
-                        && hex_part.chars().all(|c| c.is_ascii_hexdigit())
-                    {
-                        return ("🎨", "Color");
-                    }
-                }
-
-                // RGB/HSL color detection
-                if (trimmed.starts_with("rgb(")
-                    || trimmed.starts_with("rgba(")
-                    || trimmed.starts_with("hsl(")
-                    || trimmed.starts_with("hsla("))
-                    && trimmed.ends_with(')')
-                {
-                    return ("🎨", "Color");
-                }
-
-                // File path detection
-                if trimmed.starts_with('/')
-                    || trimmed.starts_with("~/")
-                    || trimmed.starts_with("./")
-                    || trimmed.starts_with("../")
-                {
-                    // Make sure it looks like a path (has separators, no spaces at start)
-                    if trimmed.contains('/') && !trimmed.contains("  ") {
-                        return ("📁", "Path");
-                    }
-                }
-
-                // Phone number detection: starts with + or digits, mostly digits/spaces/dashes/parens
-                if trimmed.len() >= 7 && trimmed.len() <= 20 {
-                    let first = trimmed.chars().next().unwrap_or(' ');
-                    if first == '+' || first.is_ascii_digit() {
-                        let digit_count = trimmed.chars().filter(|c| c.is_ascii_digit()).count();
-                        let valid_chars = trimmed
-                            .chars()
-                            .all(|c| c.is_ascii_digit() || " -+()".contains(c));
-                        if valid_chars && digit_count >= 7 {
-                            return ("📞", "Phone");
-                        }
-                    }
-                }
+            ClipboardContentType::Binary => ("📄", "Binary"),
+            ClipboardContentType::Text => self.detected_kind.icon_label(),
+        }
+    }
 
-                // Code snippet detection: look for common programming patterns
-                if Self::looks_like_code(trimmed) {
-                    return ("💻", "Code");
-                }
+    /// Fill in `detected_kind` from the content (text entries only).
+    pub fn detect_kind(&mut self) {
+        if self.content_type == ClipboardContentType::Text {
+            self.detected_kind = DetectedKind::detect(&self.content);
+        }
+    }
 
-                // Default: plain text
-                ("📝", "Text")
+    /// The RGB value of a hex color entry (`#RGB`, `#RRGGBB` or `#RRGGBBAA`).
+    pub fn hex_color_rgb(&self) -> Option<(u8, u8, u8)> {
+        if self.detected_kind != DetectedKind::HexColor {
+            return None;
+        }
+        let hex = self.content.trim().trim_start_matches('#');
+        let channel = |range: std::ops::Range<usize>| u8::from_str_radix(hex.get(range)?, 16).ok();
+        match hex.len() {
+            3 | 4 => {
+                let short = |i: usize| channel(i..i + 1).map(|v| v * 17);
+                Some((short(0)?, short(1)?, short(2)?))
             }
+            6 | 8 => Some((channel(0..2)?, channel(2..4)?, channel(4..6)?)),
+            _ => None,
         }
     }
 
@@ -561,8 +830,41 @@ impl ClipboardEntry {
         false
     }
 
-    pub fn metadata_label(&self) -> String {
+    /// Directory (under the data dir) holding the file named by `content`,
+    /// for image and binary entries.
+    pub fn file_dir(&self) -> Option<&'static str> {
+        match self.content_type {
+            ClipboardContentType::Text => None,
+            ClipboardContentType::Image => Some(IMAGES_DIR),
+            ClipboardContentType::Binary => Some(BLOBS_DIR),
+        }
+    }
+
+    /// Bytes the entry takes up: its text plus any stored file.
+    pub fn stored_bytes(&self) -> u64 {
+        self.content.len() as u64 + self.file_bytes()
+    }
+
+    /// Bytes of the entry's image or binary file (0 for text).
+    pub fn file_bytes(&self) -> u64 {
+        self.image_info.as_ref().map_or(0, |i| i.size_bytes)
+            + self.binary_info.as_ref().map_or(0, |i| i.size_bytes)
+    }
+
+    /// Returns true if the entry carries `tag` (ignoring case).
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Metadata line shown under each entry. `relative_time` picks between
+    /// "5m ago" and an absolute timestamp.
+    pub fn metadata_label(&self, relative_time: bool) -> String {
         let pin_prefix = if self.pinned { "📌 " } else { "" };
+        let time = if relative_time {
+            self.relative_time()
+        } else {
+            self.absolute_time()
+        };
 
         // Special handling for secrets
         if let Some(ref secret) = self.secret_info {
@@ -571,35 +873,86 @@ impl ClipboardEntry {
         }
 
         let (icon, label) = self.detect_category();
+        let copies_suffix = if self.copy_count > 0 {
+            format!(" · copied {}×", self.copy_count)
+        } else {
+            String::new()
+        };
+        let source_suffix = self
+            .source_app
+            .as_ref()
+            .map(|app| format!("{} · from {}", copies_suffix, app))
+            .unwrap_or(copies_suffix);
         match self.content_type {
             ClipboardContentType::Text => {
+                let selection_suffix = if self.selection == Selection::Primary {
+                    " · Primary"
+                } else {
+                    ""
+                };
+                let rich_suffix = if self.rich_content.is_some() {
+                    " · <>"
+                } else {
+                    ""
+                };
+                let truncated_suffix = if self.truncated {
+                    " · ✂ truncated"
+                } else {
+                    ""
+                };
                 format!(
-                    "{}{} {} · {} char",
+                    "{}{} {} · {} char{}{}{}{} · {}",
                     pin_prefix,
                     icon,
                     label,
-                    self.content.len()
+                    self.content.chars().count(),
+                    selection_suffix,
+                    rich_suffix,
+                    truncated_suffix,
+                    source_suffix,
+                    time
                 )
             }
             ClipboardContentType::Image => {
                 if let Some(info) = &self.image_info {
+                    let svg_suffix = if self.svg_content.is_some() {
+                        " · SVG"
+                    } else {
+                        ""
+                    };
                     format!(
-                        "{}{} {} · {}",
+                        "{}{} {} · {}{}{} · {}",
                         pin_prefix,
                         icon,
                         label,
-                        format_size(info.size_bytes)
+                        format_size(info.size_bytes),
+                        svg_suffix,
+                        source_suffix,
+                        time
                     )
                 } else {
-                    format!("{}{} {} · Unknown size", pin_prefix, icon, label)
+                    format!(
+                        "{}{} {} · Unknown size{} · {}",
+                        pin_prefix, icon, label, source_suffix, time
+                    )
                 }
             }
+            ClipboardContentType::Binary => {
+                let size = self
+                    .binary_info
+                    .as_ref()
+                    .map_or_else(|| "Unknown size".to_string(), |i| format_size(i.size_bytes));
+                format!(
+                    "{}{} {} · {}{} · {}",
+                    pin_prefix, icon, label, size, source_suffix, time
+                )
+            }
         }
     }
 
-    /// Generate preview lines for display in the TUI.
+    /// Generate preview lines for display in the TUI, `width` columns wide.
     /// If `reveal` is true, show the actual content even for secrets.
-    pub fn preview_lines_with_reveal(&self, reveal: bool) -> Vec<String> {
+    pub fn preview_lines_with_reveal(&self, reveal: bool, width: usize) -> Vec<String> {
         // Mask secret content unless revealed
         if self.is_secret() && !reveal {
             let provider = self
@@ -610,8 +963,8 @@ impl ClipboardEntry {
 
             // Show masked content with a hint of the beginning
             let trimmed = self.content.trim();
-            let mask = if trimmed.len() > 6 {
-                let visible = &trimmed[..4];
+            let mask = if trimmed.chars().count() > 6 {
+                let visible: String = trimmed.chars().take(4).collect();
                 format!("{}{}", visible, "•".repeat(20))
             } else {
                 "•".repeat(20)
@@ -620,56 +973,218 @@ impl ClipboardEntry {
             return vec![format!("{} — {}", provider, mask)];
         }
 
-        self.preview_lines()
+        self.preview_lines(width)
     }
 
-    pub fn preview_lines(&self) -> Vec<String> {
+    /// Text wrapped to `width` columns in up to `preview_max_lines` lines
+    /// (one line for images and binary data).
+    /// Never empty, so every list item keeps at least one content row.
+    pub fn preview_lines(&self, width: usize) -> Vec<String> {
         match self.content_type {
+            // A blob would only fill both lines with noise; show its ends instead
+            ClipboardContentType::Text if self.detected_kind == DetectedKind::Blob => {
+                let chars: Vec<char> = self.content.trim().chars().collect();
+                let head: String = chars[..20].iter().collect();
+                let tail: String = chars[chars.len() - 10..].iter().collect();
+                let line = format!("{}…{} ({} chars)", head, tail, chars.len());
+                vec![truncate_to_width(&line, width)]
+            }
             ClipboardContentType::Text => {
                 // Normalize text: replace newlines/tabs with spaces to treat as continuous flow
                 let clean_text = self.content.replace(['\n', '\t'], " ");
                 let words: Vec<&str> = clean_text.split_whitespace().collect();
-
-                let mut lines: Vec<String> = Vec::new();
-                let mut current_line = String::new();
-                let max_width = 85; // Approximate width to fit comfortably in the box
-
-                for word in words {
-                    if lines.len() >= 2 {
-                        // If we are about to start a 3rd line, stick "..." at end of 2nd and stop
-                        let last_idx = lines.len() - 1;
-                        if lines[last_idx].len() + 3 <= max_width {
-                            lines[last_idx].push_str("...");
-                        }
-                        break;
-                    }
-
-                    if current_line.len() + word.len() + 1 > max_width {
-                        if !current_line.is_empty() {
-                            lines.push(current_line);
-                            current_line = String::new();
-                        }
-                    }
-
-                    if !current_line.is_empty() {
-                        current_line.push(' ');
-                    }
-                    current_line.push_str(word);
+                if words.is_empty() {
+                    return vec![String::from("(empty)")];
                 }
-
-                if lines.len() < 2 && !current_line.is_empty() {
-                    lines.push(current_line);
-                }
-
-                lines
+                wrap_words(&words, width, config().preview_max_lines)
             }
             ClipboardContentType::Image => {
                 if let Some(info) = &self.image_info {
-                    vec![format!("Image {}×{}", info.width, info.height)]
+                    match &info.format {
+                        Some(format) => vec![format!(
+                            "Image {}×{} {}",
+                            info.width,
+                            info.height,
+                            format.to_uppercase()
+                        )],
+                        None => vec![format!("Image {}×{}", info.width, info.height)],
+                    }
                 } else {
                     vec![String::from("Image")]
                 }
             }
+            ClipboardContentType::Binary => match &self.binary_info {
+                Some(info) => vec![truncate_to_width(&info.mime, width)],
+                None => vec![String::from("Binary data")],
+            },
+        }
+    }
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+/// Cut `text` to `width` terminal columns, ending it with "…" if anything
+/// was left out.
+fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    format!("{}…", prefix_within(text, width.saturating_sub(1)))
+}
+
+/// The longest start of `text` that fits in `width` terminal columns,
+/// never splitting a grapheme (e.g. an emoji made of several code points).
+fn prefix_within(text: &str, width: usize) -> &str {
+    let mut kept_width = 0;
+    for (i, grapheme) in text.grapheme_indices(true) {
+        kept_width += grapheme.width();
+        if kept_width > width {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// Lay `words` out in lines of at most `width` columns, breaking a word
+/// wider than a line between graphemes. Past `max_lines` the last line
+/// ends with "…".
+fn wrap_words(words: &[&str], width: usize, max_lines: usize) -> Vec<String> {
+    let width = width.max(2);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+
+    for word in words {
+        let word_width = word.width();
+        if !current.is_empty() && current_width + 1 + word_width <= width {
+            current.push(' ');
+            current.push_str(word);
+            current_width += 1 + word_width;
+            continue;
+        }
+        if !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+            current_width = 0;
+        }
+        for grapheme in word.graphemes(true) {
+            let grapheme_width = grapheme.width();
+            if current_width + grapheme_width > width {
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+            }
+            current.push_str(grapheme);
+            current_width += grapheme_width;
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    if lines.len() > max_lines.max(1) {
+        lines.truncate(max_lines.max(1));
+        if let Some(last) = lines.last_mut() {
+            // Force the ellipsis even when the line isn't full
+            *last = format!("{}…", prefix_within(last, width - 1));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cut_off_full_line_ends_with_an_ellipsis() {
+        let lines = wrap_words(&["aaaa", "bbbb", "cccc"], 4, 2);
+        assert_eq!(lines, ["aaaa", "bbb…"]);
+    }
+
+    #[test]
+    fn cut_off_short_line_still_ends_with_an_ellipsis() {
+        let lines = wrap_words(&["aaaa", "bb", "cccc"], 4, 2);
+        assert_eq!(lines, ["aaaa", "bb…"]);
+    }
+
+    /// Family and flag emoji (several code points each), CJK and a combining accent
+    const MIXED: &str = "👨‍👩‍👧 🇯🇵 漢字かな Cafe\u{301} 👍🏽";
+
+    fn starts_at_grapheme_boundary(text: &str, cut: usize) -> bool {
+        cut == text.len() || text.grapheme_indices(true).any(|(i, _)| i == cut)
+    }
+
+    #[test]
+    fn truncation_keeps_graphemes_whole_and_fits() {
+        for width in 1..=MIXED.width() + 1 {
+            let truncated = truncate_to_width(MIXED, width);
+            assert!(
+                truncated.width() <= width,
+                "{:?} wider than {}",
+                truncated,
+                width
+            );
+            let kept = truncated.strip_suffix('…').unwrap_or(&truncated);
+            assert!(MIXED.starts_with(kept));
+            assert!(
+                starts_at_grapheme_boundary(MIXED, kept.len()),
+                "{:?} splits a grapheme",
+                kept
+            );
+        }
+    }
+
+    #[test]
+    fn wrapping_keeps_graphemes_whole_and_fits() {
+        let words: Vec<&str> = MIXED.split_whitespace().collect();
+        for width in 2..=MIXED.width() + 1 {
+            let lines = wrap_words(&words, width, usize::MAX);
+            for line in &lines {
+                assert!(line.width() <= width, "{:?} wider than {}", line, width);
+                for grapheme in line.graphemes(true) {
+                    assert!(MIXED.graphemes(true).any(|g| g == grapheme) || grapheme == " ");
+                }
+            }
+            assert_eq!(lines.concat().replace(' ', ""), words.concat());
         }
     }
+
+    #[test]
+    fn text_entry_previews_its_content() {
+        let entry = ClipboardEntry::new_text("hello  world\n".to_string());
+        assert_eq!(entry.preview_lines(40), ["hello world"]);
+        assert!(entry.metadata_label(false).contains("13 char"));
+    }
+
+    #[test]
+    fn empty_entry_previews_a_placeholder() {
+        let entry = ClipboardEntry::new_text(" \n\t".to_string());
+        assert_eq!(entry.preview_lines(40), ["(empty)"]);
+        assert!(entry.metadata_label(false).contains("3 char"));
+    }
+
+    #[test]
+    fn long_entry_preview_is_capped() {
+        let entry = ClipboardEntry::new_text("word ".repeat(200));
+        let lines = entry.preview_lines(20);
+        assert_eq!(lines.len(), config().preview_max_lines);
+        assert!(lines.iter().all(|line| line.width() <= 20));
+        assert!(lines.last().unwrap().ends_with('…'));
+    }
+
+    #[test]
+    fn image_entry_shows_dimensions_and_size() {
+        let info = ImageInfo {
+            width: 1920,
+            height: 1080,
+            size_bytes: 2048,
+            format: Some("png".to_string()),
+            original_size_bytes: None,
+        };
+        let entry = ClipboardEntry::new_image("img_1.png".to_string(), info, 1);
+        assert_eq!(entry.preview_lines(40), ["Image 1920×1080 PNG"]);
+        let label = entry.metadata_label(false);
+        assert!(label.contains("Image · 2.0 KB"), "{}", label);
+    }
 }
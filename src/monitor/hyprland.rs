@@ -1,5 +1,11 @@
 use std::env;
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+
+use crate::utils::SOURCE_QUERY_TIMEOUT_MS;
 
 pub fn apply_hyprland_rules() {
     // Check if we are running in Hyprland
@@ -9,20 +15,20 @@ pub fn apply_hyprland_rules() {
     let version = get_hyprland_version();
 
     if !has_env && version.is_none() {
-        println!("i Hyprland not detected (no env var or hyprctl response). Skipping auto-config.");
+        info!("Hyprland not detected (no env var or hyprctl response). Skipping auto-config.");
         return;
     }
 
-    println!("⚡ Detected Hyprland session, attempting to apply window rules...");
+    info!("Detected Hyprland session, attempting to apply window rules...");
     
     // Default to older version logic if version check fails, or check semantic version
     // Hyprland versions are typically like "v0.39.1" or "0.39.1"
     
     let is_v0_53_plus = if let Some(ver) = version {
-        println!("  ✓ Hyprland version detected: {}", ver);
+        info!("Hyprland version detected: {}", ver);
         is_version_ge(&ver, 0, 53)
     } else {
-        println!("  ! Could not detect Hyprland version, assuming older syntax.");
+        warn!("Could not detect Hyprland version, assuming older syntax.");
         false
     };
 
@@ -99,7 +105,7 @@ fn apply_rules_legacy() {
             .arg(rule.strip_prefix("windowrulev2 ").unwrap_or(rule))
             .status();
     }
-    println!("Applied legacy window rules (windowrulev2)");
+    info!("Applied legacy window rules (windowrulev2)");
 }
 
 fn apply_rules_v53() {
@@ -123,5 +129,35 @@ fn apply_rules_v53() {
             .arg(arg)
             .status();
     }
-    println!("Applied v0.53+ window rules (windowrule)");
+    info!("Applied v0.53+ window rules (windowrule)");
+}
+
+/// Class of the focused window (e.g. "firefox"), from `hyprctl activewindow`.
+/// Gives up after `SOURCE_QUERY_TIMEOUT_MS` so capture isn't held up.
+pub fn active_window_class() -> Option<String> {
+    let mut child = Command::new("hyprctl")
+        .args(["activewindow", "-j"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let deadline = Instant::now() + Duration::from_millis(SOURCE_QUERY_TIMEOUT_MS);
+    while child.try_wait().ok()?.is_none() {
+        if Instant::now() >= deadline {
+            debug!("hyprctl activewindow timed out");
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    let output = child.wait_with_output().ok()?;
+    let window: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    window
+        .get("class")
+        .and_then(|class| class.as_str())
+        .filter(|class| !class.is_empty())
+        .map(String::from)
 }
@@ -4,5 +4,6 @@ pub mod signal;
 pub use process::*;
 pub use signal::*;
 pub mod wayland;
+#[cfg(target_os = "linux")]
 pub mod hyprland;
 
@@ -1,65 +1,287 @@
-use std::fs;
-use std::path::PathBuf;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+#[cfg(not(target_os = "linux"))]
+use std::process::Command;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
 
-use crate::clipboard::{
-    ClipboardBackend, get_clipboard_image, get_clipboard_text, get_clipboard_types,
-};
+use log::{debug, error, info};
+
+use crate::clipboard::{ClipboardProvider, is_image_data, offered_binary_type};
+use crate::config::config;
 use crate::history::ClipboardHistory;
-use crate::utils::{PID_FILE, POLL_INTERVAL_MS};
+#[cfg(target_os = "linux")]
+use crate::history::custom_data_dir;
+use crate::models::Selection;
+#[cfg(target_os = "linux")]
+use crate::utils::DATA_DIR_ENV_VAR;
+use crate::utils::{DAEMON_LOCK_FILE, PID_FILE, POLL_INTERVAL_MS};
 
 // ============================================================================
 // PID FILE MANAGEMENT
 // ============================================================================
 
-pub fn write_pid_file(data_dir: &PathBuf) -> Result<(), std::io::Error> {
+pub fn write_pid_file(data_dir: &Path) -> Result<(), std::io::Error> {
     let pid_path = data_dir.join(PID_FILE);
     fs::write(pid_path, std::process::id().to_string())
 }
 
+/// Held by the daemon while it runs. The OS drops the lock when the process
+/// exits, so a crashed daemon doesn't block the next one.
+pub struct DaemonLock {
+    _file: File,
+}
+
+/// Become the only daemon for `data_dir`: lock `DAEMON_LOCK_FILE`, then write
+/// the PID file. Fails if another daemon holds the lock.
+pub fn lock_daemon(data_dir: &Path) -> Result<DaemonLock, String> {
+    let path = data_dir.join(DAEMON_LOCK_FILE);
+    let file = fs::create_dir_all(data_dir)
+        .and_then(|_| {
+            OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(false)
+                .open(&path)
+        })
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            return Err(match running_daemon_pid(data_dir) {
+                Some(pid) => format!("daemon already running (pid {})", pid),
+                None => "daemon already running".to_string(),
+            });
+        }
+        Err(TryLockError::Error(e)) => {
+            return Err(format!("Failed to lock {}: {}", path.display(), e));
+        }
+    }
+    write_pid_file(data_dir).map_err(|e| format!("Failed to write the PID file: {}", e))?;
+    Ok(DaemonLock { _file: file })
+}
+
+/// Returns the PID of another daemon that is still running, according to the PID file.
+/// Stale PID files (process gone, or PID reused by a different program) return None.
+pub fn running_daemon_pid(data_dir: &Path) -> Option<u32> {
+    let pid: u32 = fs::read_to_string(data_dir.join(PID_FILE))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+
+    if pid == std::process::id() {
+        return None;
+    }
+
+    // The PID may have been reused after a crash, so also compare process names
+    let name = process_name(pid)?;
+    (name == process_name(std::process::id())?).then_some(pid)
+}
+
+/// Name of the running process `pid`, or None when there is none.
+#[cfg(target_os = "linux")]
+fn process_name(pid: u32) -> Option<String> {
+    let comm = fs::read_to_string(PathBuf::from("/proc").join(pid.to_string()).join("comm"));
+    Some(comm.ok()?.trim().to_string())
+}
+
+/// Name of the running process `pid`, or None when there is none. Without
+/// /proc (macOS, BSDs) `ps` is asked; it may print the full path.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_name(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let comm = String::from_utf8_lossy(&output.stdout);
+    let name = Path::new(comm.trim()).file_name()?;
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Name of the running process `pid`, or None when there is none, from
+/// `tasklist`'s CSV output (`"name.exe","pid",...`).
+#[cfg(not(unix))]
+fn process_name(pid: u32) -> Option<String> {
+    let output = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let name = listing.lines().next()?.split(',').next()?.trim_matches('"');
+    (!name.is_empty() && listing.contains(&format!("\"{}\"", pid))).then(|| name.to_string())
+}
+
 pub fn remove_pid_file(data_dir: &PathBuf) {
     let _ = fs::remove_file(data_dir.join(PID_FILE));
 }
 
+/// systemd user unit name. A copy of the last generated unit is kept in the
+/// data dir to tell generated files from user-edited ones.
+#[cfg(target_os = "linux")]
+pub const SYSTEMD_UNIT_FILE: &str = "clipboard-manager.service";
+
+#[cfg(target_os = "linux")]
+pub fn get_systemd_unit_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("systemd").join("user").join(SYSTEMD_UNIT_FILE))
+}
+
+/// A systemd user service running the daemon without banner or hints.
+/// A custom data dir is passed on, so the service uses the same history.
+#[cfg(target_os = "linux")]
+pub fn systemd_unit_content(binary_path: &str) -> String {
+    let environment = custom_data_dir()
+        .map(|dir| format!("Environment=\"{}={}\"\n", DATA_DIR_ENV_VAR, dir.display()))
+        .unwrap_or_default();
+    format!(
+        r#"[Unit]
+Description=Clipboard Manager daemon
+PartOf=graphical-session.target
+After=graphical-session.target
+
+[Service]
+{}ExecStart="{}" --daemon-quiet
+Restart=on-failure
+
+[Install]
+WantedBy=graphical-session.target
+"#,
+        environment, binary_path
+    )
+}
+
+/// Install the systemd user unit in `~/.config/systemd/user/`. A unit the
+/// user has edited is left alone (see `write_generated`).
+#[cfg(target_os = "linux")]
+pub fn create_systemd_unit(data_dir: &Path, binary_path: &str) -> Result<(), std::io::Error> {
+    let Some(unit_path) = get_systemd_unit_path() else {
+        return Ok(());
+    };
+    if let Some(parent) = unit_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content = systemd_unit_content(binary_path);
+    write_generated(&unit_path, &data_dir.join(SYSTEMD_UNIT_FILE), &content)
+}
+
+/// Write a generated file, remembering what was written in `generated_copy`.
+/// A file the user has edited (it matches neither the new nor the last
+/// generated content) is left alone.
+#[cfg(target_os = "linux")]
+fn write_generated(path: &Path, generated_copy: &Path, content: &str) -> std::io::Result<()> {
+    match fs::read_to_string(path) {
+        Ok(existing) if existing == content => {}
+        Ok(existing) if fs::read_to_string(generated_copy).is_ok_and(|g| g == existing) => {
+            fs::write(path, content)?;
+        }
+        Ok(_) => {
+            info!("Keeping user-modified {}", path.display());
+            return Ok(());
+        }
+        Err(_) => fs::write(path, content)?,
+    }
+
+    fs::write(generated_copy, content)
+}
+
+#[cfg(target_os = "linux")]
 pub fn get_trigger_script_path(data_dir: &PathBuf) -> PathBuf {
     data_dir.join("trigger.sh")
 }
 
-pub fn create_trigger_script(data_dir: &PathBuf, binary_path: &str) -> Result<(), std::io::Error> {
-    let script_path = get_trigger_script_path(data_dir);
-
-    let script_content = format!(
-        r#"#!/bin/bash
-BINARY="{}"
+/// Window class of the picker terminal, matched by the Hyprland rules
+#[cfg(target_os = "linux")]
+const TERMINAL_CLASS: &str = "floating-clipboard";
 
-if command -v kitty &> /dev/null; then
+/// The first installed terminal opens the picker
+#[cfg(target_os = "linux")]
+const TERMINAL_CASCADE: &str = r#"if command -v kitty &> /dev/null; then
     kitty --class floating-clipboard \
           --title "Clipboard Manager" \
           -o initial_window_width=900 \
           -o initial_window_height=600 \
           -o remember_window_size=no \
-          "$BINARY" --ui &
+          "$BINARY" --ui $UI_ARGS &
 elif command -v alacritty &> /dev/null; then
     alacritty --class floating-clipboard \
               --title "Clipboard Manager" \
               -o window.dimensions.columns=100 \
               -o window.dimensions.lines=30 \
-              -e "$BINARY" --ui &
+              -e "$BINARY" --ui $UI_ARGS &
 elif command -v foot &> /dev/null; then
     foot --app-id=floating-clipboard \
          --title="Clipboard Manager" \
          --window-size-chars=100x30 \
-         "$BINARY" --ui &
+         "$BINARY" --ui $UI_ARGS &
+elif command -v wezterm &> /dev/null; then
+    wezterm start --class floating-clipboard \
+                  -- "$BINARY" --ui $UI_ARGS &
+elif command -v ghostty &> /dev/null; then
+    ghostty --class=floating-clipboard \
+            --title="Clipboard Manager" \
+            --window-width=100 \
+            --window-height=30 \
+            -e "$BINARY" --ui $UI_ARGS &
+elif command -v xterm &> /dev/null; then
+    xterm -class floating-clipboard \
+          -title "Clipboard Manager" \
+          -geometry 100x30 \
+          -e "$BINARY" --ui $UI_ARGS &
 else
     notify-send "Clipboard Manager" "No suitable terminal found"
 fi
-"#,
-        binary_path
+"#;
+
+/// The script line running `terminal_command`: `{binary}` becomes the
+/// clipboard-manager path, `{args}` the `--ui` arguments and `{class}` the
+/// window class.
+#[cfg(target_os = "linux")]
+fn custom_terminal_launch(command: &str) -> String {
+    format!(
+        "{} &\n",
+        command
+            .replace("{binary}", "\"$BINARY\"")
+            .replace("{args}", "$UI_ARGS")
+            .replace("{class}", TERMINAL_CLASS)
+    )
+}
+
+#[cfg(target_os = "linux")]
+/// `ui_args` are appended to `--ui`, e.g. `--backend xclip` so the picker
+/// uses the same backend as the daemon. `terminal_command` from the config
+/// replaces the built-in terminal cascade. A script the user has edited is
+/// kept; otherwise it is rewritten whenever its content would change.
+pub fn create_trigger_script(
+    data_dir: &PathBuf,
+    binary_path: &str,
+    ui_args: &str,
+) -> Result<(), std::io::Error> {
+    let script_path = get_trigger_script_path(data_dir);
+
+    let launch = match &config().terminal_command {
+        Some(command) => custom_terminal_launch(command),
+        None => TERMINAL_CASCADE.to_string(),
+    };
+    // A custom data dir is exported, so the picker reads the same history
+    let environment = custom_data_dir()
+        .map(|dir| format!("export {}=\"{}\"\n", DATA_DIR_ENV_VAR, dir.display()))
+        .unwrap_or_default();
+    let script_content = format!(
+        "#!/bin/bash\nBINARY=\"{}\"\nUI_ARGS=\"{}\"\n{}\n{}",
+        binary_path, ui_args, environment, launch
     );
 
-    fs::write(&script_path, script_content)?;
+    write_generated(
+        &script_path,
+        &data_dir.join("trigger.sh.generated"),
+        &script_content,
+    )?;
 
     #[cfg(unix)]
     {
@@ -76,57 +298,178 @@ fi
 // POLLING MONITOR (FALLBACK)
 // ============================================================================
 
-pub fn monitor_loop(history: Arc<ClipboardHistory>, backend: ClipboardBackend) {
-    println!("📋 Clipboard monitor started (Polling Fallback)");
+/// Hash of captured content, to tell a new copy from the last one
+pub(crate) fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Track a newly seen clipboard hash and return true once it has been seen on
+/// `debounce_polls` consecutive polls, so multi-step writes settle first.
+fn settled(pending_hash: &mut Option<u64>, pending_since: &mut u64, hash: u64, poll: u64) -> bool {
+    if *pending_hash != Some(hash) {
+        *pending_hash = Some(hash);
+        *pending_since = poll;
+    }
+    poll - *pending_since + 1 >= config().debounce_polls.max(1) as u64
+}
+
+/// `quiet` (from `--daemon-quiet` / `daemon_quiet`) turns off the heartbeat.
+/// Returns once `shutdown` is set.
+pub fn monitor_loop(
+    history: Arc<ClipboardHistory>,
+    clipboard: &dyn ClipboardProvider,
+    quiet: bool,
+    shutdown: &AtomicBool,
+) {
+    info!("Clipboard monitor started (Polling Fallback)");
 
     let mut last_text_hash: Option<u64> = None;
     let mut last_image_hash: Option<u64> = None;
+    let mut last_binary_hash: Option<u64> = None;
+    let mut pending_hash: Option<u64> = None;
+    let mut pending_since = 0u64;
     let mut poll_count = 0u64;
 
-    loop {
+    while !shutdown.load(Ordering::Relaxed) {
         thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
         poll_count += 1;
 
         // Heartbeat every ~10 seconds
-        if poll_count % 67 == 0 {
-
-            // println!("💓 Monitor active - {} items in history", count);
+        if !quiet && poll_count % 67 == 0 {
+            debug!(
+                "Monitor active - {} items in history",
+                history.get_all().len()
+            );
         }
 
         // Check for images first (higher priority)
-        let types = get_clipboard_types(backend);
+        let types = clipboard.get_types();
+
+        // Skip content marked as sensitive (e.g. copied from a password manager)
+        if config().has_sensitive_type(&types) {
+            continue;
+        }
+
         let has_image = types.iter().any(|t| t.starts_with("image/"));
 
-        if has_image {
-            if let Some(image_data) = get_clipboard_image(backend) {
-                use std::collections::hash_map::DefaultHasher;
-                use std::hash::{Hash, Hasher};
+        // Some compositors offer image types for content that isn't an image
+        // (e.g. a file reference); that is captured as text below instead
+        let image_data = has_image
+            .then(|| clipboard.get_image(&types))
+            .flatten()
+            .filter(|data| is_image_data(data));
 
-                let mut hasher = DefaultHasher::new();
-                image_data.hash(&mut hasher);
-                let hash = hasher.finish();
+        if let Some(image_data) = image_data {
+            let hash = hash_of(&image_data);
+
+            if Some(hash) != last_image_hash
+                && settled(&mut pending_hash, &mut pending_since, hash, poll_count)
+            {
+                let svg = clipboard.get_svg(&types);
+                if let Err(e) = history.add_image(image_data, types.clone(), svg) {
+                    error!("Failed to add image: {}", e);
+                }
+                // Remembered even on failure so a full or read-only disk
+                // doesn't make every poll retry the same image
+                last_image_hash = Some(hash);
+                last_text_hash = None;
+                last_binary_hash = None;
+            }
+        } else if let Some(mime) = offered_binary_type(&types) {
+            // Whitelisted types win over the plain-text fallback
+            if let Some(data) = clipboard.get_data(mime) {
+                let hash = hash_of(&data);
 
-                if Some(hash) != last_image_hash {
-                    if let Err(e) = history.add_image(image_data) {
-                        eprintln!("Failed to add image: {}", e);
+                if Some(hash) != last_binary_hash
+                    && settled(&mut pending_hash, &mut pending_since, hash, poll_count)
+                {
+                    if let Err(e) = history.add_binary(data, mime.to_string()) {
+                        error!("Failed to add {} data: {}", mime, e);
                     }
-                    last_image_hash = Some(hash);
+                    last_binary_hash = Some(hash);
                     last_text_hash = None;
+                    last_image_hash = None;
                 }
             }
-        } else if let Some(content) = get_clipboard_text(backend) {
-            use std::collections::hash_map::DefaultHasher;
-            use std::hash::{Hash, Hasher};
-
-            let mut hasher = DefaultHasher::new();
-            content.hash(&mut hasher);
-            let hash = hasher.finish();
+        } else if let Some(content) = clipboard.get_text() {
+            let hash = hash_of(&content);
 
-            if Some(hash) != last_text_hash {
-                history.add_text(content);
+            if Some(hash) != last_text_hash
+                && settled(&mut pending_hash, &mut pending_since, hash, poll_count)
+            {
+                let html = clipboard.get_html(&types);
+                if let Err(e) = history.add_text(content, Selection::Clipboard, html) {
+                    error!("Failed to add text: {}", e);
+                }
                 last_text_hash = Some(hash);
                 last_image_hash = None;
+                last_binary_hash = None;
             }
         }
     }
 }
+
+// ============================================================================
+// PRIMARY SELECTION MONITOR
+// ============================================================================
+
+/// Poll the middle-click PRIMARY selection and store its text as
+/// `Selection::Primary`, until `shutdown` is set.
+pub fn primary_monitor_loop(
+    history: Arc<ClipboardHistory>,
+    clipboard: &dyn ClipboardProvider,
+    shutdown: &AtomicBool,
+) {
+    info!("Primary selection monitor started");
+
+    let mut last_hash: Option<u64> = None;
+    let mut pending_hash: Option<u64> = None;
+    let mut pending_since = 0u64;
+    let mut poll_count = 0u64;
+
+    while !shutdown.load(Ordering::Relaxed) {
+        thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+        poll_count += 1;
+
+        if let Some(content) = clipboard.get_primary_text() {
+            let hash = hash_of(&content);
+
+            // Debouncing also skips the intermediate states of a drag-selection
+            if Some(hash) != last_hash
+                && settled(&mut pending_hash, &mut pending_since, hash, poll_count)
+            {
+                if let Err(e) = history.add_text(content, Selection::Primary, None) {
+                    error!("Failed to add text: {}", e);
+                }
+                last_hash = Some(hash);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::scratch_history;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn terminal_command_placeholders_are_filled_in() {
+        let launch = custom_terminal_launch("st -c {class} -e {binary} --ui {args}");
+        assert_eq!(
+            launch,
+            "st -c floating-clipboard -e \"$BINARY\" --ui $UI_ARGS &\n"
+        );
+    }
+
+    #[test]
+    fn only_one_daemon_holds_the_lock() {
+        let dir = scratch_history("daemon-lock").data_dir().clone();
+        let lock = lock_daemon(&dir).unwrap();
+        assert!(lock_daemon(&dir).is_err());
+        drop(lock);
+        assert!(lock_daemon(&dir).is_ok());
+    }
+}
@@ -1,47 +1,308 @@
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread;
-
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
+use log::{info, warn};
+#[cfg(unix)]
 use signal_hook::consts::signal::*;
+#[cfg(unix)]
 use signal_hook::iterator::Signals;
-use crate::clipboard::ClipboardBackend;
+
+use crate::clipboard::{ClipboardBackend, ClipboardProvider};
+use crate::config::config;
 use crate::history::ClipboardHistory;
+use crate::utils::{
+    BACKEND_CHECK_INTERVAL_SECS, BACKEND_FILE, PAUSE_FILE, TTL_SWEEP_INTERVAL_SECS, notify_desktop,
+};
+
+/// While set, the monitors keep tracking the clipboard but nothing is stored
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+// ============================================================================
+// PAUSE (INCOGNITO) MODE
+// ============================================================================
+
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// Flip the pause state and mirror it in the status file the UI reads.
+pub fn toggle_pause(data_dir: &Path) {
+    let paused = !PAUSED.fetch_xor(true, Ordering::Relaxed);
+    let status_path = data_dir.join(PAUSE_FILE);
+    let result = if paused {
+        fs::write(&status_path, "")
+    } else {
+        fs::remove_file(&status_path)
+    };
+    if let Err(e) = result {
+        warn!("Failed to update {}: {}", status_path.display(), e);
+    }
+    info!("Capture {}", if paused { "paused" } else { "resumed" });
+}
+
+/// A daemon always starts recording; drop a status file left by a crash.
+pub fn clear_pause_file(data_dir: &Path) {
+    let _ = fs::remove_file(data_dir.join(PAUSE_FILE));
+}
+
+// ============================================================================
+// BACKEND STATUS
+// ============================================================================
+
+/// Record the daemon's backend in the status file the UI reads, marked
+/// "disconnected" once the session it talks to is gone.
+pub fn write_backend_file(data_dir: &Path, backend: ClipboardBackend, connected: bool) {
+    let path = data_dir.join(BACKEND_FILE);
+    let status = if connected {
+        backend.name().to_string()
+    } else {
+        format!("{} disconnected", backend.name())
+    };
+    if let Err(e) = fs::write(&path, status) {
+        warn!("Failed to update {}: {}", path.display(), e);
+    }
+}
+
+pub fn clear_backend_file(data_dir: &Path) {
+    let _ = fs::remove_file(data_dir.join(BACKEND_FILE));
+}
+
+/// The running daemon's backend name and whether its session is still there.
+/// None when no daemon is running.
+pub fn read_backend_file(data_dir: &Path) -> Option<(String, bool)> {
+    let status = fs::read_to_string(data_dir.join(BACKEND_FILE)).ok()?;
+    let mut words = status.split_whitespace();
+    let name = words.next()?.to_string();
+    Some((name, words.next() != Some("disconnected")))
+}
 
+/// Record `backend` in the status file and keep it up to date from a
+/// thread that ends on shutdown.
+pub fn start_backend_watch(
+    backend: ClipboardBackend,
+    data_dir: PathBuf,
+    shutdown: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    write_backend_file(&data_dir, backend, true);
+    thread::spawn(move || watch_backend_session(backend, &data_dir, &shutdown))
+}
 
+/// Watch for the display session the daemon started in going away, e.g.
+/// after logging out of Wayland into X11 with the daemon left running.
+/// The daemon's environment is fixed at startup, so re-running detection
+/// would always agree with itself; this checks the session's socket instead.
+fn watch_backend_session(backend: ClipboardBackend, data_dir: &Path, shutdown: &AtomicBool) {
+    let interval = Duration::from_secs(BACKEND_CHECK_INTERVAL_SECS);
+    let mut connected = true;
+    while !sleep_unless_shutdown(shutdown, interval) {
+        if backend.session_available() == connected {
+            continue;
+        }
+        connected = !connected;
+        write_backend_file(data_dir, backend, connected);
+        if connected {
+            info!("The {} session is back, capturing again", backend.name());
+        } else {
+            warn!(
+                "The {} session this daemon started in is gone; nothing is captured until the daemon is restarted",
+                backend.name()
+            );
+            notify_desktop(
+                "Clipboard capture stopped",
+                "The display session the daemon started in is gone. Restart the daemon to capture again.",
+            );
+        }
+    }
+}
 
 // ============================================================================
 // SIGNAL LISTENER
 // ============================================================================
 
-pub fn start_signal_listener(shutdown_trigger: Arc<AtomicBool>) {
-    thread::spawn(move || {
-        if let Ok(mut signals) = Signals::new(&[SIGTERM, SIGINT]) {
+/// SIGTERM/SIGINT request a shutdown; SIGUSR1 toggles pause. The listener
+/// thread ends once it has requested the shutdown.
+#[cfg(unix)]
+pub fn start_signal_listener(
+    shutdown_trigger: Arc<AtomicBool>,
+    data_dir: PathBuf,
+) -> Option<JoinHandle<()>> {
+    Some(thread::spawn(move || {
+        if let Ok(mut signals) = Signals::new(&[SIGTERM, SIGINT, SIGUSR1]) {
             for signal in signals.forever() {
-                if signal == SIGTERM || signal == SIGINT {
+                if signal == SIGUSR1 {
+                    toggle_pause(&data_dir);
+                } else if signal == SIGTERM || signal == SIGINT {
                     shutdown_trigger.store(true, Ordering::Relaxed);
                     break;
                 }
             }
         }
-    });
+    }))
+}
+
+/// Windows has no SIGTERM; Ctrl+C (and console close) is the shutdown request.
+#[cfg(not(unix))]
+pub fn start_signal_listener(
+    shutdown_trigger: Arc<AtomicBool>,
+    _data_dir: PathBuf,
+) -> Option<JoinHandle<()>> {
+    if let Err(e) = ctrlc::set_handler(move || shutdown_trigger.store(true, Ordering::Relaxed)) {
+        warn!("Failed to install Ctrl+C handler: {}", e);
+    }
+    None
+}
+
+// ============================================================================
+// SHUTDOWN
+// ============================================================================
+
+/// Sleep for `duration`, waking early once `shutdown` is set.
+/// Returns true when shutting down.
+pub fn sleep_unless_shutdown(shutdown: &AtomicBool, duration: Duration) -> bool {
+    let deadline = Instant::now() + duration;
+    while !shutdown.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(100)));
+    }
+    true
+}
+
+/// Join the daemon's threads once they've been asked to stop, giving up on
+/// any still running after `timeout` (they end with the process).
+pub fn join_threads(threads: Vec<JoinHandle<()>>, timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline && !threads.iter().all(JoinHandle::is_finished) {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let mut stuck = 0;
+    for handle in threads {
+        if handle.is_finished() {
+            let _ = handle.join();
+        } else {
+            stuck += 1;
+        }
+    }
+    if stuck > 0 {
+        warn!("{} threads didn't stop within {:?}", stuck, timeout);
+    }
 }
 
 // ============================================================================
 // CLIPBOARD MONITORING
 // ============================================================================
 
-pub fn start_clipboard_monitor(history: Arc<ClipboardHistory>, backend: ClipboardBackend) {
-    // Attempt to configure Hyprland window rules automatically
-    crate::monitor::hyprland::apply_hyprland_rules();
-
-    if matches!(backend, ClipboardBackend::WlClipboard) {
+/// Start the monitor threads and return them, to be joined on shutdown.
+/// With `watch`, `wl-paste --watch` signals changes and `clipboard` is read
+/// on each; otherwise `clipboard` is polled. Polling loops stop once
+/// `shutdown` is set; the Wayland watchers stop when `stop_wayland_watchers`
+/// kills their `wl-paste` processes.
+pub fn start_clipboard_monitor(
+    history: Arc<ClipboardHistory>,
+    clipboard: Arc<dyn ClipboardProvider>,
+    watch: bool,
+    quiet: bool,
+    shutdown: Arc<AtomicBool>,
+) -> Vec<JoinHandle<()>> {
+    let mut threads = Vec::new();
+    if watch {
         // Use event-driven watcher for Wayland
-        crate::monitor::wayland::monitor_wayland(history);
+        threads.extend(crate::monitor::wayland::monitor_wayland(
+            Arc::clone(&history),
+            Arc::clone(&clipboard),
+        ));
     } else {
         // Fallback to polling for other backends (e.g. Arboard/X11)
-        thread::spawn(move || {
-            crate::monitor::process::monitor_loop(history, backend);
-        });
+        let history = Arc::clone(&history);
+        let clipboard = Arc::clone(&clipboard);
+        let shutdown = Arc::clone(&shutdown);
+        threads.push(thread::spawn(move || {
+            crate::monitor::process::monitor_loop(history, clipboard.as_ref(), quiet, &shutdown);
+        }));
+    }
+
+    if let Some(ttl_secs) = config().entry_ttl_secs {
+        let history = Arc::clone(&history);
+        let shutdown = Arc::clone(&shutdown);
+        threads.push(thread::spawn(move || {
+            loop {
+                history.cleanup_stale(ttl_secs);
+                let interval = Duration::from_secs(TTL_SWEEP_INTERVAL_SECS);
+                if sleep_unless_shutdown(&shutdown, interval) {
+                    break;
+                }
+            }
+        }));
+    }
+
+    // The Wayland watcher already follows the primary selection
+    if config().capture_primary && !watch {
+        threads.push(thread::spawn(move || {
+            crate::monitor::process::primary_monitor_loop(history, clipboard.as_ref(), &shutdown);
+        }));
+    }
+    threads
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::MemoryClipboard;
+    use crate::history::scratch_history;
+
+    /// Run the polling monitor on `clipboard` until `content` is the newest
+    /// entry, or give up after a few seconds.
+    fn capture(history: &Arc<ClipboardHistory>, clipboard: &Arc<MemoryClipboard>, content: &str) {
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let threads = start_clipboard_monitor(
+            Arc::clone(history),
+            Arc::clone(clipboard) as Arc<dyn ClipboardProvider>,
+            false,
+            true,
+            Arc::clone(&shutdown),
+        );
+        let newest_is_content = || {
+            history
+                .get_all()
+                .first()
+                .is_some_and(|entry| entry.content == content)
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !newest_is_content() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(20));
+        }
+        shutdown.store(true, Ordering::Relaxed);
+        join_threads(threads, Duration::from_secs(5));
+        assert!(newest_is_content(), "{:?} was not captured", content);
+    }
+
+    #[test]
+    fn copied_text_becomes_the_newest_entry() {
+        let history = Arc::new(scratch_history("monitor-capture"));
+        let clipboard = Arc::new(MemoryClipboard::default());
+        clipboard.copy_text("hello");
+        capture(&history, &clipboard, "hello");
+        assert_eq!(history.get_all().len(), 1);
+    }
+
+    #[test]
+    fn copying_the_same_text_again_is_deduplicated() {
+        let history = Arc::new(scratch_history("monitor-dedup"));
+        let clipboard = Arc::new(MemoryClipboard::default());
+        clipboard.copy_text("first");
+        capture(&history, &clipboard, "first");
+        clipboard.copy_text("second");
+        capture(&history, &clipboard, "second");
+        clipboard.copy_text("first");
+        capture(&history, &clipboard, "first");
+
+        let contents: Vec<_> = history.get_all().into_iter().map(|e| e.content).collect();
+        assert_eq!(contents, ["first", "second"]);
     }
 }
@@ -1,76 +1,206 @@
+use std::fs;
 use std::io::{BufRead, BufReader};
-use std::process::{Command, Stdio};
-use std::sync::Arc;
-use std::thread;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
 
-use crate::clipboard::{ClipboardBackend, get_clipboard_image, get_clipboard_text};
+use log::{error, info, warn};
+
+use crate::clipboard::{ClipboardProvider, is_image_data, offered_binary_type, offered_image_type};
+use crate::config::config;
 use crate::history::ClipboardHistory;
+use crate::models::Selection;
+use crate::monitor::hash_of;
+use crate::utils::WATCHER_PID_FILE;
+
+/// Running `wl-paste --watch` children, killed by `stop_wayland_watchers` on shutdown
+static WATCHERS: Mutex<Vec<Child>> = Mutex::new(Vec::new());
+
+/// Watch the clipboard (and the primary selection when `capture_primary` is
+/// set) for changes, one `wl-paste --watch` per selection. The returned
+/// threads end once `stop_wayland_watchers` has killed the watchers.
+pub fn monitor_wayland(
+    history: Arc<ClipboardHistory>,
+    clipboard: Arc<dyn ClipboardProvider>,
+) -> Vec<JoinHandle<()>> {
+    let mut threads = vec![spawn_watcher(
+        Arc::clone(&history),
+        Arc::clone(&clipboard),
+        Selection::Clipboard,
+    )];
+    if config().capture_primary {
+        threads.push(spawn_watcher(history, clipboard, Selection::Primary));
+    }
+    threads
+}
 
-pub fn monitor_wayland(history: Arc<ClipboardHistory>) {
+fn spawn_watcher(
+    history: Arc<ClipboardHistory>,
+    clipboard: Arc<dyn ClipboardProvider>,
+    selection: Selection,
+) -> JoinHandle<()> {
     thread::spawn(move || {
-        println!("Displaying Wayland watcher...");
-        
+        info!("Starting Wayland watcher ({:?})...", selection);
+
         // We use wl-paste --watch to output a delimiter "CHANGED" whenever clipboard content changes.
         // This avoids polling and uses Wayland's native change notification.
-        let mut cmd = Command::new("wl-paste")
+        let mut command = Command::new("wl-paste");
+        if selection == Selection::Primary {
+            command.arg("--primary");
+        }
+        let mut cmd = match command
             .arg("--watch")
             .arg("echo")
             .arg("CHANGED")
             .stdout(Stdio::piped())
             .spawn()
-            .expect("Failed to start wl-paste watcher");
+        {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                error!("Failed to start wl-paste watcher: {}", e);
+                return;
+            }
+        };
 
-        let stdout = cmd.stdout.take().expect("Failed to open stdout");
+        let Some(stdout) = cmd.stdout.take() else {
+            error!("Failed to open wl-paste watcher stdout");
+            return;
+        };
         let reader = BufReader::new(stdout);
+        {
+            let mut watchers = WATCHERS.lock().unwrap();
+            watchers.push(cmd);
+            record_watcher_pids(history.data_dir(), &watchers);
+        }
 
         let mut last_hash: Option<u64> = None;
 
-        for line in reader.lines() {
-            if let Ok(l) = line {
-                if l.trim() == "CHANGED" {
-                     handle_clipboard_change(&history, &mut last_hash);
-                }
+        // Ends when the watcher exits (or is killed on shutdown)
+        for line in reader.lines().map_while(Result::ok) {
+            if line.trim() == "CHANGED" {
+                handle_clipboard_change(&history, clipboard.as_ref(), selection, &mut last_hash);
             }
         }
-        
-        let _ = cmd.wait();
-    });
+    })
+}
+
+/// Kill the `wl-paste --watch` children so they don't outlive the daemon.
+pub fn stop_wayland_watchers(data_dir: &Path) {
+    for mut child in WATCHERS.lock().unwrap().drain(..) {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    let _ = fs::remove_file(data_dir.join(WATCHER_PID_FILE));
+}
+
+/// Remember the watcher PIDs so a daemon that died without cleaning up can
+/// have its orphans reaped by the next one.
+fn record_watcher_pids(data_dir: &Path, watchers: &[Child]) {
+    let pids: Vec<String> = watchers.iter().map(|c| c.id().to_string()).collect();
+    if let Err(e) = fs::write(data_dir.join(WATCHER_PID_FILE), pids.join("\n")) {
+        warn!("Failed to record watcher PIDs: {}", e);
+    }
+}
+
+/// Kill `wl-paste --watch` processes left behind by a previous daemon that
+/// crashed or was killed with SIGKILL. PIDs that have since been reused by
+/// another program are left alone.
+pub fn reap_stale_watchers(data_dir: &Path) {
+    let path = data_dir.join(WATCHER_PID_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return;
+    };
+
+    for pid in content.lines().filter_map(|l| l.trim().parse::<u32>().ok()) {
+        let Ok(cmdline) = fs::read(format!("/proc/{}/cmdline", pid)) else {
+            continue;
+        };
+        let args: Vec<&[u8]> = cmdline.split(|b| *b == 0).collect();
+        let is_watcher =
+            args.iter().any(|a| a.ends_with(b"wl-paste")) && args.iter().any(|a| *a == b"--watch");
+        if is_watcher {
+            info!("Reaping stale wl-paste watcher (pid {})", pid);
+            let _ = Command::new("kill").arg(pid.to_string()).status();
+        }
+    }
+
+    let _ = fs::remove_file(path);
 }
 
-fn handle_clipboard_change(history: &Arc<ClipboardHistory>, last_hash: &mut Option<u64>) {
-    // We assume Wayland backend since this is the specific Wayland monitor
-    let backend = ClipboardBackend::WlClipboard;
-    
-    // Check for images first
-    if let Some(image_data) = get_clipboard_image(backend) {
-         use std::collections::hash_map::DefaultHasher;
-         use std::hash::{Hash, Hasher};
-
-         let mut hasher = DefaultHasher::new();
-         image_data.hash(&mut hasher);
-         let hash = hasher.finish();
-         
-         if Some(hash) != *last_hash {
-             if let Err(e) = history.add_image(image_data) {
-                 eprintln!("Error adding image: {}", e);
-             }
-             *last_hash = Some(hash);
-         }
-         return;
+/// Store what `clipboard` holds after a change notification, unless it
+/// hashes the same as the last capture.
+pub fn handle_clipboard_change(
+    history: &Arc<ClipboardHistory>,
+    clipboard: &dyn ClipboardProvider,
+    selection: Selection,
+    last_hash: &mut Option<u64>,
+) {
+    // The offered types decide text vs image, so only one read is needed
+    let types = match selection {
+        Selection::Clipboard => clipboard.get_types(),
+        Selection::Primary => clipboard.get_primary_types(),
+    };
+
+    // Skip content marked as sensitive (e.g. copied from a password manager)
+    if config().has_sensitive_type(&types) {
+        return;
+    }
+
+    // Primary selections are only ever recorded as text
+    if selection == Selection::Primary {
+        if let Some(text) = clipboard.get_primary_text() {
+            let hash = hash_of(&text);
+            if Some(hash) != *last_hash {
+                if let Err(e) = history.add_text(text, Selection::Primary, None) {
+                    error!("Error adding text: {}", e);
+                }
+                *last_hash = Some(hash);
+            }
+        }
+        return;
+    }
+
+    // Some compositors offer image types for content that isn't an image
+    // (e.g. a file reference); that is captured as text below instead
+    let image_data = offered_image_type(&types)
+        .and_then(|_| clipboard.get_image(&types))
+        .filter(|data| is_image_data(data));
+    if let Some(image_data) = image_data {
+        let hash = hash_of(&image_data);
+        if Some(hash) != *last_hash {
+            let svg = clipboard.get_svg(&types);
+            if let Err(e) = history.add_image(image_data, types, svg) {
+                error!("Error adding image: {}", e);
+            }
+            // Remembered even on failure so the same image isn't retried
+            *last_hash = Some(hash);
+        }
+        return;
+    }
+
+    // Whitelisted types win over the plain-text fallback most apps also offer
+    if let Some(mime) = offered_binary_type(&types) {
+        if let Some(data) = clipboard.get_data(mime) {
+            let hash = hash_of(&data);
+            if Some(hash) != *last_hash {
+                if let Err(e) = history.add_binary(data, mime.to_string()) {
+                    error!("Error adding {} data: {}", mime, e);
+                }
+                *last_hash = Some(hash);
+            }
+        }
+        return;
     }
-    
-    // Check for text
-    if let Some(text) = get_clipboard_text(backend) {
-         use std::collections::hash_map::DefaultHasher;
-         use std::hash::{Hash, Hasher};
-
-         let mut hasher = DefaultHasher::new();
-         text.hash(&mut hasher);
-         let hash = hasher.finish();
-         
-         if Some(hash) != *last_hash {
-             history.add_text(text);
-             *last_hash = Some(hash);
-         }
+
+    if let Some(text) = clipboard.get_text() {
+        let hash = hash_of(&text);
+        if Some(hash) != *last_hash {
+            let html = clipboard.get_html(&types);
+            if let Err(e) = history.add_text(text, Selection::Clipboard, html) {
+                error!("Error adding text: {}", e);
+            }
+            *last_hash = Some(hash);
+        }
     }
 }
@@ -1,17 +1,157 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
 use ratatui::widgets::ListState;
 
+use crate::config::config;
+use crate::models::{ClipboardContentType, ClipboardEntry, DetectedKind, Selection};
+use crate::utils::{COPY_FLASH_MS, CURSOR_FILE, JUMP_TIMEOUT_MS};
+
 // ============================================================================
 // TERMINAL UI APP STATE
 // ============================================================================
 
+/// Which screen the TUI shows. Overlays (emoji picker, clear confirm) are
+/// tracked separately and draw on top of either.
+pub enum AppMode {
+    List,
+    /// Full content of one text entry with a line cursor
+    Detail(DetailView),
+}
+
+/// State of the detail view for a single text entry.
+pub struct DetailView {
+    pub entry_id: u64,
+    pub lines: Vec<String>,
+    pub cursor: usize,
+    /// Other end of a visual range started with `v`, if any
+    pub anchor: Option<usize>,
+    /// First visible line (updated each frame by the renderer)
+    pub scroll: usize,
+    /// Number of visible lines (updated each frame by the renderer)
+    pub page_size: usize,
+    /// For a blob that parses as JSON: the other layout (pretty-printed or
+    /// as copied), swapped with `lines` by `toggle_pretty`
+    pub alternate_lines: Option<Vec<String>>,
+    /// `lines` currently holds the pretty-printed JSON
+    pub pretty: bool,
+}
+
+impl DetailView {
+    pub fn new(entry: &ClipboardEntry) -> Self {
+        let mut lines: Vec<String> = entry.content.lines().map(String::from).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let alternate_lines = (entry.detected_kind == DetectedKind::Blob)
+            .then(|| serde_json::from_str::<serde_json::Value>(entry.content.trim()).ok())
+            .flatten()
+            .and_then(|json| serde_json::to_string_pretty(&json).ok())
+            .map(|pretty| pretty.lines().map(String::from).collect());
+        Self {
+            entry_id: entry.id,
+            lines,
+            cursor: 0,
+            anchor: None,
+            scroll: 0,
+            page_size: 1,
+            alternate_lines,
+            pretty: false,
+        }
+    }
+
+    /// Switch a JSON blob between pretty-printed and as copied.
+    pub fn toggle_pretty(&mut self) {
+        if let Some(alternate) = self.alternate_lines.as_mut() {
+            std::mem::swap(&mut self.lines, alternate);
+            self.pretty = !self.pretty;
+            self.cursor = 0;
+            self.anchor = None;
+            self.scroll = 0;
+        }
+    }
+
+    /// Move the cursor by `delta` lines, clamped to the content.
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.lines.len() - 1;
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn toggle_anchor(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+    }
+
+    /// Inclusive line range that Enter would copy.
+    pub fn selected_range(&self) -> (usize, usize) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        (anchor.min(self.cursor), anchor.max(self.cursor))
+    }
+
+    pub fn selected_text(&self) -> String {
+        let (start, end) = self.selected_range();
+        self.lines[start..=end].join("\n")
+    }
+}
+
 pub struct AppState {
+    pub mode: AppMode,
     pub list_state: ListState,
     pub should_quit: bool,
     pub selected_index: Option<usize>,
+    /// Set only by `select()`; the copy + paste on exit is gated on this
+    pub was_selected: bool,
+    /// Type the selected entry into the focused window instead of copying it
+    pub type_selected: bool,
+    /// Copy the selected image or binary entry as a file path / URI instead
+    /// of its data
+    pub path_selected: bool,
+    /// Put the selected entry in the PRIMARY selection instead of pasting it
+    pub primary_selected: bool,
     pub selected_entry: Option<crate::models::ClipboardEntry>,
     pub show_clear_confirm: bool,
+    /// While clearing pinned entries too: what has been typed to confirm it
+    pub clear_all_input: Option<String>,
+    /// Waiting for y/n before deleting (with `confirm_delete`)
+    pub show_delete_confirm: bool,
+    /// Ids of entries marked with Space for a bulk delete
+    pub marked: HashSet<u64>,
+    /// Moving past either end of the list wraps to the other end
+    pub wrap_navigation: bool,
+    /// Entry to select once the list is rebuilt (after it moved, e.g. with M)
+    pub follow_entry: Option<u64>,
     pub is_searching: bool,
     pub search_query: String,
+    /// Query of the last search, for jumping between its matches with n / N
+    pub last_search: String,
+    /// Treat `search_query` as a regular expression instead of a fuzzy pattern
+    pub search_is_regex: bool,
+    /// Match the search query's case exactly (fuzzy search ignores case otherwise)
+    pub search_case_sensitive: bool,
+    /// Only show entries from this selection (None shows both)
+    pub selection_filter: Option<Selection>,
+    /// Number of list items that fit on screen (updated each frame by the renderer)
+    pub list_page_size: usize,
+    /// Only show entries of this content type (None shows all)
+    pub type_filter: Option<ClipboardContentType>,
+    /// Only show entries copied from this application (None shows all)
+    pub source_filter: Option<String>,
+    /// Show "5m ago" style times instead of absolute timestamps
+    pub relative_time: bool,
+    /// One line per entry instead of the multi-line preview
+    pub compact_list: bool,
+    /// List the most-copied entries first instead of the newest
+    pub sort_by_copies: bool,
+    /// Entry being tagged with A and the tags typed so far
+    pub tag_input: Option<(u64, String)>,
+    /// Text typed since `'` and when the last key came (see `type_to_jump`)
+    pub jump: Option<(String, Instant)>,
+    /// Message shown in the footer instead of the key hints, and since when
+    pub flash: Option<(String, Instant)>,
     /// Tracks which entry index is currently being revealed (for secrets)
     pub reveal_index: Option<usize>,
     /// Whether the emoji picker overlay is open
@@ -28,18 +168,43 @@ pub struct AppState {
     pub emoji_search: String,
     /// The emoticon value selected by the user (to be copied to clipboard)
     pub emoji_selected: Option<String>,
+    /// Text picked in the detail view or trimmed with W (to be copied to clipboard)
+    pub detail_selected: Option<String>,
 }
 
 impl AppState {
     pub fn new() -> Self {
         let mut state = Self {
+            mode: AppMode::List,
             list_state: ListState::default(),
             should_quit: false,
             selected_index: None,
+            was_selected: false,
+            type_selected: false,
+            path_selected: false,
+            primary_selected: false,
             selected_entry: None,
             show_clear_confirm: false,
+            clear_all_input: None,
+            show_delete_confirm: false,
+            marked: HashSet::new(),
+            wrap_navigation: config().wrap_navigation,
+            follow_entry: None,
             is_searching: false,
             search_query: String::new(),
+            last_search: String::new(),
+            search_is_regex: false,
+            search_case_sensitive: false,
+            selection_filter: None,
+            list_page_size: 1,
+            type_filter: None,
+            source_filter: None,
+            relative_time: true,
+            compact_list: config().compact_list,
+            sort_by_copies: false,
+            tag_input: None,
+            jump: None,
+            flash: None,
             reveal_index: None,
             show_emoji_picker: false,
             emoji_category_index: 0,
@@ -48,6 +213,7 @@ impl AppState {
             emoji_grid_scroll: 0,
             emoji_search: String::new(),
             emoji_selected: None,
+            detail_selected: None,
         };
         state.list_state.select(Some(0));
         state
@@ -60,7 +226,15 @@ impl AppState {
         let i = self
             .list_state
             .selected()
-            .map(|i| if i >= max - 1 { 0 } else { i + 1 })
+            .map(|i| {
+                if i < max - 1 {
+                    i + 1
+                } else if self.wrap_navigation {
+                    0
+                } else {
+                    max - 1
+                }
+            })
             .unwrap_or(0);
         self.list_state.select(Some(i));
     }
@@ -72,20 +246,234 @@ impl AppState {
         let i = self
             .list_state
             .selected()
-            .map(|i| if i == 0 { max - 1 } else { i - 1 })
+            .map(|i| match i {
+                0 if self.wrap_navigation => max - 1,
+                0 => 0,
+                i => i - 1,
+            })
             .unwrap_or(0);
         self.list_state.select(Some(i));
     }
 
+    /// Move down a page; stops at the last entry, and wraps to the top when
+    /// already there (like `next`, unless `wrap_navigation` is off).
+    pub fn page_down(&mut self, max: usize, page: usize) {
+        if max == 0 {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(i) if i >= max - 1 && self.wrap_navigation => 0,
+            Some(i) => (i + page.max(1)).min(max - 1),
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    /// Move up a page; stops at the first entry, and wraps to the bottom when
+    /// already there (like `previous`, unless `wrap_navigation` is off).
+    pub fn page_up(&mut self, max: usize, page: usize) {
+        if max == 0 {
+            return;
+        }
+        let i = match self.list_state.selected() {
+            Some(0) if self.wrap_navigation => max - 1,
+            Some(i) => i.saturating_sub(page.max(1)).min(max - 1),
+            None => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn go_first(&mut self, max: usize) {
+        if max > 0 {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    pub fn go_last(&mut self, max: usize) {
+        if max > 0 {
+            self.list_state.select(Some(max - 1));
+        }
+    }
+
     pub fn select(&mut self) {
         self.selected_index = self.list_state.selected();
+        self.was_selected = true;
         self.should_quit = true;
     }
 
+    /// Like `select`, but the entry is typed out rather than pasted.
+    pub fn select_to_type(&mut self) {
+        self.type_selected = true;
+        self.select();
+    }
+
+    /// Like `select`, but an image or binary entry is copied as its file path.
+    pub fn select_as_path(&mut self) {
+        self.path_selected = true;
+        self.select();
+    }
+
+    /// Like `select`, but the entry goes to the PRIMARY selection for a
+    /// middle-click paste.
+    pub fn select_to_primary(&mut self) {
+        self.primary_selected = true;
+        self.select();
+    }
+
     pub fn quit(&mut self) {
+        self.selected_index = None;
+        self.was_selected = false;
         self.should_quit = true;
     }
 
+    /// Mark or unmark an entry for bulk delete.
+    pub fn toggle_mark(&mut self, id: u64) {
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+    }
+
+    /// Cycle the selection filter: all → clipboard → primary → all.
+    pub fn cycle_selection_filter(&mut self) {
+        self.selection_filter = match self.selection_filter {
+            None => Some(Selection::Clipboard),
+            Some(Selection::Clipboard) => Some(Selection::Primary),
+            Some(Selection::Primary) => None,
+        };
+    }
+
+    /// Cycle the content type filter: all → text → images → binary → all.
+    pub fn cycle_type_filter(&mut self) {
+        self.type_filter = match self.type_filter {
+            None => Some(ClipboardContentType::Text),
+            Some(ClipboardContentType::Text) => Some(ClipboardContentType::Image),
+            Some(ClipboardContentType::Image) => Some(ClipboardContentType::Binary),
+            Some(ClipboardContentType::Binary) => None,
+        };
+    }
+
+    /// Keep the current query for n / N once the search is left. An empty
+    /// query leaves the previous one in place.
+    pub fn remember_search(&mut self) {
+        if !self.search_query.is_empty() {
+            self.last_search = self.search_query.clone();
+        }
+    }
+
+    /// Cycle the source filter through `sources` (sorted app names): all →
+    /// each source in turn → all.
+    pub fn cycle_source_filter(&mut self, sources: &[String]) {
+        let next = match &self.source_filter {
+            None => 0,
+            Some(current) => match sources.iter().position(|s| s == current) {
+                Some(pos) => pos + 1,
+                None => sources.len(),
+            },
+        };
+        self.source_filter = sources.get(next).cloned();
+    }
+
+    /// Start typing tags for the entry `id`.
+    pub fn open_tag_input(&mut self, id: u64) {
+        self.tag_input = Some((id, String::new()));
+    }
+
+    // ========================================================================
+    // LIST NAVIGATION
+    // ========================================================================
+
+    /// `'` starts a jump: the next printable keys go to the jump buffer
+    /// instead of their usual actions
+    pub fn start_jump(&mut self) {
+        self.jump = Some((String::new(), Instant::now()));
+    }
+
+    /// Whether a jump is in progress (a key came within `JUMP_TIMEOUT_MS`)
+    pub fn jump_active(&self) -> bool {
+        self.jump
+            .as_ref()
+            .is_some_and(|(_, at)| at.elapsed() < Duration::from_millis(JUMP_TIMEOUT_MS))
+    }
+
+    /// Add `c` to the jump buffer and select the next entry whose text
+    /// contains it, ignoring case. The first key moves past the current
+    /// entry; later keys keep it if it still matches.
+    pub fn type_to_jump(&mut self, c: char, entries: &[&ClipboardEntry]) {
+        let active = self.jump_active();
+        let mut buffer = match self.jump.take() {
+            Some((buffer, _)) if active => buffer,
+            _ => String::new(),
+        };
+        buffer.push(c);
+        if !entries.is_empty() {
+            let needle = buffer.to_lowercase();
+            let current = self.list_state.selected().unwrap_or(0);
+            let skip = usize::from(buffer.chars().count() == 1);
+            let found = (skip..entries.len() + skip)
+                .map(|offset| (current + offset) % entries.len())
+                .find(|&i| {
+                    let entry = entries[i];
+                    entry.content_type == ClipboardContentType::Text
+                        && !entry.is_secret()
+                        && entry.content.to_lowercase().contains(&needle)
+                });
+            if let Some(index) = found {
+                self.list_state.select(Some(index));
+            }
+        }
+        self.jump = Some((buffer, Instant::now()));
+    }
+
+    /// Select the next entry of `content_type` below the current one, e.g.
+    /// to skip past a run of screenshots. Wraps to the top with
+    /// `wrap_navigation`; the selection stays put when there is none.
+    pub fn next_of_type(
+        &mut self,
+        entries: &[&ClipboardEntry],
+        content_type: ClipboardContentType,
+    ) {
+        let len = entries.len();
+        let current = self.list_state.selected().unwrap_or(0);
+        let found = (current + 1..=current + len)
+            .filter(|&i| self.wrap_navigation || i < len)
+            .map(|i| i % len)
+            .find(|&i| entries[i].content_type == content_type);
+        if let Some(index) = found {
+            self.list_state.select(Some(index));
+        }
+    }
+
+    // ========================================================================
+    // FOOTER MESSAGES
+    // ========================================================================
+
+    /// Show `message` in the footer for `COPY_FLASH_MS`.
+    pub fn flash(&mut self, message: String) {
+        self.flash = Some((message, Instant::now()));
+    }
+
+    /// The footer message, while it hasn't timed out
+    pub fn flash_message(&self) -> Option<&str> {
+        self.flash
+            .as_ref()
+            .filter(|(_, at)| at.elapsed() < Duration::from_millis(COPY_FLASH_MS))
+            .map(|(message, _)| message.as_str())
+    }
+
+    // ========================================================================
+    // DETAIL VIEW HELPERS
+    // ========================================================================
+
+    /// Open the detail view for a text entry.
+    pub fn open_detail(&mut self, entry: &ClipboardEntry) {
+        self.mode = AppMode::Detail(DetailView::new(entry));
+    }
+
+    /// Return to the list without copying anything.
+    pub fn close_detail(&mut self) {
+        self.mode = AppMode::List;
+    }
+
     // ========================================================================
     // EMOJI PICKER HELPERS
     // ========================================================================
@@ -206,3 +594,101 @@ impl AppState {
         self.emoji_grid_scroll = 0;
     }
 }
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// CURSOR PERSISTENCE (remember_cursor)
+// ============================================================================
+
+impl AppState {
+    /// Select the entry remembered by `save_cursor`, if it still exists.
+    /// Otherwise the selection stays at the top.
+    pub fn restore_cursor(&mut self, data_dir: &Path, entries: &[ClipboardEntry]) {
+        let remembered = fs::read_to_string(data_dir.join(CURSOR_FILE))
+            .ok()
+            .and_then(|content| content.trim().parse::<u64>().ok());
+        if let Some(index) = remembered.and_then(|id| entries.iter().position(|e| e.id == id)) {
+            self.list_state.select(Some(index));
+        }
+    }
+}
+
+/// Remember the selected entry for the next launch, or forget it with `None`.
+pub fn save_cursor(data_dir: &Path, entry_id: Option<u64>) {
+    let path = data_dir.join(CURSOR_FILE);
+    let _ = match entry_id {
+        Some(id) => fs::write(path, id.to_string()),
+        None => fs::remove_file(path),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Selected index after each key of `keys` ('j' next, 'k' previous) on a
+    /// list of `len` entries
+    fn walk(len: usize, wrap: bool, keys: &str) -> Vec<Option<usize>> {
+        let mut app = AppState::new();
+        app.wrap_navigation = wrap;
+        keys.chars()
+            .map(|key| {
+                match key {
+                    'j' => app.next(len),
+                    _ => app.previous(len),
+                }
+                app.list_state.selected()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn moving_in_an_empty_list_keeps_the_selection() {
+        for wrap in [true, false] {
+            assert_eq!(walk(0, wrap, "jk"), [Some(0), Some(0)]);
+        }
+    }
+
+    #[test]
+    fn moving_in_a_single_entry_list_stays_on_it() {
+        for wrap in [true, false] {
+            assert_eq!(walk(1, wrap, "jjkk"), [Some(0); 4]);
+        }
+    }
+
+    #[test]
+    fn moving_past_either_end_wraps() {
+        assert_eq!(walk(3, true, "jjj"), [Some(1), Some(2), Some(0)]);
+        assert_eq!(walk(3, true, "kkk"), [Some(2), Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn moving_past_either_end_stops_without_wrap() {
+        assert_eq!(walk(3, false, "jjj"), [Some(1), Some(2), Some(2)]);
+        assert_eq!(walk(3, false, "kjkk"), [Some(0), Some(1), Some(0), Some(0)]);
+    }
+
+    #[test]
+    fn quitting_selects_nothing() {
+        let mut app = AppState::new();
+        app.quit();
+        assert!(app.should_quit);
+        assert!(!app.was_selected);
+        assert_eq!(app.selected_index, None);
+    }
+
+    #[test]
+    fn selecting_quits_with_the_entry() {
+        let mut app = AppState::new();
+        app.next(3);
+        app.select();
+        assert!(app.should_quit);
+        assert!(app.was_selected);
+        assert_eq!(app.selected_index, Some(1));
+    }
+}
@@ -1,24 +1,46 @@
 use crossterm::{
-    event::{self, Event as CrosstermEvent, KeyCode, KeyModifiers},
+    cursor::MoveTo,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event as CrosstermEvent, KeyCode,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use log::{debug, warn};
 use ratatui::{
     Terminal,
     backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
+    widgets::{
+        Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState,
+    },
 };
+use regex::Regex;
+use unicode_width::UnicodeWidthStr;
 
-use crate::clipboard::{ClipboardBackend, set_clipboard_image, set_clipboard_text};
-use crate::history::ClipboardHistory;
-use crate::models::ClipboardContentType;
-use crate::ui::app::AppState;
-use crate::ui::emoji;
-
-use std::time::Duration;
+use crate::clipboard::{
+    ClipboardBackend, set_clipboard_binary, set_clipboard_file, set_clipboard_html,
+    set_clipboard_image_entry, set_clipboard_text, set_primary_binary, set_primary_image,
+    set_primary_text,
+};
+use crate::config::{Action, Theme, config};
+use crate::history::{ClipboardHistory, Stash};
+use crate::models::{ClipboardContentType, ClipboardEntry, Selection};
+use crate::monitor::read_backend_file;
+use crate::ui::app::{AppMode, AppState, DetailView, save_cursor};
+use crate::ui::emoji::{self, EmoticonCategory};
+use crate::ui::thumbnail::{self, KITTY_CLEAR};
+use crate::utils::{PAUSE_FILE, UI_RELOAD_MS, format_size};
+
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 // ============================================================================
 // EMOJI GRID RENDERER
@@ -48,6 +70,7 @@ fn render_emoji_grid(
     item_index: &mut usize,
     grid_cols: &mut usize,
     grid_scroll: &mut usize,
+    theme: &Theme,
 ) {
     // Inner area after border (1 cell each side)
     let inner_w = grid_area.width.saturating_sub(2) as usize;
@@ -56,13 +79,13 @@ fn render_emoji_grid(
     if cells.is_empty() || inner_w == 0 || inner_h == 0 {
         let empty = Paragraph::new(Span::styled(
             "  No matches found",
-            Style::default().fg(Color::Red),
+            Style::default().fg(theme.error),
         ))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(Color::Magenta)),
+                .border_style(Style::default().fg(theme.picker)),
         );
         f.render_widget(empty, grid_area);
         return;
@@ -119,10 +142,10 @@ fn render_emoji_grid(
 
             let style = if is_sel {
                 Style::default()
-                    .fg(Color::Magenta)
+                    .fg(theme.picker)
                     .add_modifier(Modifier::BOLD | Modifier::REVERSED)
             } else {
-                Style::default().fg(Color::Gray)
+                Style::default().fg(theme.text)
             };
             spans.push(Span::styled(display, style));
         }
@@ -140,692 +163,1838 @@ fn render_emoji_grid(
     let grid_widget = Paragraph::new(lines).block(
         Block::default()
             .title_bottom(
-                Line::from(Span::styled(
-                    title_suffix,
-                    Style::default().fg(Color::DarkGray),
-                ))
-                .alignment(Alignment::Right),
+                Line::from(Span::styled(title_suffix, Style::default().fg(theme.meta)))
+                    .alignment(Alignment::Right),
             )
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::Magenta)),
+            .border_style(Style::default().fg(theme.picker)),
     );
     f.render_widget(grid_widget, grid_area);
 }
 
+// ============================================================================
+// DETAIL VIEW RENDERER
+// ============================================================================
+
+/// Render the full content of a text entry with line numbers, the cursor
+/// line and any `v` range highlighted. Scrolls to keep the cursor visible.
+fn render_detail(f: &mut ratatui::Frame, view: &mut DetailView, area: Rect, theme: &Theme) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Header Text
+            Constraint::Min(0),    // Content (Boxed)
+            Constraint::Length(1), // Footer Text
+        ])
+        .split(area);
+
+    let (range_start, range_end) = view.selected_range();
+    let mut header = format!(" 📄 Line {}/{}", view.cursor + 1, view.lines.len());
+    if view.anchor.is_some() {
+        header.push_str(&format!(" · {} selected", range_end - range_start + 1));
+    }
+    if view.pretty {
+        header.push_str(" · pretty JSON");
+    }
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            header,
+            Style::default()
+                .fg(theme.title)
+                .add_modifier(Modifier::BOLD),
+        )),
+        chunks[0],
+    );
+
+    // Keep the cursor inside the viewport
+    let height = chunks[1].height.saturating_sub(2).max(1) as usize;
+    view.page_size = height;
+    if view.cursor < view.scroll {
+        view.scroll = view.cursor;
+    } else if view.cursor >= view.scroll + height {
+        view.scroll = view.cursor + 1 - height;
+    }
+
+    let gutter_width = view.lines.len().to_string().len();
+    let lines: Vec<Line> = view
+        .lines
+        .iter()
+        .enumerate()
+        .skip(view.scroll)
+        .take(height)
+        .map(|(idx, line)| {
+            let mut style = Style::default().fg(theme.text);
+            if (range_start..=range_end).contains(&idx) {
+                style = if theme.monochrome {
+                    style.add_modifier(Modifier::REVERSED)
+                } else {
+                    style.bg(theme.meta).fg(theme.emphasis)
+                };
+            }
+            if idx == view.cursor {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>width$} ", idx + 1, width = gutter_width),
+                    Style::default().fg(theme.meta),
+                ),
+                Span::styled(line.replace('\t', "    "), style),
+            ])
+        })
+        .collect();
+
+    let content = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme.border)),
+    );
+    f.render_widget(content, chunks[1]);
+
+    let key_style = Style::default()
+        .fg(theme.title)
+        .add_modifier(Modifier::BOLD);
+    let text_style = Style::default().fg(theme.emphasis);
+    let sep_style = Style::default().fg(theme.meta);
+    let mut footer_spans = vec![
+        Span::styled("↑↓", key_style),
+        Span::styled(" Move ", text_style),
+        Span::styled("|", sep_style),
+        Span::styled(" V", key_style),
+        Span::styled(" Range ", text_style),
+        Span::styled("|", sep_style),
+        Span::styled(" Enter", key_style),
+        Span::styled(" Copy ", text_style),
+        Span::styled("|", sep_style),
+    ];
+    if view.alternate_lines.is_some() {
+        let label = if view.pretty {
+            " As copied "
+        } else {
+            " Pretty JSON "
+        };
+        footer_spans.extend([
+            Span::styled(" J", key_style),
+            Span::styled(label, text_style),
+            Span::styled("|", sep_style),
+        ]);
+    }
+    footer_spans.extend([
+        Span::styled(" Esc", key_style),
+        Span::styled(" Back", text_style),
+    ]);
+    let footer = Paragraph::new(Line::from(footer_spans)).alignment(Alignment::Center);
+    f.render_widget(footer, chunks[2]);
+}
+
 // ============================================================================
 // TERMINAL UI DISPLAY
 // ============================================================================
 
-pub fn show_ui(backend: ClipboardBackend) -> Result<(), Box<dyn std::error::Error>> {
-    let history = ClipboardHistory::new();
+/// Split `text` into spans, styling the characters at `matched` (char indices).
+fn highlight_matches(text: &str, matched: &[usize], theme: &Theme) -> Vec<Span<'static>> {
+    let highlight = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut current_matched = false;
+
+    for (idx, ch) in text.chars().enumerate() {
+        let is_matched = matched.contains(&idx);
+        if is_matched != current_matched && !current.is_empty() {
+            let segment = std::mem::take(&mut current);
+            spans.push(if current_matched {
+                Span::styled(segment, highlight)
+            } else {
+                Span::raw(segment)
+            });
+        }
+        current_matched = is_matched;
+        current.push(ch);
+    }
 
-    enable_raw_mode()?;
-    let mut stdout = std::io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend_term = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend_term)?;
-    terminal.clear()?;
+    if !current.is_empty() {
+        spans.push(if current_matched {
+            Span::styled(current, highlight)
+        } else {
+            Span::raw(current)
+        });
+    }
 
-    let mut app_state = AppState::new();
+    spans
+}
 
-    // Build emoji categories once outside the loop
-    let emoji_cats = emoji::categories();
+/// Char indices of `text` covered by matches of `re`.
+fn regex_match_indices(re: &Regex, text: &str) -> Vec<usize> {
+    let ranges: Vec<_> = re.find_iter(text).map(|m| m.range()).collect();
+    text.char_indices()
+        .enumerate()
+        .filter(|(_, (byte, _))| ranges.iter().any(|r| r.contains(byte)))
+        .map(|(idx, _)| idx)
+        .collect()
+}
 
-    loop {
-        // Clean up any expired secrets each tick
-        history.cleanup_expired();
+/// Rank `entry` against a search: a fuzzy score over its text (or the text
+/// recognized in it), 0 for a regex, category label or "secret" match, and
+/// None when it doesn't match.
+fn search_score(
+    matcher: &SkimMatcherV2,
+    entry: &crate::models::ClipboardEntry,
+    query: &str,
+    regex: Option<&Result<Regex, regex::Error>>,
+) -> Option<i64> {
+    match regex {
+        Some(Ok(re)) => {
+            let ocr_match = entry.ocr_text.as_deref().is_some_and(|t| re.is_match(t));
+            return (re.is_match(&entry.content) || ocr_match).then_some(0);
+        }
+        Some(Err(_)) => return None,
+        None => {}
+    }
+    if let Some(score) = matcher.fuzzy_match(&entry.content, query) {
+        return Some(score);
+    }
+    // Text recognized in images (ocr feature)
+    if let Some(score) = entry
+        .ocr_text
+        .as_deref()
+        .and_then(|text| matcher.fuzzy_match(text, query))
+    {
+        return Some(score);
+    }
+    // Also match the category label OR "secret" keyword
+    let query = query.to_lowercase();
+    let (_icon, category_label) = entry.detect_category();
+    (category_label.to_lowercase() == query || (query == "secret" && entry.is_secret()))
+        .then_some(0)
+}
 
-        // Filter entries based on search query
-        let all_entries = history.get_all();
-        let filtered_entries: Vec<&crate::models::ClipboardEntry> =
-            if app_state.is_searching && !app_state.search_query.is_empty() {
-                all_entries
-                    .iter()
-                    .filter(|e| {
-                        let query = app_state.search_query.to_lowercase();
-                        let (_icon, category_label) = e.detect_category();
-                        // Match against content OR category label OR "secret" keyword
-                        e.content.to_lowercase().contains(&query)
-                            || category_label.to_lowercase() == query
-                            || (query == "secret" && e.is_secret())
-                    })
-                    .collect()
-            } else {
-                all_entries.iter().collect()
-            };
+/// Split a search query into its `tag:<name>` filters and the remaining text.
+fn split_tag_filters(query: &str) -> (Vec<&str>, String) {
+    let mut tags = Vec::new();
+    let mut text = Vec::new();
+    for word in query.split(' ') {
+        match word.strip_prefix("tag:") {
+            Some(tag) if !tag.is_empty() => tags.push(tag),
+            _ => text.push(word),
+        }
+    }
+    (tags, text.join(" ").trim().to_string())
+}
 
-        // Clear reveal if the selected index changed away from the revealed entry
-        if let Some(reveal_idx) = app_state.reveal_index {
-            let current_sel = app_state.list_state.selected().unwrap_or(usize::MAX);
-            if current_sel != reveal_idx {
-                app_state.reveal_index = None;
+/// Apply the tags typed after A: each word is added, or removed if the entry
+/// already has it.
+fn toggle_tags(tags: &[String], input: &str) -> Vec<String> {
+    let mut tags = tags.to_vec();
+    for word in input.split_whitespace() {
+        match tags.iter().position(|t| t.eq_ignore_ascii_case(word)) {
+            Some(pos) => {
+                tags.remove(pos);
             }
+            None => tags.push(word.to_string()),
         }
+    }
+    tags
+}
 
-        terminal.draw(|f| {
-            // Background UI
-            if all_entries.is_empty() {
-                // Check ORIGINAL list for empty
-                let area = f.area();
-                let text = Paragraph::new(vec![
-                    Line::from(""),
-                    Line::from(Span::styled(
-                        "Clipboard History Empty",
-                        Style::default()
-                            .fg(Color::Cyan)
-                            .add_modifier(Modifier::BOLD),
-                    )),
-                    Line::from(""),
-                    Line::from(Span::styled(
-                        "Copy text or images to start",
-                        Style::default().fg(Color::Gray),
-                    )),
-                    Line::from(""),
-                    Line::from(Span::styled(
-                        "Press E for Emoji Picker • Esc to close",
-                        Style::default().fg(Color::Gray),
-                    )),
-                ])
-                .alignment(Alignment::Center)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::Cyan)),
-                );
+/// Footer label for the active selection filter.
+fn selection_filter_label(filter: Option<Selection>) -> &'static str {
+    match filter {
+        None => "All",
+        Some(Selection::Clipboard) => "Clipboard",
+        Some(Selection::Primary) => "Primary",
+    }
+}
 
-                let centered = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Percentage(40),
-                        Constraint::Length(9),
-                        Constraint::Percentage(40),
-                    ])
-                    .split(area);
+/// Centered y/n confirmation dialog drawn over the current screen.
+fn render_confirm(f: &mut ratatui::Frame, title: &str, message: &str, hint: &str, theme: &Theme) {
+    let area = f.area();
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    // One line per line of `message`
+    lines.extend(
+        message
+            .lines()
+            .map(|line| Line::from(Span::styled(line, Style::default().fg(theme.emphasis)))),
+    );
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        hint,
+        Style::default().fg(theme.text),
+    )));
+    let height = lines.len() as u16 + 2;
+    let text = Paragraph::new(lines).alignment(Alignment::Center).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.error)),
+    );
 
-                f.render_widget(text, centered[1]);
-            } else {
-                // Main UI
-                let chunks = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Length(1), // Header Text
-                        Constraint::Min(0),    // List (Boxed)
-                        Constraint::Length(1), // Footer Text
-                    ])
-                    .split(f.area());
-
-                // ========================
-                // 1. HEADER (Styled)
-                // ========================
-                let header_chunks = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Percentage(50), // Title
-                        Constraint::Percentage(50), // Stats
-                    ])
-                    .split(chunks[0]);
-
-                let header_title = if app_state.is_searching {
-                    Paragraph::new(Span::styled(
-                        format!(" 🔍 Search: {}_", app_state.search_query),
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
-                    ))
-                } else {
-                    Paragraph::new(Span::styled(
-                        " 📋 Clipboard",
-                        Style::default()
-                            .fg(Color::Cyan)
-                            .add_modifier(Modifier::BOLD),
-                    ))
-                };
-                f.render_widget(header_title, header_chunks[0]);
+    let centered = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(35),
+            Constraint::Length(height),
+            Constraint::Percentage(35),
+        ])
+        .split(area);
+
+    let h_centered = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(20),
+            Constraint::Percentage(60),
+            Constraint::Percentage(20),
+        ])
+        .split(centered[1]);
+
+    f.render_widget(Clear, h_centered[1]);
+    f.render_widget(text, h_centered[1]);
+}
 
-                let current_idx = if filtered_entries.is_empty() {
-                    0
-                } else {
-                    app_state.list_state.selected().unwrap_or(0) + 1
-                };
-                let total_count = filtered_entries.len();
-                let max_history = crate::utils::MAX_HISTORY;
-
-                let stats_spans = vec![
-                    Span::styled(
-                        format!("{}/{}", current_idx, total_count),
-                        Style::default()
-                            .fg(Color::White)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::styled(
-                        format!(" | max {}", max_history),
-                        Style::default().fg(Color::DarkGray),
-                    ),
-                ];
-
-                let header_stats =
-                    Paragraph::new(Line::from(stats_spans)).alignment(Alignment::Right);
-                f.render_widget(header_stats, header_chunks[1]);
-
-                // ========================
-                // 2. LIST (Themed)
-                // ========================
-                let list_inner_width = chunks[1].width.saturating_sub(4) as usize;
-
-                let items: Vec<ListItem> = filtered_entries
-                    .iter()
-                    .enumerate()
-                    .map(|(idx, entry)| {
-                        let mut lines = vec![];
-
-                        // Determine if this entry should be revealed
-                        let is_revealed = app_state.reveal_index == Some(idx);
-                        let preview = entry.preview_lines_with_reveal(is_revealed);
-                        for line in preview {
-                            lines.push(Line::from(format!(" {}", line)));
-                        }
+/// Log a history edit that failed; the picker has nowhere else to show it.
+fn log_failure<T>(result: Result<T, String>) {
+    if let Err(e) = result {
+        warn!("{}", e);
+    }
+}
 
-                        let meta = entry.metadata_label();
-                        let paddable_width = list_inner_width.saturating_sub(1);
-                        let aligned_meta = format!("{:>width$}", meta, width = paddable_width);
+/// Put the entry on the clipboard the way Enter does: text with its markup
+/// (with `restore_rich_text`), images and binary data under their own type.
+fn copy_entry(
+    history: &ClipboardHistory,
+    entry: &crate::models::ClipboardEntry,
+    backend: ClipboardBackend,
+) -> Result<(), String> {
+    match entry.content_type {
+        ClipboardContentType::Text => match &entry.rich_content {
+            Some(html) if config().restore_rich_text => {
+                set_clipboard_html(html, &entry.content, backend)
+            }
+            _ => set_clipboard_text(&entry.content, backend),
+        },
+        ClipboardContentType::Image => {
+            set_clipboard_image_entry(entry, history.images_dir(), backend)
+        }
+        ClipboardContentType::Binary => {
+            let path = history.blobs_dir().join(&entry.content);
+            let mime = entry.binary_info.as_ref().map_or("", |i| i.mime.as_str());
+            set_clipboard_binary(&path, mime, backend)
+        }
+    }
+}
 
-                        // Use a different color for secret metadata
-                        let meta_color = if entry.is_secret() {
-                            Color::Yellow
-                        } else {
-                            Color::DarkGray
-                        };
+/// Delete the marked entries if any are marked, otherwise the highlighted one.
+fn delete_selected_or_marked(
+    history: &ClipboardHistory,
+    app_state: &mut AppState,
+    entries: &[&crate::models::ClipboardEntry],
+) {
+    if app_state.marked.is_empty() {
+        delete_selected(history, app_state, entries);
+        return;
+    }
 
-                        lines.push(Line::from(Span::styled(
-                            aligned_meta,
-                            Style::default().fg(meta_color),
-                        )));
+    let marked: Vec<u64> = app_state.marked.drain().collect();
+    for id in &marked {
+        log_failure(history.delete_by_id(*id));
+    }
 
-                        lines.push(Line::from(""));
+    let remaining = entries.iter().filter(|e| !marked.contains(&e.id)).count();
+    match app_state.list_state.selected() {
+        _ if remaining == 0 => app_state.list_state.select(None),
+        Some(index) if index >= remaining => app_state.list_state.select(Some(remaining - 1)),
+        _ => {}
+    }
+}
 
-                        ListItem::new(lines)
-                    })
-                    .collect();
+/// Delete right away, or ask first when `confirm_delete` is set.
+fn request_delete(
+    history: &ClipboardHistory,
+    app_state: &mut AppState,
+    entries: &[&crate::models::ClipboardEntry],
+) {
+    if config().confirm_delete {
+        app_state.show_delete_confirm = true;
+    } else {
+        delete_selected_or_marked(history, app_state, entries);
+    }
+}
 
-                // Show "No Results" if searching and empty
-                let list = if items.is_empty() && app_state.is_searching {
-                    List::new(vec![ListItem::new("No matches found")])
-                        .block(
-                            Block::default()
-                                .borders(Borders::ALL)
-                                .border_type(BorderType::Rounded)
-                                .border_style(Style::default().fg(Color::Cyan)),
-                        )
-                        .style(Style::default().fg(Color::Red))
-                } else {
-                    List::new(items)
-                        .block(
-                            Block::default()
-                                .borders(Borders::ALL)
-                                .border_type(BorderType::Rounded)
-                                .border_style(Style::default().fg(Color::Cyan)),
-                        )
-                        .style(Style::default().fg(Color::Gray))
-                        .highlight_style(
-                            Style::default()
-                                .fg(Color::White)
-                                .add_modifier(Modifier::BOLD),
-                        )
-                        .highlight_symbol("▍ ")
-                };
+/// Delete the highlighted entry of the (possibly filtered) view by its id,
+/// then keep the selection within the shortened list.
+fn delete_selected(
+    history: &ClipboardHistory,
+    app_state: &mut AppState,
+    entries: &[&crate::models::ClipboardEntry],
+) {
+    let Some(index) = app_state.list_state.selected() else {
+        return;
+    };
+    let Some(entry) = entries.get(index) else {
+        return;
+    };
 
-                f.render_stateful_widget(list, chunks[1], &mut app_state.list_state);
+    log_failure(history.delete_by_id(entry.id));
 
-                // ========================
-                // 3. FOOTER (Styled Keys)
-                // ========================
-                let key_style = Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD);
-                let text_style = Style::default().fg(Color::White);
-                let sep_style = Style::default().fg(Color::DarkGray);
+    let new_len = entries.len() - 1;
+    if new_len == 0 {
+        app_state.list_state.select(None);
+    } else if index >= new_len {
+        app_state.list_state.select(Some(new_len - 1));
+    }
+}
 
-                // Check if the currently selected entry is a secret to show contextual hints
-                let selected_is_secret = app_state
-                    .list_state
-                    .selected()
-                    .and_then(|idx| filtered_entries.get(idx))
-                    .map(|e| e.is_secret())
-                    .unwrap_or(false);
-
-                let mut footer_spans = vec![
-                    Span::styled("↑↓", key_style),
-                    Span::styled(" Nav ", text_style),
-                    Span::styled("|", sep_style),
-                    Span::styled(" Enter", key_style),
-                    Span::styled(" Copy ", text_style),
-                    Span::styled("|", sep_style),
-                    Span::styled(" P", key_style),
-                    Span::styled(" Pin ", text_style),
-                    Span::styled("|", sep_style),
-                    Span::styled(" D", key_style),
-                    Span::styled(" Del ", text_style),
-                    Span::styled("|", sep_style),
-                    Span::styled(" S", key_style),
-                    Span::styled(" Search ", text_style),
-                    Span::styled("|", sep_style),
-                    Span::styled(" E", key_style),
-                    Span::styled(" Emoji ", text_style),
-                ];
-
-                if selected_is_secret {
-                    footer_spans.push(Span::styled("|", sep_style));
-                    footer_spans.push(Span::styled(" R", key_style));
-                    footer_spans.push(Span::styled(" Reveal ", text_style));
-                    footer_spans.push(Span::styled("|", sep_style));
-                    footer_spans.push(Span::styled(" ⇧S", key_style));
-                    footer_spans.push(Span::styled(" Keep ", text_style));
+/// Pick the range of list items to render: starting from the previous
+/// `offset`, scrolled just enough to keep `selected` visible, and filling
+/// `height` rows. Only the items near the window are measured.
+fn visible_window(
+    offset: usize,
+    selected: Option<usize>,
+    len: usize,
+    height: usize,
+    item_height: impl Fn(usize) -> usize,
+) -> (usize, usize) {
+    if len == 0 {
+        return (0, 0);
+    }
+
+    let mut start = offset.min(len - 1);
+    if let Some(selected) = selected.filter(|&s| s < len) {
+        if selected < start {
+            start = selected;
+        } else {
+            // Walk back from the selection to find how far up the window can reach
+            let mut first = selected;
+            let mut used = item_height(selected);
+            while first > start && used + item_height(first - 1) <= height {
+                first -= 1;
+                used += item_height(first);
+            }
+            start = first;
+        }
+    }
+
+    let mut end = start;
+    let mut used = 0;
+    while end < len && used < height {
+        used += item_height(end);
+        end += 1;
+    }
+    (start, end)
+}
+
+/// Map a row inside the list's inner area to an index into the rendered
+/// window, given the height of every rendered item.
+fn list_index_at(row: usize, item_heights: &[usize]) -> Option<usize> {
+    let mut top = 0;
+    for (idx, height) in item_heights.iter().enumerate() {
+        if row < top + height {
+            return Some(idx);
+        }
+        top += height;
+    }
+    None
+}
+
+/// Scroll wheel moves the selection; a left click selects a row, and clicking
+/// the already-selected row (i.e. a double-click) copies it like Enter.
+fn handle_list_mouse(
+    mouse: MouseEvent,
+    app_state: &mut AppState,
+    list_area: Rect,
+    item_heights: &[usize],
+    total: usize,
+) {
+    match mouse.kind {
+        MouseEventKind::ScrollDown => app_state.next(total),
+        MouseEventKind::ScrollUp => app_state.previous(total),
+        MouseEventKind::Down(MouseButton::Left) => {
+            // Skip the list border
+            let inner_top = list_area.y + 1;
+            let inner_bottom = list_area.y + list_area.height.saturating_sub(1);
+            if mouse.row < inner_top
+                || mouse.row >= inner_bottom
+                || mouse.column <= list_area.x
+                || mouse.column >= list_area.x + list_area.width.saturating_sub(1)
+            {
+                return;
+            }
+            let row = (mouse.row - inner_top) as usize;
+            let offset = app_state.list_state.offset();
+            if let Some(idx) = list_index_at(row, item_heights).map(|idx| offset + idx) {
+                if app_state.list_state.selected() == Some(idx) {
+                    app_state.select();
+                } else {
+                    app_state.list_state.select(Some(idx));
                 }
+            }
+        }
+        _ => {}
+    }
+}
+
+// ============================================================================
+// FRAME RENDERER
+// ============================================================================
 
-                footer_spans.push(Span::styled("|", sep_style));
-                footer_spans.push(Span::styled(" C", key_style));
-                footer_spans.push(Span::styled(" Clear ", text_style));
-                footer_spans.push(Span::styled("|", sep_style));
-                footer_spans.push(Span::styled(" Esc", key_style));
-                footer_spans.push(Span::styled(" Close", text_style));
+/// What one frame shows, gathered by `show_ui` before it draws.
+struct FrameData<'a> {
+    backend: ClipboardBackend,
+    images_dir: &'a Path,
+    theme: &'a Theme,
+    recent: Option<usize>,
+    all_entries: &'a [ClipboardEntry],
+    filtered_entries: &'a [&'a ClipboardEntry],
+    shown_scores: Option<&'a Vec<i64>>,
+    matcher: &'a SkimMatcherV2,
+    matching_text: bool,
+    search_text: &'a str,
+    search_regex: &'a Option<Result<Regex, regex::Error>>,
+    regex_invalid: bool,
+    stash: &'a Stash,
+    emoji_cats: &'a [EmoticonCategory],
+    capture_paused: bool,
+    daemon_backend: &'a Option<(String, bool)>,
+    disk_usage: u64,
+    over_disk_cap: bool,
+    kitty_graphics: bool,
+    show_preview: bool,
+}
 
-                let footer = Paragraph::new(Line::from(footer_spans)).alignment(Alignment::Center);
+/// Where a frame put the list and preview pane, for mouse hit-testing and
+/// thumbnails, and the braille thumbnail kept between frames.
+struct FrameLayout<'a> {
+    list_area: &'a mut Rect,
+    item_heights: &'a mut Vec<usize>,
+    preview_area: &'a mut Option<Rect>,
+    braille_thumbnail: &'a mut Option<(u64, Rect, Vec<String>)>,
+}
 
-                f.render_widget(footer, chunks[2]);
+/// Draw the list (or detail view) with whatever modal is open on top.
+fn draw_frame(
+    f: &mut ratatui::Frame,
+    app_state: &mut AppState,
+    data: &FrameData,
+    layout: FrameLayout,
+) {
+    let FrameData {
+        backend,
+        images_dir,
+        theme,
+        recent,
+        all_entries,
+        filtered_entries,
+        shown_scores,
+        matcher,
+        matching_text,
+        search_text,
+        search_regex,
+        regex_invalid,
+        stash,
+        emoji_cats,
+        capture_paused,
+        daemon_backend,
+        disk_usage,
+        over_disk_cap,
+        kitty_graphics,
+        show_preview,
+    } = *data;
+    let FrameLayout {
+        list_area,
+        item_heights,
+        preview_area,
+        braille_thumbnail,
+    } = layout;
+
+    // Background UI
+    if let AppMode::Detail(view) = &mut app_state.mode {
+        render_detail(f, view, f.area(), theme);
+    } else if all_entries.is_empty() {
+        // Check ORIGINAL list for empty
+        let area = f.area();
+        let text = Paragraph::new(vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                "Clipboard History Empty",
+                Style::default()
+                    .fg(theme.title)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Copy text or images to start",
+                Style::default().fg(theme.text),
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Press E for Emoji Picker • Esc to close",
+                Style::default().fg(theme.text),
+            )),
+        ])
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border)),
+        );
+
+        let centered = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(40),
+                Constraint::Length(9),
+                Constraint::Percentage(40),
+            ])
+            .split(area);
+
+        f.render_widget(text, centered[1]);
+    } else {
+        // Main UI
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1), // Header Text
+                Constraint::Min(0),    // List (Boxed)
+                Constraint::Length(1), // Footer Text
+            ])
+            .split(f.area());
+
+        // ========================
+        // 1. HEADER (Styled)
+        // ========================
+        let header_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(50), // Title
+                Constraint::Percentage(50), // Stats
+            ])
+            .split(chunks[0]);
+
+        let header_title = if let Some((_, input)) = &app_state.tag_input {
+            Paragraph::new(Line::from(vec![
+                Span::styled(
+                    " 🏷 Tag (adds, or removes if present): ",
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{}_", input),
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]))
+        } else if app_state.is_searching {
+            let mode = match (app_state.search_is_regex, app_state.search_case_sensitive) {
+                (true, true) => " (re, Aa)",
+                (true, false) => " (re)",
+                (false, true) => " (Aa)",
+                (false, false) => "",
+            };
+            let query_color = if regex_invalid {
+                theme.error
+            } else {
+                theme.accent
+            };
+            Paragraph::new(Line::from(vec![
+                Span::styled(
+                    format!(" 🔍 Search{}: ", mode),
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{}_", app_state.search_query),
+                    Style::default()
+                        .fg(query_color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]))
+        } else {
+            let title = match app_state.type_filter {
+                None => " 📋 Clipboard",
+                Some(ClipboardContentType::Text) => " 📝 Text only",
+                Some(ClipboardContentType::Image) => " 🖼 Images only",
+                Some(ClipboardContentType::Binary) => " 📄 Binary only",
+            };
+            let title = match &app_state.source_filter {
+                Some(source) => format!("{} · from {}", title, source),
+                None => title.to_string(),
+            };
+            let title = if app_state.sort_by_copies {
+                format!("{} · most copied", title)
+            } else {
+                title
+            };
+            let title = match recent {
+                Some(count) => format!("{} · last {}", title, count),
+                None => title,
+            };
+            let title = match &app_state.jump {
+                Some((buffer, _)) if app_state.jump_active() => {
+                    format!("{} · jump '{}", title, buffer)
+                }
+                _ => title,
+            };
+            Paragraph::new(Span::styled(
+                title,
+                Style::default()
+                    .fg(theme.title)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        };
+        f.render_widget(header_title, header_chunks[0]);
+
+        let current_idx = if filtered_entries.is_empty() {
+            0
+        } else {
+            app_state.list_state.selected().unwrap_or(0) + 1
+        };
+        let total_count = filtered_entries.len();
+        let max_history = crate::utils::MAX_HISTORY;
+
+        let mut stats_spans = Vec::new();
+        if capture_paused {
+            stats_spans.push(Span::styled(
+                "⏸ PAUSED  ",
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        if !stash.is_empty() {
+            stats_spans.push(Span::styled(
+                format!("📎 {} stashed  ", stash.len()),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        // Flagged when the daemon lost its session or isn't using the
+        // backend this session would (e.g. started under X11, now in Wayland)
+        let backend_warning = match &daemon_backend {
+            Some((name, false)) => {
+                Some(format!("⚠ daemon's {} session is gone, restart it  ", name))
             }
+            Some((name, true)) if name != backend.name() => {
+                Some(format!("⚠ daemon uses {}, restart it  ", name))
+            }
+            _ => None,
+        };
+        match (backend_warning, &daemon_backend) {
+            (Some(warning), _) => stats_spans.push(Span::styled(
+                warning,
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            (None, Some((name, _))) => stats_spans.push(Span::styled(
+                format!("{} | ", name),
+                Style::default().fg(theme.meta),
+            )),
+            (None, None) => {}
+        }
+        // Over `disk_warn_bytes` the size turns into a warning
+        let (disk_label, disk_style) = if over_disk_cap {
+            (
+                format!("⚠ {} on disk  ", format_size(disk_usage)),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (
+                format!("{} on disk | ", format_size(disk_usage)),
+                Style::default().fg(theme.meta),
+            )
+        };
+        stats_spans.push(Span::styled(disk_label, disk_style));
+        stats_spans.extend([
+            Span::styled(
+                format!("{}/{}", current_idx, total_count),
+                Style::default()
+                    .fg(theme.emphasis)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" | max {}", max_history),
+                Style::default().fg(theme.meta),
+            ),
+        ]);
+
+        let header_stats = Paragraph::new(Line::from(stats_spans)).alignment(Alignment::Right);
+        f.render_widget(header_stats, header_chunks[1]);
+
+        // ========================
+        // 2. LIST (Themed)
+        // ========================
+        // Reserve the right third for the preview pane when enabled
+        let (list_chunk, preview_chunk) = if show_preview {
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(67), Constraint::Percentage(33)])
+                .split(chunks[1]);
+            (panes[0], Some(panes[1]))
+        } else {
+            (chunks[1], None)
+        };
+        // One column on the right of the list for the scrollbar
+        let list_columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(list_chunk);
+        let (list_chunk, scrollbar_chunk) = (list_columns[0], list_columns[1]);
+
+        let list_inner_width = list_chunk.width.saturating_sub(4) as usize;
+        // Preview text starts after a space (and the checkbox, once
+        // anything is marked)
+        let checkbox_width = if app_state.marked.is_empty() { 0 } else { 2 };
+        let wrap_width = list_inner_width.saturating_sub(1 + checkbox_width);
+        let compact = app_state.compact_list;
+
+        // Only build items for the visible window, so large histories
+        // don't format every preview on each frame
+        let reveal_index = app_state.reveal_index;
+        let selected = app_state.list_state.selected();
+        let (window_start, window_end) = visible_window(
+            app_state.list_state.offset(),
+            selected,
+            filtered_entries.len(),
+            list_chunk.height.saturating_sub(2) as usize,
+            |idx| {
+                if compact {
+                    return 1;
+                }
+                filtered_entries[idx]
+                    .preview_lines_with_reveal(reveal_index == Some(idx), wrap_width)
+                    .len()
+                    + 2
+            },
+        );
+        app_state.list_page_size = (window_end - window_start).max(1);
+
+        let items: Vec<ListItem> = filtered_entries
+            .iter()
+            .enumerate()
+            .skip(window_start)
+            .take(window_end - window_start)
+            .map(|(idx, entry)| {
+                let mut lines = vec![];
+
+                // Determine if this entry should be revealed
+                let is_revealed = app_state.reveal_index == Some(idx);
+                // The compact layout keeps the first line behind the
+                // entry's icon and puts the time at its right end
+                let compact_time = compact.then(|| {
+                    if app_state.relative_time {
+                        entry.relative_time()
+                    } else {
+                        entry.absolute_time()
+                    }
+                });
+                let compact_icon = compact.then(|| {
+                    let icon = if entry.is_secret() {
+                        "🔒"
+                    } else {
+                        entry.detect_category().0
+                    };
+                    let pin = if entry.pinned { "📌" } else { "" };
+                    let clip = if stash.contains(entry.id) { "📎" } else { "" };
+                    format!("{}{}{} ", pin, clip, icon)
+                });
+                let preview_width = match (&compact_icon, &compact_time) {
+                    (Some(icon), Some(time)) => {
+                        wrap_width.saturating_sub(icon.width() + time.width() + 1)
+                    }
+                    _ => wrap_width,
+                };
+                let preview = entry.preview_lines_with_reveal(is_revealed, preview_width);
+                let line_count = if compact { 1 } else { preview.len() };
+                for (line_idx, line) in preview.into_iter().take(line_count).enumerate() {
+                    // Highlight the characters matched by the search query
+                    let matched = if let Some(Ok(re)) = &search_regex {
+                        regex_match_indices(re, &line)
+                    } else if matching_text {
+                        matcher
+                            .fuzzy_indices(&line, search_text)
+                            .map(|(_, indices)| indices)
+                            .unwrap_or_default()
+                    } else {
+                        Vec::new()
+                    };
+                    let mut spans = vec![Span::raw(" ")];
+                    // Checkboxes appear once anything is marked
+                    if line_idx == 0 && !app_state.marked.is_empty() {
+                        let checkbox = if app_state.marked.contains(&entry.id) {
+                            "☑ "
+                        } else {
+                            "☐ "
+                        };
+                        spans.push(Span::styled(checkbox, Style::default().fg(theme.accent)));
+                    }
+                    // Hex colors get a swatch of the actual color
+                    if let Some((r, g, b)) = entry
+                        .hex_color_rgb()
+                        .filter(|_| line_idx == 0 && !theme.monochrome)
+                    {
+                        spans.push(Span::styled("  ", Style::default().bg(Color::Rgb(r, g, b))));
+                        spans.push(Span::raw(" "));
+                    }
+                    if let Some(icon) = &compact_icon {
+                        spans.push(Span::raw(icon.clone()));
+                    }
+                    spans.extend(highlight_matches(&line, &matched, theme));
+                    if let Some(time) = &compact_time {
+                        let used: usize = spans.iter().map(Span::width).sum();
+                        let padding = list_inner_width.saturating_sub(used + time.width() + 1);
+                        spans.push(Span::raw(" ".repeat(padding.max(1))));
+                        spans.push(Span::styled(time.clone(), Style::default().fg(theme.meta)));
+                    }
+                    lines.push(Line::from(spans));
+                }
+                if compact {
+                    item_heights.push(lines.len());
+                    return ListItem::new(lines);
+                }
 
-            // ========================================
-            // MODAL: Clear Confirm
-            // ========================================
-            if app_state.show_clear_confirm {
-                let area = f.area();
-                let text = Paragraph::new(vec![
-                    Line::from(""),
-                    Line::from(Span::styled(
-                        "⚠  Clear All History?",
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
-                    )),
-                    Line::from(""),
-                    Line::from(Span::styled(
-                        "This will permanently delete all clipboard entries and images.",
-                        Style::default().fg(Color::White),
-                    )),
-                    Line::from(""),
-                    Line::from(Span::styled(
-                        "Press Y to confirm • N or Esc to cancel",
-                        Style::default().fg(Color::Gray),
-                    )),
-                ])
-                .alignment(Alignment::Center)
+                let meta = entry.metadata_label(app_state.relative_time);
+                let meta = if stash.contains(entry.id) {
+                    format!("📎 {}", meta)
+                } else {
+                    meta
+                };
+                let meta = match shown_scores {
+                    Some(scores) => format!("score {} · {}", scores[idx], meta),
+                    None => meta,
+                };
+                // Tags go in front of the metadata as chips
+                let chip_style = if theme.monochrome {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default().fg(theme.highlight_fg).bg(theme.accent)
+                };
+                let chips: Vec<String> =
+                    entry.tags.iter().map(|tag| format!(" {} ", tag)).collect();
+                let chips_width: usize = chips.iter().map(|chip| chip.width() + 1).sum();
+                let paddable_width = list_inner_width
+                    .saturating_sub(1)
+                    .saturating_sub(chips_width);
+
+                // Use a different color for secret metadata
+                let meta_color = if entry.is_secret() {
+                    theme.accent
+                } else {
+                    theme.meta
+                };
+
+                // Display width, since icons and CJK take two columns
+                let meta_width = meta.width();
+                let mut meta_spans = vec![Span::raw(
+                    " ".repeat(paddable_width.saturating_sub(meta_width)),
+                )];
+                for chip in chips {
+                    meta_spans.push(Span::styled(chip, chip_style));
+                    meta_spans.push(Span::raw(" "));
+                }
+                meta_spans.push(Span::styled(meta, Style::default().fg(meta_color)));
+                lines.push(Line::from(meta_spans));
+
+                lines.push(Line::from(""));
+
+                item_heights.push(lines.len());
+                ListItem::new(lines)
+            })
+            .collect();
+
+        // Show "No Results" if searching/filtering leaves nothing
+        let list = if items.is_empty() {
+            List::new(vec![ListItem::new("No matches found")])
                 .block(
                     Block::default()
                         .borders(Borders::ALL)
-                        .border_style(Style::default().fg(Color::Red)),
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(theme.border)),
+                )
+                .style(Style::default().fg(theme.error))
+        } else {
+            List::new(items)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(theme.border)),
+                )
+                .style(Style::default().fg(theme.text))
+                .highlight_style(
+                    Style::default()
+                        .fg(theme.highlight_fg)
+                        .bg(theme.highlight_bg)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .highlight_symbol("▍ ")
+        };
+
+        *list_area = list_chunk;
+        let mut window_state = ListState::default()
+            .with_selected(selected.map(|idx| idx.saturating_sub(window_start)));
+        f.render_stateful_widget(list, list_chunk, &mut window_state);
+        *app_state.list_state.offset_mut() = window_start;
+
+        // Scrollbar: position of the selection within the filtered list
+        if !filtered_entries.is_empty() {
+            let mut scrollbar_state = ScrollbarState::new(filtered_entries.len())
+                .viewport_content_length(window_end - window_start)
+                .position(selected.unwrap_or(window_start));
+            let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .track_style(Style::default().fg(theme.border))
+                .thumb_style(Style::default().fg(theme.accent));
+            f.render_stateful_widget(
+                scrollbar,
+                scrollbar_chunk.inner(Margin {
+                    vertical: 1,
+                    horizontal: 0,
+                }),
+                &mut scrollbar_state,
+            );
+        }
+
+        // Preview pane: the text description stays underneath the
+        // thumbnail, so it shows through if the image can't be drawn
+        if let Some(area) = preview_chunk {
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(theme.border))
+                .title(" Preview ");
+            let inner = block.inner(area);
+            let selected_entry = app_state
+                .list_state
+                .selected()
+                .and_then(|idx| filtered_entries.get(idx));
+            let mut lines: Vec<Line> = Vec::new();
+            // Kitty terminals draw the real image over the pane instead
+            if let Some(entry) = selected_entry.filter(|entry| {
+                !kitty_graphics && entry.content_type == ClipboardContentType::Image
+            }) {
+                let cached = braille_thumbnail
+                    .as_ref()
+                    .is_some_and(|(id, cached_area, _)| *id == entry.id && *cached_area == inner);
+                if !cached {
+                    let path = images_dir.join(&entry.content);
+                    let art = thumbnail::braille_thumbnail(&path, inner).unwrap_or_else(|e| {
+                        debug!("No thumbnail: {}", e);
+                        Vec::new()
+                    });
+                    *braille_thumbnail = Some((entry.id, inner, art));
+                }
+                if let Some((_, _, art)) = &braille_thumbnail {
+                    lines.extend(
+                        art.iter().map(|row| {
+                            Line::styled(row.clone(), Style::default().fg(theme.accent))
+                        }),
+                    );
+                    if !art.is_empty() {
+                        lines.push(Line::default());
+                    }
+                }
+            }
+            if let Some(entry) = selected_entry {
+                lines.extend(
+                    entry
+                        .preview_lines_with_reveal(false, inner.width as usize)
+                        .into_iter()
+                        .map(Line::from),
                 );
+            }
+            let preview = Paragraph::new(lines)
+                .block(block)
+                .style(Style::default().fg(theme.text))
+                .wrap(ratatui::widgets::Wrap { trim: false });
+            f.render_widget(preview, area);
+            *preview_area = Some(inner);
+        } else {
+            *preview_area = None;
+        }
+
+        // ========================
+        // 3. FOOTER (Styled Keys)
+        // ========================
+        let key_style = Style::default()
+            .fg(theme.title)
+            .add_modifier(Modifier::BOLD);
+        let text_style = Style::default().fg(theme.emphasis);
+        let sep_style = Style::default().fg(theme.meta);
+
+        // Check if the currently selected entry is a secret to show contextual hints
+        let selected_is_secret = app_state
+            .list_state
+            .selected()
+            .and_then(|idx| filtered_entries.get(idx))
+            .map(|e| e.is_secret())
+            .unwrap_or(false);
+
+        let mut footer_spans = vec![
+            Span::styled("↑↓", key_style),
+            Span::styled(" Nav ", text_style),
+            Span::styled("|", sep_style),
+            Span::styled(" Enter", key_style),
+            Span::styled(" Copy ", text_style),
+            Span::styled("|", sep_style),
+            Span::styled(" O", key_style),
+            Span::styled(" Open ", text_style),
+            Span::styled("|", sep_style),
+            Span::styled(" P", key_style),
+            Span::styled(" Pin ", text_style),
+            Span::styled("|", sep_style),
+            Span::styled(" D", key_style),
+            Span::styled(" Del ", text_style),
+            Span::styled("|", sep_style),
+            Span::styled(" U", key_style),
+            Span::styled(" Undo ", text_style),
+            Span::styled("|", sep_style),
+            Span::styled(" S", key_style),
+            Span::styled(" Search ", text_style),
+            Span::styled("|", sep_style),
+            Span::styled(" Tab", key_style),
+            Span::styled(
+                format!(" {} ", selection_filter_label(app_state.selection_filter)),
+                text_style,
+            ),
+            Span::styled("|", sep_style),
+            Span::styled(" T", key_style),
+            Span::styled(" Type ", text_style),
+            Span::styled("|", sep_style),
+            Span::styled(" E", key_style),
+            Span::styled(" Emoji ", text_style),
+        ];
+
+        if selected_is_secret {
+            footer_spans.push(Span::styled("|", sep_style));
+            footer_spans.push(Span::styled(" R", key_style));
+            footer_spans.push(Span::styled(" Reveal ", text_style));
+            footer_spans.push(Span::styled("|", sep_style));
+            footer_spans.push(Span::styled(" ⇧S", key_style));
+            footer_spans.push(Span::styled(" Keep ", text_style));
+        }
+
+        if !app_state.marked.is_empty() {
+            footer_spans.push(Span::styled("|", sep_style));
+            footer_spans.push(Span::styled(" D", key_style));
+            footer_spans.push(Span::styled(
+                format!(" Del {} marked ", app_state.marked.len()),
+                text_style,
+            ));
+        }
+
+        if over_disk_cap {
+            footer_spans.push(Span::styled("|", sep_style));
+            footer_spans.push(Span::styled(" X", key_style));
+            footer_spans.push(Span::styled(" Free space ", text_style));
+        }
+
+        footer_spans.push(Span::styled("|", sep_style));
+        footer_spans.push(Span::styled(" C", key_style));
+        footer_spans.push(Span::styled(" Clear ", text_style));
+        footer_spans.push(Span::styled("|", sep_style));
+        footer_spans.push(Span::styled(" Esc", key_style));
+        footer_spans.push(Span::styled(" Close", text_style));
+
+        // A copy with K reports back here for a moment
+        if let Some(message) = app_state.flash_message() {
+            footer_spans = vec![Span::styled(message.to_string(), key_style)];
+        }
+
+        let footer = Paragraph::new(Line::from(footer_spans)).alignment(Alignment::Center);
+
+        f.render_widget(footer, chunks[2]);
+    }
+
+    // ========================================
+    // MODAL: Clear Confirm
+    // ========================================
+    if app_state.show_clear_confirm {
+        // What C removes: pinned entries stay with `clear_keeps_pinned`,
+        // unless the typed "yes" confirmation is open
+        let keep_pinned = config().clear_keeps_pinned && app_state.clear_all_input.is_none();
+        let pinned = all_entries.iter().filter(|e| e.pinned).count();
+        let removed: Vec<&crate::models::ClipboardEntry> = all_entries
+            .iter()
+            .filter(|e| !(keep_pinned && e.pinned))
+            .collect();
+        let removed_size = format_size(removed.iter().map(|e| e.stored_bytes()).sum::<u64>());
+        let (title, message, hint) = match &app_state.clear_all_input {
+            Some(input) => (
+                "⚠  Delete Everything?",
+                format!(
+                    "All {} entries ({}) will be deleted, including {} pinned.\nType yes to confirm: {}_",
+                    removed.len(),
+                    removed_size,
+                    pinned,
+                    input
+                ),
+                "Enter to confirm • Esc to cancel",
+            ),
+            None if keep_pinned && pinned > 0 => (
+                "⚠  Clear History?",
+                format!(
+                    "{} entries ({}) will be deleted.\n{} pinned entries are kept.",
+                    removed.len(),
+                    removed_size,
+                    pinned
+                ),
+                "Y to confirm • A to delete pinned too • N or Esc to cancel",
+            ),
+            None => (
+                "⚠  Clear All History?",
+                format!(
+                    "All {} entries ({}) will be deleted, including {} pinned.",
+                    removed.len(),
+                    removed_size,
+                    pinned
+                ),
+                "Press Y to confirm • N or Esc to cancel",
+            ),
+        };
+        render_confirm(f, title, &message, hint, theme);
+    }
+
+    // ========================================
+    // MODAL: Delete Confirm
+    // ========================================
+    if app_state.show_delete_confirm {
+        let message = match app_state.marked.len() {
+            0 => "The selected entry will be deleted (U undoes).".to_string(),
+            n => format!("{} marked entries will be deleted (U undoes).", n),
+        };
+        render_confirm(
+            f,
+            "⚠  Delete?",
+            &message,
+            "Press Y to confirm • N or Esc to cancel",
+            theme,
+        );
+    }
+
+    // ========================================
+    // MODAL: Emoji Picker
+    // ========================================
+    if app_state.show_emoji_picker {
+        let area = f.area();
+
+        // Overlay layout: centered box taking most of the screen
+        let v_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(5),
+                Constraint::Min(10),
+                Constraint::Percentage(5),
+            ])
+            .split(area);
+
+        let h_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(10),
+                Constraint::Min(40),
+                Constraint::Percentage(10),
+            ])
+            .split(v_chunks[1]);
+
+        let picker_area = h_chunks[1];
+
+        // Clear the background
+        f.render_widget(Clear, picker_area);
+
+        // Split picker into: search bar, category tabs, item grid, footer
+        let picker_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1), // Search bar
+                Constraint::Length(3), // Category tabs
+                Constraint::Min(3),    // Item grid
+                Constraint::Length(1), // Footer
+            ])
+            .split(picker_area);
+
+        // -- Search bar --
+        let search_text = if app_state.emoji_search.is_empty() {
+            Span::styled(
+                " 🔍 Type to search emoticons...",
+                Style::default().fg(theme.meta),
+            )
+        } else {
+            Span::styled(
+                format!(" 🔍 {}_", app_state.emoji_search),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            )
+        };
+        let search_bar = Paragraph::new(search_text);
+        f.render_widget(search_bar, picker_chunks[0]);
+
+        // Determine if we're in search mode or browsing mode
+        let is_emoji_searching = !app_state.emoji_search.is_empty();
+
+        if is_emoji_searching {
+            // -- Search results mode --
+            let search_results = emoji::search_emoticons(emoji_cats, &app_state.emoji_search);
+
+            // Category tabs: show "Search Results" label
+            let tab_line = Line::from(Span::styled(
+                format!("  🔍 Search Results ({})  ", search_results.len()),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            let tabs = Paragraph::new(tab_line).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(theme.picker)),
+            );
+            f.render_widget(tabs, picker_chunks[1]);
+
+            // Build cells from search results
+            let cells: Vec<EmojiCell> = search_results
+                .iter()
+                .map(|r| EmojiCell {
+                    value: r.value.to_string(),
+                    name: r.name.to_string(),
+                })
+                .collect();
+
+            render_emoji_grid(
+                f,
+                &cells,
+                picker_chunks[2],
+                &mut app_state.emoji_item_index,
+                &mut app_state.emoji_grid_cols,
+                &mut app_state.emoji_grid_scroll,
+                theme,
+            );
+        } else {
+            // -- Browse mode --
+            let cat_count = emoji_cats.len();
+            let selected_cat = app_state
+                .emoji_category_index
+                .min(cat_count.saturating_sub(1));
+
+            // ---- Scrollable category tab strip ----
+            // We manually render category labels into a single
+            // Line, computing a character-level scroll offset so
+            // the selected tab is always visible.
+
+            let tab_inner_w = picker_chunks[1].width.saturating_sub(2) as usize; // border
+
+            // Build each tab string and record char-offset ranges
+            let mut tab_spans: Vec<TabSpan> = Vec::new();
+            let mut cursor: usize = 0;
+            for (idx, cat) in emoji_cats.iter().enumerate() {
+                let label = format!(" {} {} ", cat.icon, cat.name);
+                let len = label.chars().count();
+                // Add divider before all but the first
+                if idx > 0 {
+                    let div = " │ ";
+                    let div_len = div.chars().count();
+                    tab_spans.push(TabSpan {
+                        text: div.to_string(),
+                        start: cursor,
+                        end: cursor + div_len,
+                    });
+                    cursor += div_len;
+                }
+                tab_spans.push(TabSpan {
+                    text: label,
+                    start: cursor,
+                    end: cursor + len,
+                });
+                cursor += len;
+            }
+
+            // Find the char range of the selected category's label
+            // (skip divider spans — category labels are at even-ish
+            // positions; simpler: find by index)
+            let mut sel_start: usize = 0;
+            let mut sel_end: usize = 0;
+            {
+                let mut cat_idx = 0;
+                for ts in &tab_spans {
+                    if ts.text.starts_with(" │") || ts.text == " │ " {
+                        continue; // divider
+                    }
+                    if cat_idx == selected_cat {
+                        sel_start = ts.start;
+                        sel_end = ts.end;
+                        break;
+                    }
+                    cat_idx += 1;
+                }
+            }
+
+            // Compute scroll offset so selected tab is visible,
+            // with a small margin so context tabs show on each side.
+            let margin: usize = 3;
+            let mut scroll_off: usize = 0;
+            if sel_end + margin > tab_inner_w {
+                scroll_off = (sel_end + margin).saturating_sub(tab_inner_w);
+            }
+            if sel_start < scroll_off + margin {
+                scroll_off = sel_start.saturating_sub(margin);
+            }
 
-                let centered = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Percentage(35),
-                        Constraint::Length(9),
-                        Constraint::Percentage(35),
-                    ])
-                    .split(area);
-
-                let h_centered = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Percentage(20),
-                        Constraint::Percentage(60),
-                        Constraint::Percentage(20),
-                    ])
-                    .split(centered[1]);
-
-                f.render_widget(Clear, h_centered[1]);
-                f.render_widget(text, h_centered[1]);
+            // Build the visible spans by slicing the virtual line
+            let mut visible_spans: Vec<Span> = Vec::new();
+
+            // Arrow indicator if there are hidden tabs to the left
+            let has_left = scroll_off > 0;
+            let has_right = cursor > scroll_off + tab_inner_w;
+            let arrow_w: usize = (if has_left { 2 } else { 0 }) + (if has_right { 2 } else { 0 });
+            let content_w = tab_inner_w.saturating_sub(arrow_w);
+
+            if has_left {
+                visible_spans.push(Span::styled(
+                    "◀ ",
+                    Style::default()
+                        .fg(theme.picker)
+                        .add_modifier(Modifier::BOLD),
+                ));
             }
 
-            // ========================================
-            // MODAL: Emoji Picker
-            // ========================================
-            if app_state.show_emoji_picker {
-                let area = f.area();
-
-                // Overlay layout: centered box taking most of the screen
-                let v_chunks = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Percentage(5),
-                        Constraint::Min(10),
-                        Constraint::Percentage(5),
-                    ])
-                    .split(area);
-
-                let h_chunks = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([
-                        Constraint::Percentage(10),
-                        Constraint::Min(40),
-                        Constraint::Percentage(10),
-                    ])
-                    .split(v_chunks[1]);
-
-                let picker_area = h_chunks[1];
-
-                // Clear the background
-                f.render_widget(Clear, picker_area);
-
-                // Split picker into: search bar, category tabs, item grid, footer
-                let picker_chunks = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Length(1), // Search bar
-                        Constraint::Length(3), // Category tabs
-                        Constraint::Min(3),    // Item grid
-                        Constraint::Length(1), // Footer
-                    ])
-                    .split(picker_area);
-
-                // -- Search bar --
-                let search_text = if app_state.emoji_search.is_empty() {
-                    Span::styled(
-                        " 🔍 Type to search emoticons...",
-                        Style::default().fg(Color::DarkGray),
-                    )
+            // Render tab spans clipped to the visible window
+            let vis_start = scroll_off;
+            let vis_end = scroll_off + content_w;
+            let mut cat_idx: usize = 0;
+            for ts in &tab_spans {
+                let is_divider = ts.text.starts_with(" │") || ts.text == " │ ";
+
+                // Skip spans entirely outside the window
+                if ts.end <= vis_start || ts.start >= vis_end {
+                    if !is_divider {
+                        cat_idx += 1;
+                    }
+                    continue;
+                }
+
+                // Compute how much of this span is visible
+                let clip_start = if ts.start < vis_start {
+                    vis_start - ts.start
+                } else {
+                    0
+                };
+                let clip_end_chars = ts.text.chars().count();
+                let avail = if ts.end > vis_end {
+                    clip_end_chars - (ts.end - vis_end)
                 } else {
-                    Span::styled(
-                        format!(" 🔍 {}_", app_state.emoji_search),
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
-                    )
+                    clip_end_chars
                 };
-                let search_bar = Paragraph::new(search_text);
-                f.render_widget(search_bar, picker_chunks[0]);
-
-                // Determine if we're in search mode or browsing mode
-                let is_emoji_searching = !app_state.emoji_search.is_empty();
-
-                if is_emoji_searching {
-                    // -- Search results mode --
-                    let search_results =
-                        emoji::search_emoticons(&emoji_cats, &app_state.emoji_search);
-
-                    // Category tabs: show "Search Results" label
-                    let tab_line = Line::from(Span::styled(
-                        format!("  🔍 Search Results ({})  ", search_results.len()),
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
-                    ));
-                    let tabs = Paragraph::new(tab_line).block(
-                        Block::default()
-                            .borders(Borders::ALL)
-                            .border_type(BorderType::Rounded)
-                            .border_style(Style::default().fg(Color::Magenta)),
-                    );
-                    f.render_widget(tabs, picker_chunks[1]);
+                let visible_text: String = ts
+                    .text
+                    .chars()
+                    .skip(clip_start)
+                    .take(avail.saturating_sub(clip_start))
+                    .collect();
 
-                    // Build cells from search results
-                    let cells: Vec<EmojiCell> = search_results
+                if visible_text.is_empty() {
+                    if !is_divider {
+                        cat_idx += 1;
+                    }
+                    continue;
+                }
+
+                let style = if is_divider {
+                    Style::default().fg(theme.meta)
+                } else if cat_idx == selected_cat {
+                    Style::default()
+                        .fg(theme.title)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.meta)
+                };
+
+                visible_spans.push(Span::styled(visible_text, style));
+
+                if !is_divider {
+                    cat_idx += 1;
+                }
+            }
+
+            if has_right {
+                visible_spans.push(Span::styled(
+                    " ▶",
+                    Style::default()
+                        .fg(theme.picker)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+
+            let tab_bar = Paragraph::new(Line::from(visible_spans))
+                .alignment(Alignment::Left)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(theme.picker)),
+                );
+            f.render_widget(tab_bar, picker_chunks[1]);
+
+            // ---- Emoji grid for the selected category ----
+            let current_cat = selected_cat;
+            let cells: Vec<EmojiCell> = emoji_cats
+                .get(current_cat)
+                .map(|category| {
+                    category
+                        .emoticons
                         .iter()
-                        .map(|r| EmojiCell {
-                            value: r.value.to_string(),
-                            name: r.name.to_string(),
+                        .map(|e| EmojiCell {
+                            value: e.value.to_string(),
+                            name: e.name.to_string(),
                         })
-                        .collect();
-
-                    render_emoji_grid(
-                        f,
-                        &cells,
-                        picker_chunks[2],
-                        &mut app_state.emoji_item_index,
-                        &mut app_state.emoji_grid_cols,
-                        &mut app_state.emoji_grid_scroll,
-                    );
-                } else {
-                    // -- Browse mode --
-                    let cat_count = emoji_cats.len();
-                    let selected_cat = app_state
-                        .emoji_category_index
-                        .min(cat_count.saturating_sub(1));
-
-                    // ---- Scrollable category tab strip ----
-                    // We manually render category labels into a single
-                    // Line, computing a character-level scroll offset so
-                    // the selected tab is always visible.
-
-                    let tab_inner_w = picker_chunks[1].width.saturating_sub(2) as usize; // border
-
-                    // Build each tab string and record char-offset ranges
-                    let mut tab_spans: Vec<TabSpan> = Vec::new();
-                    let mut cursor: usize = 0;
-                    for (idx, cat) in emoji_cats.iter().enumerate() {
-                        let label = format!(" {} {} ", cat.icon, cat.name);
-                        let len = label.chars().count();
-                        // Add divider before all but the first
-                        if idx > 0 {
-                            let div = " │ ";
-                            let div_len = div.chars().count();
-                            tab_spans.push(TabSpan {
-                                text: div.to_string(),
-                                start: cursor,
-                                end: cursor + div_len,
-                            });
-                            cursor += div_len;
-                        }
-                        tab_spans.push(TabSpan {
-                            text: label,
-                            start: cursor,
-                            end: cursor + len,
-                        });
-                        cursor += len;
-                    }
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            render_emoji_grid(
+                f,
+                &cells,
+                picker_chunks[2],
+                &mut app_state.emoji_item_index,
+                &mut app_state.emoji_grid_cols,
+                &mut app_state.emoji_grid_scroll,
+                theme,
+            );
+        }
 
-                    // Find the char range of the selected category's label
-                    // (skip divider spans — category labels are at even-ish
-                    // positions; simpler: find by index)
-                    let mut sel_start: usize = 0;
-                    let mut sel_end: usize = 0;
-                    {
-                        let mut cat_idx = 0;
-                        for ts in &tab_spans {
-                            if ts.text.starts_with(" │") || ts.text == " │ " {
-                                continue; // divider
-                            }
-                            if cat_idx == selected_cat {
-                                sel_start = ts.start;
-                                sel_end = ts.end;
-                                break;
-                            }
-                            cat_idx += 1;
-                        }
-                    }
+        // -- Picker footer --
+        let pk = Style::default()
+            .fg(theme.picker)
+            .add_modifier(Modifier::BOLD);
+        let pt = Style::default().fg(theme.emphasis);
+        let ps = Style::default().fg(theme.meta);
+
+        let picker_footer_spans = if app_state.emoji_search.is_empty() {
+            vec![
+                Span::styled("↑↓←→", pk),
+                Span::styled(" Navigate ", pt),
+                Span::styled("|", ps),
+                Span::styled(" Tab/⇧Tab", pk),
+                Span::styled(" Category ", pt),
+                Span::styled("|", ps),
+                Span::styled(" Enter", pk),
+                Span::styled(" Copy ", pt),
+                Span::styled("|", ps),
+                Span::styled(" Type", pk),
+                Span::styled(" Search ", pt),
+                Span::styled("|", ps),
+                Span::styled(" Esc", pk),
+                Span::styled(" Close", pt),
+            ]
+        } else {
+            vec![
+                Span::styled("↑↓←→", pk),
+                Span::styled(" Navigate ", pt),
+                Span::styled("|", ps),
+                Span::styled(" Enter", pk),
+                Span::styled(" Copy ", pt),
+                Span::styled("|", ps),
+                Span::styled(" Esc", pk),
+                Span::styled(" Clear search", pt),
+            ]
+        };
+
+        let picker_footer =
+            Paragraph::new(Line::from(picker_footer_spans)).alignment(Alignment::Center);
+        f.render_widget(picker_footer, picker_chunks[3]);
+    }
+}
 
-                    // Compute scroll offset so selected tab is visible,
-                    // with a small margin so context tabs show on each side.
-                    let margin: usize = 3;
-                    let mut scroll_off: usize = 0;
-                    if sel_end + margin > tab_inner_w {
-                        scroll_off = (sel_end + margin).saturating_sub(tab_inner_w);
-                    }
-                    if sel_start < scroll_off + margin {
-                        scroll_off = sel_start.saturating_sub(margin);
-                    }
+/// Run the picker. Colors are dropped when `no_color` is set or `NO_COLOR`
+/// is present in the environment; `debug_scores` shows each result's fuzzy
+/// match score while searching.
+pub fn show_ui(
+    backend: ClipboardBackend,
+    no_color: bool,
+    recent: Option<usize>,
+    debug_scores: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let history = ClipboardHistory::new().with_control_socket();
 
-                    // Build the visible spans by slicing the virtual line
-                    let mut visible_spans: Vec<Span> = Vec::new();
-
-                    // Arrow indicator if there are hidden tabs to the left
-                    let has_left = scroll_off > 0;
-                    let has_right = cursor > scroll_off + tab_inner_w;
-                    let arrow_w: usize =
-                        (if has_left { 2 } else { 0 }) + (if has_right { 2 } else { 0 });
-                    let content_w = tab_inner_w.saturating_sub(arrow_w);
-
-                    if has_left {
-                        visible_spans.push(Span::styled(
-                            "◀ ",
-                            Style::default()
-                                .fg(Color::Magenta)
-                                .add_modifier(Modifier::BOLD),
-                        ));
-                    }
+    enable_raw_mode()?;
+    let mut stdout = std::io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend_term = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend_term)?;
+    terminal.clear()?;
 
-                    // Render tab spans clipped to the visible window
-                    let vis_start = scroll_off;
-                    let vis_end = scroll_off + content_w;
-                    let mut cat_idx: usize = 0;
-                    for ts in &tab_spans {
-                        let is_divider = ts.text.starts_with(" │") || ts.text == " │ ";
-
-                        // Skip spans entirely outside the window
-                        if ts.end <= vis_start || ts.start >= vis_end {
-                            if !is_divider {
-                                cat_idx += 1;
-                            }
-                            continue;
-                        }
+    let mut app_state = AppState::new();
+    // `--recent N`: just the newest N entries, one line each
+    if recent.is_some() {
+        app_state.compact_list = true;
+    }
+    if config().remember_cursor {
+        app_state.restore_cursor(history.data_dir(), &history.get_all());
+    }
 
-                        // Compute how much of this span is visible
-                        let clip_start = if ts.start < vis_start {
-                            vis_start - ts.start
-                        } else {
-                            0
-                        };
-                        let clip_end_chars = ts.text.chars().count();
-                        let avail = if ts.end > vis_end {
-                            clip_end_chars - (ts.end - vis_end)
-                        } else {
-                            clip_end_chars
-                        };
-                        let visible_text: String = ts
-                            .text
-                            .chars()
-                            .skip(clip_start)
-                            .take(avail.saturating_sub(clip_start))
-                            .collect();
-
-                        if visible_text.is_empty() {
-                            if !is_divider {
-                                cat_idx += 1;
-                            }
-                            continue;
-                        }
+    // Entries gathered with y, pasted together with Y
+    let mut stash = Stash::load(history.data_dir());
 
-                        let style = if is_divider {
-                            Style::default().fg(Color::DarkGray)
-                        } else if cat_idx == selected_cat {
-                            Style::default()
-                                .fg(Color::Cyan)
-                                .add_modifier(Modifier::BOLD)
-                        } else {
-                            Style::default().fg(Color::DarkGray)
-                        };
+    // Build emoji categories once outside the loop
+    let emoji_cats = emoji::categories();
 
-                        visible_spans.push(Span::styled(visible_text, style));
+    // Fuzzy matchers used by search, picked by `search_case_sensitive`
+    let ignore_case_matcher = SkimMatcherV2::default().ignore_case();
+    let case_matcher = SkimMatcherV2::default().respect_case();
+    let plain_theme = Theme::plain();
+    let no_color = no_color || std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty());
+    let theme = if no_color {
+        &plain_theme
+    } else {
+        &config().theme
+    };
 
-                        if !is_divider {
-                            cat_idx += 1;
-                        }
-                    }
+    // Where the list was drawn and how tall each item is, for mouse hit-testing
+    let mut list_area = Rect::default();
+    let mut item_heights: Vec<usize> = Vec::new();
+
+    // Image previews need the Kitty graphics protocol; the pane is only shown
+    // when it's available. `shown_thumbnail` is the (entry id, area) on screen.
+    let kitty_graphics = thumbnail::supports_kitty_graphics();
+    let mut preview_area: Option<Rect> = None;
+    let mut shown_thumbnail: Option<(u64, Rect)> = None;
+    // Without it, `preview = true` shows the pane with braille thumbnails.
+    // Only the selected image is decoded: (entry id, area, lines) is reused
+    // until either changes.
+    let show_preview = kitty_graphics || config().preview;
+    let mut braille_thumbnail: Option<(u64, Rect, Vec<String>)> = None;
+    // Entries captured while the window is open show up on the next check;
+    // the selection stays on the entry it was on (`selected_id`)
+    let mut last_reload = Instant::now();
+    let mut selected_id: Option<u64> = None;
 
-                    if has_right {
-                        visible_spans.push(Span::styled(
-                            " ▶",
-                            Style::default()
-                                .fg(Color::Magenta)
-                                .add_modifier(Modifier::BOLD),
-                        ));
-                    }
+    loop {
+        if last_reload.elapsed() >= Duration::from_millis(UI_RELOAD_MS) {
+            last_reload = Instant::now();
+            if history.check_and_reload() && app_state.follow_entry.is_none() {
+                app_state.follow_entry = selected_id;
+            }
+        }
 
-                    let tab_bar = Paragraph::new(Line::from(visible_spans))
-                        .alignment(Alignment::Left)
-                        .block(
-                            Block::default()
-                                .borders(Borders::ALL)
-                                .border_type(BorderType::Rounded)
-                                .border_style(Style::default().fg(Color::Magenta)),
-                        );
-                    f.render_widget(tab_bar, picker_chunks[1]);
-
-                    // ---- Emoji grid for the selected category ----
-                    let current_cat = selected_cat;
-                    let cells: Vec<EmojiCell> = emoji_cats
-                        .get(current_cat)
-                        .map(|category| {
-                            category
-                                .emoticons
-                                .iter()
-                                .map(|e| EmojiCell {
-                                    value: e.value.to_string(),
-                                    name: e.name.to_string(),
-                                })
-                                .collect()
-                        })
-                        .unwrap_or_default();
-
-                    render_emoji_grid(
-                        f,
-                        &cells,
-                        picker_chunks[2],
-                        &mut app_state.emoji_item_index,
-                        &mut app_state.emoji_grid_cols,
-                        &mut app_state.emoji_grid_scroll,
-                    );
+        // Clean up any expired secrets each tick
+        history.cleanup_expired();
+
+        // Filter entries based on selection filter and search query.
+        // Search results are ranked by fuzzy score; the stable sort keeps the
+        // existing (pinned-first, newest-first) order for equal scores.
+        let all_entries = history.get_all();
+        let matcher = if app_state.search_case_sensitive {
+            &case_matcher
+        } else {
+            &ignore_case_matcher
+        };
+        let filtering_search = app_state.is_searching && !app_state.search_query.is_empty();
+        // `tag:<name>` words filter by tag; the rest is matched against the content
+        let (search_tags, search_text) = split_tag_filters(&app_state.search_query);
+        let matching_text = filtering_search && !search_text.is_empty();
+        // In regex mode an invalid pattern matches nothing (and is shown in red)
+        let search_regex =
+            (matching_text && app_state.search_is_regex).then(|| Regex::new(&search_text));
+        let regex_invalid = matches!(search_regex, Some(Err(_)));
+        let mut scored_entries: Vec<(i64, &crate::models::ClipboardEntry)> = all_entries
+            .iter()
+            .filter(|e| {
+                app_state
+                    .selection_filter
+                    .is_none_or(|selection| e.selection == selection)
+            })
+            .filter(|e| {
+                app_state
+                    .type_filter
+                    .is_none_or(|content_type| e.content_type == content_type)
+            })
+            .filter(|e| {
+                app_state
+                    .source_filter
+                    .as_ref()
+                    .is_none_or(|source| e.source_app.as_ref() == Some(source))
+            })
+            .filter_map(|e| {
+                if !filtering_search {
+                    return Some((0, e));
+                }
+                if !search_tags.iter().all(|tag| e.has_tag(tag)) {
+                    return None;
                 }
+                if search_text.is_empty() {
+                    return Some((0, e));
+                }
+                search_score(matcher, e, &search_text, search_regex.as_ref())
+                    .map(|score| (score, e))
+            })
+            .collect();
+        if let Some(count) = recent {
+            // Newest first regardless of pins, so the last copies are on top
+            scored_entries.sort_by_key(|(_, e)| std::cmp::Reverse(e.timestamp));
+            scored_entries.truncate(count);
+        }
+        if filtering_search {
+            scored_entries.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        } else if app_state.sort_by_copies {
+            // Pins stay on top; equal counts keep the newest-first order
+            scored_entries.sort_by_key(|(_, e)| {
+                (std::cmp::Reverse(e.pinned), std::cmp::Reverse(e.copy_count))
+            });
+        }
+        let (filtered_scores, filtered_entries): (Vec<i64>, Vec<&crate::models::ClipboardEntry>) =
+            scored_entries.into_iter().unzip();
+        // `--debug-scores`: the fuzzy score each result was ranked by
+        let shown_scores =
+            (debug_scores && matching_text && search_regex.is_none()).then_some(&filtered_scores);
+
+        // Keep the selection on an entry that was just moved
+        if let Some(index) = app_state
+            .follow_entry
+            .take()
+            .and_then(|id| filtered_entries.iter().position(|e| e.id == id))
+        {
+            app_state.list_state.select(Some(index));
+        }
 
-                // -- Picker footer --
-                let pk = Style::default()
-                    .fg(Color::Magenta)
-                    .add_modifier(Modifier::BOLD);
-                let pt = Style::default().fg(Color::White);
-                let ps = Style::default().fg(Color::DarkGray);
-
-                let picker_footer_spans = if app_state.emoji_search.is_empty() {
-                    vec![
-                        Span::styled("↑↓←→", pk),
-                        Span::styled(" Navigate ", pt),
-                        Span::styled("|", ps),
-                        Span::styled(" Tab/⇧Tab", pk),
-                        Span::styled(" Category ", pt),
-                        Span::styled("|", ps),
-                        Span::styled(" Enter", pk),
-                        Span::styled(" Copy ", pt),
-                        Span::styled("|", ps),
-                        Span::styled(" Type", pk),
-                        Span::styled(" Search ", pt),
-                        Span::styled("|", ps),
-                        Span::styled(" Esc", pk),
-                        Span::styled(" Close", pt),
-                    ]
-                } else {
-                    vec![
-                        Span::styled("↑↓←→", pk),
-                        Span::styled(" Navigate ", pt),
-                        Span::styled("|", ps),
-                        Span::styled(" Enter", pk),
-                        Span::styled(" Copy ", pt),
-                        Span::styled("|", ps),
-                        Span::styled(" Esc", pk),
-                        Span::styled(" Clear search", pt),
-                    ]
-                };
+        // Leave the detail view if its entry went away (e.g. an expired secret)
+        let detail_entry_gone = match &app_state.mode {
+            AppMode::Detail(view) => !all_entries.iter().any(|e| e.id == view.entry_id),
+            AppMode::List => false,
+        };
+        if detail_entry_gone {
+            app_state.close_detail();
+        }
 
-                let picker_footer =
-                    Paragraph::new(Line::from(picker_footer_spans)).alignment(Alignment::Center);
-                f.render_widget(picker_footer, picker_chunks[3]);
+        // Clear reveal if the selected index changed away from the revealed entry
+        if let Some(reveal_idx) = app_state.reveal_index {
+            let current_sel = app_state.list_state.selected().unwrap_or(usize::MAX);
+            if current_sel != reveal_idx {
+                app_state.reveal_index = None;
             }
-        })?;
+        }
+
+        // The daemon keeps this file while capture is paused (--toggle-pause)
+        let capture_paused = history.data_dir().join(PAUSE_FILE).exists();
+        // The daemon's backend, and whether its session is still there
+        let daemon_backend = read_backend_file(history.data_dir());
+        let disk_usage = history.disk_usage();
+        let disk_warn_bytes = config().disk_warn_bytes;
+        let over_disk_cap = disk_warn_bytes.is_some_and(|cap| disk_usage > cap);
+
+        item_heights.clear();
+        let frame = FrameData {
+            backend,
+            images_dir: history.images_dir(),
+            theme,
+            recent,
+            all_entries: &all_entries,
+            filtered_entries: &filtered_entries,
+            shown_scores,
+            matcher,
+            matching_text,
+            search_text: &search_text,
+            search_regex: &search_regex,
+            regex_invalid,
+            stash: &stash,
+            emoji_cats: &emoji_cats,
+            capture_paused,
+            daemon_backend: &daemon_backend,
+            disk_usage,
+            over_disk_cap,
+            kitty_graphics,
+            show_preview,
+        };
+        let layout = FrameLayout {
+            list_area: &mut list_area,
+            item_heights: &mut item_heights,
+            preview_area: &mut preview_area,
+            braille_thumbnail: &mut braille_thumbnail,
+        };
+        terminal.draw(|f| draw_frame(f, &mut app_state, &frame, layout))?;
+
+        // ====================================================================
+        // IMAGE THUMBNAIL (Kitty graphics, drawn on top of the frame)
+        // ====================================================================
+        if kitty_graphics {
+            let modal_open = app_state.show_emoji_picker
+                || app_state.show_clear_confirm
+                || app_state.show_delete_confirm
+                || matches!(app_state.mode, AppMode::Detail(_));
+            let selected_image = app_state
+                .list_state
+                .selected()
+                .and_then(|idx| filtered_entries.get(idx))
+                .filter(|entry| entry.content_type == ClipboardContentType::Image && !modal_open)
+                .zip(preview_area);
+            let wanted = selected_image.map(|(entry, area)| (entry.id, area));
+
+            // Only re-send the image when the selection or pane changes
+            if wanted != shown_thumbnail {
+                let out = terminal.backend_mut();
+                write!(out, "{}", KITTY_CLEAR)?;
+                if let Some((entry, area)) = selected_image {
+                    let path = history.images_dir().join(&entry.content);
+                    match thumbnail::kitty_thumbnail(&path, area) {
+                        Ok(sequence) => {
+                            execute!(out, MoveTo(area.x, area.y))?;
+                            write!(out, "{}", sequence)?;
+                        }
+                        Err(e) => debug!("No thumbnail: {}", e),
+                    }
+                }
+                out.flush()?;
+                shown_thumbnail = wanted;
+            }
+        }
 
         // ====================================================================
         // INPUT HANDLING
         // ====================================================================
         if event::poll(Duration::from_millis(50))? {
-            if let CrosstermEvent::Key(key) = event::read()? {
+            let event = event::read()?;
+            if let CrosstermEvent::Mouse(mouse) = event {
+                // Mouse only drives the history list, not the modals
+                if !app_state.show_emoji_picker
+                    && !app_state.show_clear_confirm
+                    && !app_state.show_delete_confirm
+                    && matches!(app_state.mode, AppMode::List)
+                {
+                    handle_list_mouse(
+                        mouse,
+                        &mut app_state,
+                        list_area,
+                        &item_heights,
+                        filtered_entries.len(),
+                    );
+                }
+            } else if let CrosstermEvent::Resize(..) = event {
+                // ratatui only redraws cells it thinks changed, which can leave
+                // stale fragments behind after a resize; start from a blank
+                // screen and re-send the thumbnail for the new layout
+                terminal.clear()?;
+                if kitty_graphics {
+                    write!(terminal.backend_mut(), "{}", KITTY_CLEAR)?;
+                }
+                shown_thumbnail = None;
+            } else if let CrosstermEvent::Key(key) = event {
                 // ---- Emoji Picker Mode ----
                 if app_state.show_emoji_picker {
                     let is_emoji_searching = !app_state.emoji_search.is_empty();
@@ -910,30 +2079,133 @@ pub fn show_ui(backend: ClipboardBackend) -> Result<(), Box<dyn std::error::Erro
                         _ => {}
                     }
                 }
+                // ---- Detail View ----
+                else if let AppMode::Detail(view) = &mut app_state.mode {
+                    let page = view.page_size as isize;
+                    match config().keymap.action(key.code) {
+                        Some(Action::Quit) => app_state.close_detail(),
+                        Some(Action::Down) => view.move_cursor(1),
+                        Some(Action::Up) => view.move_cursor(-1),
+                        Some(Action::Copy) => {
+                            app_state.detail_selected = Some(view.selected_text())
+                        }
+                        Some(_) => {}
+                        None => match key.code {
+                            KeyCode::Esc => app_state.close_detail(),
+                            KeyCode::PageDown => view.move_cursor(page),
+                            KeyCode::PageUp => view.move_cursor(-page),
+                            KeyCode::Home | KeyCode::Char('g') => view.move_cursor(isize::MIN),
+                            KeyCode::End | KeyCode::Char('G') => view.move_cursor(isize::MAX),
+                            KeyCode::Char('v') | KeyCode::Char('V') => view.toggle_anchor(),
+                            KeyCode::Char('J') => view.toggle_pretty(),
+                            _ => {}
+                        },
+                    }
+                }
+                // ---- Clear All Confirm Mode (type "yes") ----
+                else if let Some(input) = &mut app_state.clear_all_input {
+                    match key.code {
+                        KeyCode::Enter => {
+                            if input.trim().eq_ignore_ascii_case("yes") {
+                                log_failure(history.clear_all());
+                            }
+                            app_state.clear_all_input = None;
+                            app_state.show_clear_confirm = false;
+                        }
+                        KeyCode::Esc => {
+                            app_state.clear_all_input = None;
+                            app_state.show_clear_confirm = false;
+                        }
+                        KeyCode::Backspace => {
+                            input.pop();
+                        }
+                        KeyCode::Char(c) => input.push(c),
+                        _ => {}
+                    }
+                }
                 // ---- Clear Confirm Mode ----
                 else if app_state.show_clear_confirm {
                     match key.code {
                         KeyCode::Char('y') | KeyCode::Char('Y') => {
-                            history.clear();
+                            log_failure(history.clear());
                             app_state.show_clear_confirm = false;
                         }
+                        // A: delete pinned entries too, after typing "yes"
+                        KeyCode::Char('a') | KeyCode::Char('A')
+                            if config().clear_keeps_pinned
+                                && all_entries.iter().any(|e| e.pinned) =>
+                        {
+                            app_state.clear_all_input = Some(String::new());
+                        }
                         KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
                             app_state.show_clear_confirm = false;
                         }
                         _ => {}
                     }
                 }
+                // ---- Delete Confirm Mode ----
+                else if app_state.show_delete_confirm {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') => {
+                            delete_selected_or_marked(&history, &mut app_state, &filtered_entries);
+                            app_state.show_delete_confirm = false;
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                            app_state.show_delete_confirm = false;
+                        }
+                        _ => {}
+                    }
+                }
+                // ---- Tag Input ----
+                else if let Some((id, input)) = &mut app_state.tag_input {
+                    match key.code {
+                        KeyCode::Esc => app_state.tag_input = None,
+                        KeyCode::Enter => {
+                            if let Some(entry) = all_entries.iter().find(|e| e.id == *id) {
+                                log_failure(
+                                    history.set_tags(entry.id, toggle_tags(&entry.tags, input)),
+                                );
+                            }
+                            app_state.tag_input = None;
+                        }
+                        KeyCode::Char(c) => input.push(c),
+                        KeyCode::Backspace => {
+                            input.pop();
+                        }
+                        _ => {}
+                    }
+                }
                 // ---- Search Mode ----
                 else if app_state.is_searching {
                     match key.code {
                         KeyCode::Esc => {
                             app_state.is_searching = false;
+                            app_state.remember_search();
                             app_state.search_query.clear();
                         }
                         KeyCode::Enter => {
                             // Confirm selection
+                            app_state.remember_search();
                             app_state.select();
                         }
+                        // Delete / Ctrl+D: delete the highlighted match (resolved by id,
+                        // since the filtered index doesn't match the history position)
+                        KeyCode::Delete => {
+                            request_delete(&history, &mut app_state, &filtered_entries);
+                        }
+                        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            request_delete(&history, &mut app_state, &filtered_entries);
+                        }
+                        // Ctrl+R: toggle between fuzzy and regex search
+                        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app_state.search_is_regex = !app_state.search_is_regex;
+                            app_state.list_state.select(Some(0));
+                        }
+                        // Alt+C: toggle case-sensitive matching
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::ALT) => {
+                            app_state.search_case_sensitive = !app_state.search_case_sensitive;
+                            app_state.list_state.select(Some(0));
+                        }
                         KeyCode::Char(c) => {
                             app_state.search_query.push(c);
                             // Reset selection to top on search change
@@ -945,103 +2217,372 @@ pub fn show_ui(backend: ClipboardBackend) -> Result<(), Box<dyn std::error::Erro
                         }
                         KeyCode::Down => app_state.next(filtered_entries.len()),
                         KeyCode::Up => app_state.previous(filtered_entries.len()),
+                        KeyCode::PageDown => {
+                            app_state.page_down(filtered_entries.len(), app_state.list_page_size)
+                        }
+                        KeyCode::PageUp => {
+                            app_state.page_up(filtered_entries.len(), app_state.list_page_size)
+                        }
+                        KeyCode::Home => app_state.go_first(filtered_entries.len()),
+                        KeyCode::End => app_state.go_last(filtered_entries.len()),
                         _ => {}
                     }
                 }
                 // ---- Normal Mode ----
                 else {
                     let entries_len = filtered_entries.len();
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => app_state.quit(),
-                        KeyCode::Char('c') | KeyCode::Char('C') if entries_len > 0 => {
-                            app_state.show_clear_confirm = true;
+                    // Shift+Enter / I: type the selected text entry out instead of
+                    // pasting it (checked first, since Enter alone is Copy)
+                    let type_key = (key.code == KeyCode::Enter
+                        && key.modifiers.contains(KeyModifiers::SHIFT))
+                        || matches!(key.code, KeyCode::Char('i') | KeyCode::Char('I'));
+                    // While a jump is in progress every printable key extends it
+                    let jump_key = match key.code {
+                        KeyCode::Char(c)
+                            if app_state.jump_active()
+                                && !key
+                                    .modifiers
+                                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                        {
+                            Some(c)
                         }
-                        // Shift+S: stop expiry on a secret entry
-                        KeyCode::Char('S')
-                            if key.modifiers.contains(KeyModifiers::SHIFT) && entries_len > 0 =>
+                        _ => None,
+                    };
+                    if let Some(c) = jump_key {
+                        app_state.type_to_jump(c, &filtered_entries);
+                    } else if type_key {
+                        if app_state
+                            .list_state
+                            .selected()
+                            .and_then(|index| filtered_entries.get(index))
+                            .is_some_and(|e| e.content_type == ClipboardContentType::Text)
                         {
-                            if let Some(index) = app_state.list_state.selected() {
-                                if !app_state.is_searching {
-                                    if let Some(entry) = filtered_entries.get(index) {
-                                        if entry.is_secret() {
-                                            history.stop_expiry(index);
-                                        }
-                                    }
+                            app_state.select_to_type();
+                        }
+                    } else if let Some(action) = config().keymap.action(key.code) {
+                        match action {
+                            // Esc drops the marks before it closes the window
+                            Action::Quit
+                                if key.code == KeyCode::Esc && !app_state.marked.is_empty() =>
+                            {
+                                app_state.marked.clear();
+                            }
+                            Action::Quit => app_state.quit(),
+                            Action::Clear if entries_len > 0 => {
+                                app_state.show_clear_confirm = true;
+                            }
+                            Action::Search => {
+                                app_state.is_searching = true;
+                                app_state.search_query.clear();
+                                app_state.list_state.select(Some(0));
+                            }
+                            Action::Down => app_state.next(entries_len),
+                            Action::Up => app_state.previous(entries_len),
+                            Action::Copy if entries_len > 0 => app_state.select(),
+                            Action::Pin if entries_len > 0 => {
+                                if let Some(entry) = app_state
+                                    .list_state
+                                    .selected()
+                                    .and_then(|index| filtered_entries.get(index))
+                                {
+                                    log_failure(history.toggle_pin(entry.id));
                                 }
                             }
+                            Action::Delete if entries_len > 0 => {
+                                request_delete(&history, &mut app_state, &filtered_entries);
+                            }
+                            _ => {}
                         }
-                        KeyCode::Char('s') => {
-                            // Enter Search Mode (lowercase s only)
-                            app_state.is_searching = true;
-                            app_state.search_query.clear();
-                            app_state.list_state.select(Some(0));
-                        }
-                        // E: open emoji picker
-                        KeyCode::Char('e') | KeyCode::Char('E') => {
-                            app_state.open_emoji_picker();
-                        }
-                        KeyCode::Down | KeyCode::Char('j') => app_state.next(entries_len),
-                        KeyCode::Up | KeyCode::Char('k') => app_state.previous(entries_len),
-                        KeyCode::Enter if entries_len > 0 => app_state.select(),
-                        // R: toggle reveal on a secret entry
-                        KeyCode::Char('r') | KeyCode::Char('R') if entries_len > 0 => {
-                            if let Some(index) = app_state.list_state.selected() {
-                                if let Some(entry) = filtered_entries.get(index) {
+                    } else {
+                        match key.code {
+                            // Shift+S: stop expiry on a secret entry
+                            KeyCode::Char('S')
+                                if key.modifiers.contains(KeyModifiers::SHIFT)
+                                    && entries_len > 0 =>
+                            {
+                                if let Some(entry) = app_state
+                                    .list_state
+                                    .selected()
+                                    .and_then(|index| filtered_entries.get(index))
+                                {
                                     if entry.is_secret() {
-                                        if app_state.reveal_index == Some(index) {
-                                            // Toggle off
-                                            app_state.reveal_index = None;
+                                        log_failure(history.stop_expiry(entry.id));
+                                    }
+                                }
+                            }
+                            // Tab: cycle selection filter (all → clipboard → primary)
+                            KeyCode::Tab => {
+                                app_state.cycle_selection_filter();
+                                app_state.list_state.select(Some(0));
+                            }
+                            // t: cycle content type filter (all → text → images → binary)
+                            KeyCode::Char('t') => {
+                                app_state.cycle_type_filter();
+                                app_state.list_state.select(Some(0));
+                            }
+                            // F: cycle the source app filter (all → each app)
+                            KeyCode::Char('f') | KeyCode::Char('F') => {
+                                let mut sources: Vec<String> = all_entries
+                                    .iter()
+                                    .filter_map(|e| e.source_app.clone())
+                                    .collect();
+                                sources.sort();
+                                sources.dedup();
+                                app_state.cycle_source_filter(&sources);
+                                app_state.list_state.select(Some(0));
+                            }
+                            // z: toggle newest-first / most-copied-first
+                            KeyCode::Char('z') | KeyCode::Char('Z') => {
+                                app_state.sort_by_copies = !app_state.sort_by_copies;
+                                app_state.list_state.select(Some(0));
+                            }
+                            // Shift+T: toggle relative / absolute timestamps
+                            KeyCode::Char('T') => {
+                                app_state.relative_time = !app_state.relative_time;
+                            }
+                            // V: switch between the multi-line and one-line list
+                            KeyCode::Char('v') | KeyCode::Char('V') => {
+                                app_state.compact_list = !app_state.compact_list;
+                            }
+                            // O: open the selected text entry in the detail view
+                            // (secrets only once revealed)
+                            KeyCode::Char('o') | KeyCode::Char('O') if entries_len > 0 => {
+                                let index = app_state.list_state.selected();
+                                if let Some(entry) = index
+                                    .and_then(|index| filtered_entries.get(index))
+                                    .filter(|e| {
+                                        e.content_type == ClipboardContentType::Text
+                                            && (!e.is_secret() || app_state.reveal_index == index)
+                                    })
+                                {
+                                    app_state.open_detail(entry);
+                                }
+                            }
+                            // W: copy the selected text entry with leading/trailing
+                            // whitespace stripped (the stored entry is unchanged)
+                            KeyCode::Char('w') | KeyCode::Char('W') if entries_len > 0 => {
+                                let index = app_state.list_state.selected();
+                                if let Some(entry) = index
+                                    .and_then(|index| filtered_entries.get(index))
+                                    .filter(|e| {
+                                        e.content_type == ClipboardContentType::Text
+                                            && (!e.is_secret() || app_state.reveal_index == index)
+                                    })
+                                {
+                                    app_state.detail_selected =
+                                        Some(entry.content.trim().to_string());
+                                }
+                            }
+                            // y: add the selected text to the stash (or take it out)
+                            KeyCode::Char('y') => {
+                                if let Some(entry) = app_state
+                                    .list_state
+                                    .selected()
+                                    .and_then(|index| filtered_entries.get(index))
+                                    .filter(|e| e.content_type == ClipboardContentType::Text)
+                                {
+                                    stash.toggle(entry.id);
+                                }
+                            }
+                            // Y: copy & paste the stashed texts, joined with newlines
+                            KeyCode::Char('Y') => {
+                                if let Some((_, text)) = stash.text(&history.get_all()) {
+                                    app_state.detail_selected = Some(text);
+                                    if config().clear_stash_on_paste {
+                                        stash.clear();
+                                    }
+                                }
+                            }
+                            // L: copy the selected image or binary data as its
+                            // file location
+                            KeyCode::Char('l') | KeyCode::Char('L')
+                                if app_state
+                                    .list_state
+                                    .selected()
+                                    .and_then(|index| filtered_entries.get(index))
+                                    .is_some_and(|e| e.file_dir().is_some()) =>
+                            {
+                                app_state.select_as_path();
+                            }
+                            // B: copy to the PRIMARY selection (middle-button paste)
+                            KeyCode::Char('b') | KeyCode::Char('B') if entries_len > 0 => {
+                                app_state.select_to_primary();
+                            }
+                            // A: add or remove tags on the selected entry
+                            KeyCode::Char('a') | KeyCode::Char('A') if entries_len > 0 => {
+                                if let Some(entry) = app_state
+                                    .list_state
+                                    .selected()
+                                    .and_then(|index| filtered_entries.get(index))
+                                {
+                                    app_state.open_tag_input(entry.id);
+                                }
+                            }
+                            // M: move the selected entry to the top
+                            KeyCode::Char('m') | KeyCode::Char('M') if entries_len > 0 => {
+                                if let Some(entry) = app_state
+                                    .list_state
+                                    .selected()
+                                    .and_then(|index| filtered_entries.get(index))
+                                {
+                                    log_failure(history.move_to_front(entry.id));
+                                    app_state.follow_entry = Some(entry.id);
+                                }
+                            }
+                            // n / N: jump to the next / previous entry matching the
+                            // last search, without filtering the list
+                            KeyCode::Char(key @ ('n' | 'N'))
+                                if entries_len > 0 && !app_state.last_search.is_empty() =>
+                            {
+                                let regex = app_state
+                                    .search_is_regex
+                                    .then(|| Regex::new(&app_state.last_search));
+                                let start = app_state.list_state.selected().unwrap_or(0);
+                                // Ends on the current entry, so a lone match stays selected
+                                let found = (1..=entries_len)
+                                    .map(|offset| {
+                                        if key == 'n' {
+                                            (start + offset) % entries_len
                                         } else {
-                                            app_state.reveal_index = Some(index);
+                                            (start + entries_len - offset) % entries_len
                                         }
-                                    }
+                                    })
+                                    .find(|&index| {
+                                        search_score(
+                                            matcher,
+                                            filtered_entries[index],
+                                            &app_state.last_search,
+                                            regex.as_ref(),
+                                        )
+                                        .is_some()
+                                    });
+                                if let Some(index) = found {
+                                    app_state.list_state.select(Some(index));
                                 }
                             }
-                        }
-                        KeyCode::Char('p') | KeyCode::Char('P') if entries_len > 0 => {
-                            if let Some(index) = app_state.list_state.selected() {
-                                if !app_state.is_searching {
-                                    history.toggle_pin(index);
+                            // X: free disk space once over `disk_warn_bytes`
+                            KeyCode::Char('x') | KeyCode::Char('X') => {
+                                if let Some(cap) = disk_warn_bytes.filter(|_| over_disk_cap) {
+                                    log_failure(history.free_space(cap));
                                 }
                             }
-                        }
-                        KeyCode::Char('d') | KeyCode::Char('D') | KeyCode::Delete
-                            if entries_len > 0 =>
-                        {
-                            if let Some(index) = app_state.list_state.selected() {
-                                if !app_state.is_searching {
-                                    history.delete_entry(index);
-                                    let new_len = history.get_all().len();
-                                    if new_len == 0 {
-                                        app_state.list_state.select(None);
-                                    } else if index >= new_len {
-                                        app_state.list_state.select(Some(index - 1));
+                            // E: open emoji picker
+                            KeyCode::Char('e') | KeyCode::Char('E') => {
+                                app_state.open_emoji_picker();
+                            }
+                            KeyCode::PageDown => {
+                                app_state.page_down(entries_len, app_state.list_page_size)
+                            }
+                            KeyCode::PageUp => {
+                                app_state.page_up(entries_len, app_state.list_page_size)
+                            }
+                            KeyCode::Home | KeyCode::Char('g') => app_state.go_first(entries_len),
+                            KeyCode::End | KeyCode::Char('G') => app_state.go_last(entries_len),
+                            // R: toggle reveal on a secret entry
+                            KeyCode::Char('r') | KeyCode::Char('R') if entries_len > 0 => {
+                                if let Some(index) = app_state.list_state.selected() {
+                                    if let Some(entry) = filtered_entries.get(index) {
+                                        if entry.is_secret() {
+                                            if app_state.reveal_index == Some(index) {
+                                                // Toggle off
+                                                app_state.reveal_index = None;
+                                            } else {
+                                                app_state.reveal_index = Some(index);
+                                            }
+                                        }
                                     }
                                 }
                             }
+                            // Space: mark / unmark the entry for a bulk delete
+                            KeyCode::Char(' ') if entries_len > 0 => {
+                                if let Some(entry) = app_state
+                                    .list_state
+                                    .selected()
+                                    .and_then(|index| filtered_entries.get(index))
+                                {
+                                    app_state.toggle_mark(entry.id);
+                                }
+                            }
+                            // U: undo the last delete
+                            KeyCode::Char('u') | KeyCode::Char('U') => {
+                                log_failure(history.undo_delete());
+                            }
+                            // 1-9: copy the Nth entry of the current view
+                            KeyCode::Char(digit @ '1'..='9') => {
+                                let index = digit as usize - '1' as usize;
+                                if index < entries_len {
+                                    app_state.list_state.select(Some(index));
+                                    app_state.select();
+                                }
+                            }
+                            // ': jump to the next entry containing the text typed next
+                            KeyCode::Char('\'') => app_state.start_jump(),
+                            // ] / [: next image / next text entry
+                            KeyCode::Char(']') => app_state
+                                .next_of_type(&filtered_entries, ClipboardContentType::Image),
+                            KeyCode::Char('[') => app_state
+                                .next_of_type(&filtered_entries, ClipboardContentType::Text),
+                            // K: copy the selected entry but keep the picker open,
+                            // without pasting it
+                            KeyCode::Char('K') => {
+                                if let Some(entry) = app_state
+                                    .list_state
+                                    .selected()
+                                    .and_then(|index| filtered_entries.get(index))
+                                {
+                                    log_failure(history.expect_echo(entry.id));
+                                    let message = match copy_entry(&history, entry, backend) {
+                                        Ok(()) => {
+                                            log_failure(history.record_copy(entry.id));
+                                            "✓ Copied".to_string()
+                                        }
+                                        Err(e) => format!("✗ {}", e),
+                                    };
+                                    app_state.flash(message);
+                                }
+                            }
+                            _ => {}
                         }
-                        _ => {}
                     }
                 }
             }
         }
 
+        // Remembered for the next reload check (after the keys moved it)
+        selected_id = app_state
+            .list_state
+            .selected()
+            .and_then(|index| filtered_entries.get(index))
+            .map(|e| e.id);
+
         // ====================================================================
-        // HANDLE EMOJI SELECTION (copy to clipboard + paste)
+        // HANDLE EMOJI / DETAIL SELECTION (copy to clipboard + paste)
         // ====================================================================
-        if let Some(emoji_value) = app_state.emoji_selected.take() {
+        let picked = app_state
+            .emoji_selected
+            .take()
+            .map(|value| (value, "emoticon"))
+            .or_else(|| app_state.detail_selected.take().map(|text| (text, "text")));
+        if let Some((emoji_value, what)) = picked {
             // Close the emoji picker UI state (already closed via close_emoji_picker,
             // but ensure it's clean)
             app_state.show_emoji_picker = false;
 
             // We need to exit the TUI, set clipboard, and paste
             // Store as a pseudo-selected entry so the exit logic handles it
+            if kitty_graphics {
+                write!(terminal.backend_mut(), "{}", KITTY_CLEAR)?;
+            }
             disable_raw_mode()?;
-            execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+            execute!(
+                terminal.backend_mut(),
+                LeaveAlternateScreen,
+                DisableMouseCapture
+            )?;
             terminal.show_cursor()?;
 
             if set_clipboard_text(&emoji_value, backend).is_ok() {
-                println!("✓ Copied emoticon: {}", emoji_value);
+                println!("✓ Copied {}: {}", what, emoji_value);
 
                 // Auto-paste
                 if let Ok(exe) = std::env::current_exe() {
@@ -1056,41 +2597,122 @@ pub fn show_ui(backend: ClipboardBackend) -> Result<(), Box<dyn std::error::Erro
         // HANDLE QUIT / SELECTION
         // ====================================================================
         if app_state.should_quit {
-            // Capture selected entry before exiting if we were selecting
-            if let Some(idx) = app_state.list_state.selected() {
-                if let Some(entry) = filtered_entries.get(idx) {
-                    // Only set if we actually "Selected" (pressed enter)
-                    // 'select()' sets selected_index.
-                    if app_state.selected_index.is_some() {
-                        app_state.selected_entry = Some((*entry).clone());
-                    }
-                }
+            // Capture the selected entry only if we actually selected
+            // (Enter); a plain Esc/quit must never copy or paste anything.
+            if app_state.was_selected {
+                app_state.selected_entry = app_state
+                    .selected_index
+                    .and_then(|idx| filtered_entries.get(idx))
+                    .map(|entry| (*entry).clone());
+            }
+            if config().remember_cursor {
+                // A copied entry moves to the top, so only a plain quit is remembered
+                let cursor = app_state
+                    .list_state
+                    .selected()
+                    .and_then(|idx| filtered_entries.get(idx))
+                    .filter(|_| !app_state.was_selected)
+                    .map(|entry| entry.id);
+                save_cursor(history.data_dir(), cursor);
             }
             break;
         }
     }
 
+    if kitty_graphics {
+        write!(terminal.backend_mut(), "{}", KITTY_CLEAR)?;
+    }
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
     terminal.show_cursor()?;
 
+    // Typing happens in a detached process too, once focus is back on the target
+    if let Some(entry) = app_state
+        .selected_entry
+        .as_ref()
+        .filter(|_| app_state.type_selected)
+    {
+        let index = history.get_all().iter().position(|e| e.id == entry.id);
+        if let (Some(index), Ok(exe)) = (index, std::env::current_exe()) {
+            std::process::Command::new(exe)
+                .arg("--type")
+                .arg(index.to_string())
+                .spawn()
+                .ok();
+            println!("✓ Typing entry");
+        }
+        return Ok(());
+    }
+
     // Use captured entry instead of index lookup
     if let Some(entry) = app_state.selected_entry {
+        // The daemon ignores the copy instead of recording it again, so the
+        // entry keeps its place and timestamp
+        log_failure(history.record_copy(entry.id));
+        log_failure(history.expect_echo(entry.id));
         let mut pasted = false;
         match entry.content_type {
+            // The paste keystroke would paste CLIPBOARD, so nothing is pasted
+            ClipboardContentType::Text if app_state.primary_selected => {
+                if set_primary_text(&entry.content, backend).is_ok() {
+                    println!("✓ Copied to the primary selection (middle-click to paste)");
+                }
+            }
+            ClipboardContentType::Image if app_state.primary_selected => {
+                let image_path = history.images_dir().join(&entry.content);
+                if set_primary_image(&image_path, backend).is_ok() {
+                    println!("✓ Copied image to the primary selection (middle-click to paste)");
+                }
+            }
             ClipboardContentType::Text => {
-                if set_clipboard_text(&entry.content, backend).is_ok() {
+                if copy_entry(&history, &entry, backend).is_ok() {
                     println!("✓ Copied to clipboard");
                     pasted = true;
                 }
             }
-            ClipboardContentType::Image => {
+            ClipboardContentType::Image if app_state.path_selected => {
                 let image_path = history.images_dir().join(&entry.content);
-                if set_clipboard_image(&image_path, backend).is_ok() {
+                if set_clipboard_file(&image_path, backend).is_ok() {
+                    println!("✓ Copied image path: {}", image_path.display());
+                    pasted = true;
+                }
+            }
+            ClipboardContentType::Image => {
+                if copy_entry(&history, &entry, backend).is_ok() {
                     println!("✓ Copied image to clipboard");
                     pasted = true;
                 }
             }
+            ClipboardContentType::Binary if app_state.path_selected => {
+                let path = history.blobs_dir().join(&entry.content);
+                if set_clipboard_file(&path, backend).is_ok() {
+                    println!("✓ Copied file path: {}", path.display());
+                    pasted = true;
+                }
+            }
+            ClipboardContentType::Binary => {
+                let path = history.blobs_dir().join(&entry.content);
+                let mime = entry.binary_info.as_ref().map_or("", |i| i.mime.as_str());
+                let result = if app_state.primary_selected {
+                    set_primary_binary(&path, mime, backend)
+                } else {
+                    set_clipboard_binary(&path, mime, backend)
+                };
+                match result {
+                    Ok(()) if app_state.primary_selected => {
+                        println!("✓ Copied {} to the primary selection", mime)
+                    }
+                    Ok(()) => {
+                        println!("✓ Copied {} to clipboard", mime);
+                        pasted = true;
+                    }
+                    Err(e) => eprintln!("Error: {}", e),
+                }
+            }
         }
 
         if pasted {
@@ -1104,3 +2726,104 @@ pub fn show_ui(backend: ClipboardBackend) -> Result<(), Box<dyn std::error::Erro
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::scratch_history;
+    use crate::models::ImageInfo;
+    use ratatui::backend::TestBackend;
+
+    /// Draw every screen at `width`×`height`; the layout math must not panic.
+    fn draw_at(width: u16, height: u16) {
+        let history = scratch_history(&format!("narrow-{}x{}", width, height));
+        let info = ImageInfo {
+            width: 640,
+            height: 480,
+            size_bytes: 2048,
+            format: Some("png".to_string()),
+            original_size_bytes: None,
+        };
+        let entries = vec![
+            ClipboardEntry::new_text("hello\nworld ".repeat(20)),
+            ClipboardEntry::new_text("#ff8800".to_string()),
+            ClipboardEntry::new_image("img_1.png".to_string(), info, 1),
+        ];
+        let filtered: Vec<&ClipboardEntry> = entries.iter().collect();
+        let theme = Theme::plain();
+        let matcher = SkimMatcherV2::default();
+        let stash = Stash::load(history.data_dir());
+        let emoji_cats = emoji::categories();
+        let data = FrameData {
+            backend: ClipboardBackend::Arboard,
+            images_dir: history.images_dir(),
+            theme: &theme,
+            recent: None,
+            all_entries: &entries,
+            filtered_entries: &filtered,
+            shown_scores: None,
+            matcher: &matcher,
+            matching_text: false,
+            search_text: "",
+            search_regex: &None,
+            regex_invalid: false,
+            stash: &stash,
+            emoji_cats: &emoji_cats,
+            capture_paused: false,
+            daemon_backend: &None,
+            disk_usage: 0,
+            over_disk_cap: false,
+            kitty_graphics: false,
+            show_preview: true,
+        };
+
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let mut app_state = AppState::new();
+        let mut list_area = Rect::default();
+        let mut item_heights = Vec::new();
+        let mut preview_area = None;
+        let mut braille_thumbnail = None;
+        let mut draw = |app_state: &mut AppState| {
+            item_heights.clear();
+            let layout = FrameLayout {
+                list_area: &mut list_area,
+                item_heights: &mut item_heights,
+                preview_area: &mut preview_area,
+                braille_thumbnail: &mut braille_thumbnail,
+            };
+            terminal
+                .draw(|f| draw_frame(f, app_state, &data, layout))
+                .unwrap();
+        };
+
+        // The list, with each kind of entry selected
+        for idx in 0..entries.len() {
+            app_state.list_state.select(Some(idx));
+            draw(&mut app_state);
+        }
+
+        // The detail view of a text and an image entry
+        app_state.open_detail(&entries[0]);
+        draw(&mut app_state);
+        app_state.close_detail();
+        app_state.open_detail(&entries[2]);
+        draw(&mut app_state);
+        app_state.close_detail();
+
+        // The emoji picker, browsing and searching
+        app_state.show_emoji_picker = true;
+        draw(&mut app_state);
+        app_state.emoji_search = "smile".to_string();
+        draw(&mut app_state);
+    }
+
+    #[test]
+    fn a_tiny_terminal_draws_without_panicking() {
+        draw_at(5, 5);
+    }
+
+    #[test]
+    fn a_short_narrow_terminal_draws_without_panicking() {
+        draw_at(9, 3);
+    }
+}
@@ -0,0 +1,117 @@
+use std::io::{self, BufRead, Write};
+use std::process::{Command, Stdio};
+
+use crate::clipboard::{
+    ClipboardBackend, ClipboardProvider, set_clipboard_binary, set_clipboard_html,
+    set_clipboard_image_entry,
+};
+use crate::config::config;
+use crate::history::ClipboardHistory;
+use crate::models::{ClipboardContentType, ClipboardEntry};
+use crate::utils::PREVIEW_WIDTH;
+use log::warn;
+
+// ============================================================================
+// DMENU MODE
+// ============================================================================
+//
+// Each entry is written as one line, `\u{1}<id>\t<preview>`, for an external
+// picker such as rofi, wofi or fzf. The chosen line is read back and the
+// entry it names is copied to the clipboard.
+
+/// Marks the start of the hidden id so it can't be confused with entry text
+const ID_PREFIX: char = '\u{1}';
+
+fn menu_line(entry: &ClipboardEntry) -> String {
+    let preview = entry
+        .preview_lines(PREVIEW_WIDTH)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let preview = preview.trim().replace(['\t', '\n', '\r'], " ");
+    format!("{}{}\t{}", ID_PREFIX, entry.id, preview)
+}
+
+/// Id named by a picked line. Pickers that strip control characters are
+/// handled by making the prefix optional.
+fn parse_selection(line: &str) -> Option<u64> {
+    let line = line.trim_end_matches(['\n', '\r']);
+    let line = line.strip_prefix(ID_PREFIX).unwrap_or(line);
+    let id = line.split_once('\t').map_or(line, |(id, _)| id);
+    id.trim().parse().ok()
+}
+
+/// Offer the history to a picker and copy the chosen entry.
+///
+/// With an empty `picker` the menu goes to stdout and the choice is read from
+/// stdin; otherwise `picker` (e.g. `["rofi", "-dmenu"]`) is run with the menu
+/// on its stdin and the choice taken from its stdout.
+pub fn run_dmenu(backend: ClipboardBackend, picker: &[String]) -> Result<(), String> {
+    // Through the daemon, so it can ignore the copy (see `expect_echo`)
+    let history = ClipboardHistory::new().with_control_socket();
+    let entries = history.get_all();
+    let menu: String = entries.iter().map(|e| menu_line(e) + "\n").collect();
+
+    let selection = match picker.split_first() {
+        None => {
+            let mut stdout = io::stdout();
+            stdout
+                .write_all(menu.as_bytes())
+                .and_then(|_| stdout.flush())
+                .map_err(|e| format!("Failed to write menu: {}", e))?;
+            let mut line = String::new();
+            io::stdin()
+                .lock()
+                .read_line(&mut line)
+                .map_err(|e| format!("Failed to read selection: {}", e))?;
+            line
+        }
+        Some((program, args)) => {
+            let mut child = Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin
+                    .write_all(menu.as_bytes())
+                    .map_err(|e| format!("Failed to write menu: {}", e))?;
+            }
+            let output = child
+                .wait_with_output()
+                .map_err(|e| format!("Failed to read selection: {}", e))?;
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        }
+    };
+
+    if selection.trim().is_empty() {
+        return Err("Nothing selected".to_string());
+    }
+    let entry = parse_selection(&selection)
+        .and_then(|id| entries.iter().find(|e| e.id == id))
+        .ok_or_else(|| format!("No entry matches the selection: {}", selection.trim()))?;
+
+    if let Err(e) = history.expect_echo(entry.id) {
+        warn!("{}", e);
+    }
+    match entry.content_type {
+        ClipboardContentType::Text => match &entry.rich_content {
+            Some(html) if config().restore_rich_text => {
+                set_clipboard_html(html, &entry.content, backend)
+            }
+            _ => backend.set_text(&entry.content),
+        },
+        ClipboardContentType::Image => {
+            set_clipboard_image_entry(entry, history.images_dir(), backend)
+        }
+        ClipboardContentType::Binary => {
+            let mime = entry.binary_info.as_ref().map_or("", |i| i.mime.as_str());
+            set_clipboard_binary(&history.blobs_dir().join(&entry.content), mime, backend)
+        }
+    }
+}
@@ -1,5 +1,8 @@
 pub mod app;
 pub mod display;
+pub mod dmenu;
 pub mod emoji;
+pub mod thumbnail;
 
 pub use display::*;
+pub use dmenu::*;
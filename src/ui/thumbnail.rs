@@ -0,0 +1,136 @@
+use std::env;
+use std::io::Cursor;
+use std::path::Path;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ratatui::layout::Rect;
+
+// ============================================================================
+// KITTY GRAPHICS THUMBNAILS
+// ============================================================================
+
+/// Largest side of the PNG sent to the terminal, in pixels
+const THUMBNAIL_MAX_PX: u32 = 480;
+
+/// The graphics protocol caps each escape sequence payload at 4096 bytes
+const CHUNK_SIZE: usize = 4096;
+
+/// Largest braille preview, in cells
+const TEXT_PREVIEW_MAX_COLS: u16 = 40;
+const TEXT_PREVIEW_MAX_ROWS: u16 = 20;
+
+/// Escape sequence deleting every image placed by us on screen.
+pub const KITTY_CLEAR: &str = "\x1b_Ga=d,q=2\x1b\\";
+
+/// Returns true if the terminal understands the Kitty graphics protocol.
+pub fn supports_kitty_graphics() -> bool {
+    if env::var("KITTY_WINDOW_ID").is_ok() {
+        return true;
+    }
+    env::var("TERM").is_ok_and(|term| term.contains("kitty") || term.contains("ghostty"))
+}
+
+/// Build the escape sequence that draws the image at `path` scaled to fit in
+/// `area` (in cells), preserving aspect ratio. The cursor must already be at
+/// the top-left of `area` when the sequence is written.
+pub fn kitty_thumbnail(path: &Path, area: Rect) -> Result<String, String> {
+    if area.width == 0 || area.height == 0 {
+        return Err("Preview area is empty".to_string());
+    }
+
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to load image: {}", e))?
+        .thumbnail(THUMBNAIL_MAX_PX, THUMBNAIL_MAX_PX);
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+
+    // Terminal cells are roughly twice as tall as they are wide
+    let (width, height) = (image.width().max(1) as f64, image.height().max(1) as f64);
+    let scale = (area.width as f64 / width).min(area.height as f64 * 2.0 / height);
+    let cols = ((width * scale) as u16).clamp(1, area.width);
+    let rows = ((height * scale / 2.0) as u16).clamp(1, area.height);
+
+    let encoded = STANDARD.encode(&png);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(CHUNK_SIZE).collect();
+
+    let mut sequence = String::new();
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let more = if idx + 1 < chunks.len() { 1 } else { 0 };
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if idx == 0 {
+            sequence.push_str(&format!(
+                "\x1b_Ga=T,f=100,q=2,C=1,c={},r={},m={};{}\x1b\\",
+                cols, rows, more, chunk
+            ));
+        } else {
+            sequence.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+
+    Ok(sequence)
+}
+
+// ============================================================================
+// BRAILLE THUMBNAILS (for terminals without graphics)
+// ============================================================================
+
+/// Bit of each dot in a braille cell, indexed by [row][column]
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Render the image at `path` as braille characters fitting in `area`
+/// (capped at TEXT_PREVIEW_MAX_COLS x TEXT_PREVIEW_MAX_ROWS), preserving
+/// aspect ratio. Each cell holds 2x4 dots, lit where the pixel is brighter
+/// than the image's average.
+pub fn braille_thumbnail(path: &Path, area: Rect) -> Result<Vec<String>, String> {
+    let cols = area.width.min(TEXT_PREVIEW_MAX_COLS) as u32;
+    let rows = area.height.min(TEXT_PREVIEW_MAX_ROWS) as u32;
+    if cols == 0 || rows == 0 {
+        return Err("Preview area is empty".to_string());
+    }
+
+    // A braille dot is about as wide as it is tall, so one dot per pixel
+    // keeps the aspect ratio
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to load image: {}", e))?
+        .thumbnail(cols * 2, rows * 4)
+        .to_luma_alpha8();
+    let (width, height) = image.dimensions();
+
+    // Transparent pixels stay dark and don't count towards the average
+    let opaque: Vec<u64> = image
+        .pixels()
+        .filter(|p| p[1] >= 128)
+        .map(|p| p[0] as u64)
+        .collect();
+    let threshold = opaque.iter().sum::<u64>() / (opaque.len() as u64).max(1);
+    let lit = |x: u32, y: u32| {
+        x < width && y < height && {
+            let pixel = image.get_pixel(x, y);
+            pixel[1] >= 128 && pixel[0] as u64 > threshold
+        }
+    };
+
+    let lines = (0..height.div_ceil(4))
+        .map(|row| {
+            (0..width.div_ceil(2))
+                .map(|col| {
+                    let mut bits = 0;
+                    for (dy, dots) in BRAILLE_DOTS.iter().enumerate() {
+                        for (dx, bit) in dots.iter().enumerate() {
+                            if lit(col * 2 + dx as u32, row * 4 + dy as u32) {
+                                bits |= bit;
+                            }
+                        }
+                    }
+                    char::from_u32(0x2800 + bits).unwrap_or(' ')
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(lines)
+}
@@ -5,6 +5,61 @@
 pub const MAX_HISTORY: usize = 50;
 pub const POLL_INTERVAL_MS: u64 = 150;
 pub const HISTORY_FILE: &str = "clipboard_history.jsonl";
+/// Format of the history file, recorded in its first line. Files without
+/// that line are format 0 and are upgraded on load. Format 2 hashes with
+/// `hash_bytes`; older hashes are recomputed
+pub const HISTORY_VERSION: u32 = 2;
+/// Locked by every process while it rewrites the history file
+pub const HISTORY_LOCK_FILE: &str = "clipboard_history.lock";
+/// The daemon's save worker waits this long for more captures before writing
+pub const SAVE_DEBOUNCE_MS: u64 = 200;
 pub const PID_FILE: &str = "clipboard_manager.pid";
+/// Locked by the running daemon for as long as it runs, so only one starts
+pub const DAEMON_LOCK_FILE: &str = "daemon.lock";
+pub const WATCHER_PID_FILE: &str = "watchers.pid";
+pub const CURSOR_FILE: &str = "ui_cursor";
+/// Ids of the entries stashed with `y`
+pub const STASH_FILE: &str = "stash.json";
+/// Present while the daemon's capture is paused (read by the UI for its badge)
+pub const PAUSE_FILE: &str = "paused";
+/// The running daemon's backend, and whether its session is still there
+/// (read by the UI)
+pub const BACKEND_FILE: &str = "backend";
 pub const IMAGES_DIR: &str = "images";
+/// Raw data of binary entries (`binary_mime_types`)
+pub const BLOBS_DIR: &str = "blobs";
 pub const SECRET_EXPIRY_SECS: i64 = 300; // 5 minutes
+pub const MAX_UNDO: usize = 10;
+/// Control socket error for UNDO with no delete left to undo
+pub const NOTHING_TO_UNDO: &str = "Nothing to undo";
+/// Single-line text longer than this with almost no whitespace (minified
+/// JSON, base64) is shown as a blob
+pub const BLOB_MIN_CHARS: usize = 500;
+/// Width of one-line previews outside the list (dmenu, `--remove`)
+pub const PREVIEW_WIDTH: usize = 85;
+/// How often the open UI checks the history file for new captures
+pub const UI_RELOAD_MS: u64 = 500;
+/// Keys typed after `'` within this long of each other build one jump
+pub const JUMP_TIMEOUT_MS: u64 = 1000;
+/// How long the footer shows the outcome of a copy that keeps the picker open
+pub const COPY_FLASH_MS: u64 = 1500;
+/// How long a deleted entry's content is ignored by the monitors, since it
+/// is usually still on the clipboard
+pub const TOMBSTONE_SECS: u64 = 30;
+/// How long the daemon ignores an entry the picker just put back on the
+/// clipboard, so the copy isn't captured again as a new one
+pub const ECHO_WINDOW_MS: u64 = 1000;
+pub const CONFIG_FILE: &str = "config.toml";
+pub const TTL_SWEEP_INTERVAL_SECS: u64 = 60;
+/// How often the daemon checks that the display session it started in still exists
+pub const BACKEND_CHECK_INTERVAL_SECS: u64 = 30;
+/// How long shutdown waits for the monitor threads to finish what they're doing
+pub const SHUTDOWN_TIMEOUT_MS: u64 = 2000;
+pub const CONTROL_SOCKET: &str = "control.sock";
+pub const CONTROL_TIMEOUT_SECS: u64 = 2;
+/// How long to wait for `hyprctl activewindow` when `track_source` is on
+pub const SOURCE_QUERY_TIMEOUT_MS: u64 = 250;
+/// Overrides backend detection, like `--backend` (`wl`, `xclip` or `arboard`)
+pub const BACKEND_ENV_VAR: &str = "CLIPBOARD_MANAGER_BACKEND";
+/// Overrides the data directory, like `--data-dir`
+pub const DATA_DIR_ENV_VAR: &str = "CLIPBOARD_MANAGER_DATA_DIR";
@@ -2,6 +2,15 @@
 // HELPER FUNCTIONS
 // ============================================================================
 
+/// FNV-1a hash of `data`, used for `ClipboardEntry::dedup_hash`. It is
+/// saved in the history file, so unlike std's `DefaultHasher` it must give
+/// the same value on every build.
+pub fn hash_bytes(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 /// Format bytes into human-readable size string
 #[inline]
 pub fn format_size(bytes: u64) -> String {
@@ -12,17 +21,25 @@ pub fn format_size(bytes: u64) -> String {
     }
 }
 
-use crate::clipboard::ClipboardBackend;
+use log::{info, warn};
+use std::env;
 use std::process::Command;
 use std::{thread, time::Duration};
 
-/// Performs paste action (intended to be run in a detached process)
-pub fn perform_background_paste(backend: ClipboardBackend) {
+/// Performs paste action (intended to be run in a detached process).
+///
+/// The keystroke tool is picked from the session type rather than the
+/// clipboard backend, since arboard may be in use on either display server.
+pub fn perform_background_paste() {
     // Wait for the main window to largely close and focus to return
     thread::sleep(Duration::from_millis(300));
 
+    let wayland = env::var("WAYLAND_DISPLAY").is_ok()
+        || env::var("XDG_SESSION_TYPE").is_ok_and(|v| v == "wayland");
+    let x11 = env::var("DISPLAY").is_ok();
+
     // Try wtype (Wayland - wlroots based compositors)
-    if matches!(backend, ClipboardBackend::WlClipboard) {
+    if wayland {
         if Command::new("wtype")
             .arg("-M")
             .arg("ctrl")
@@ -35,7 +52,7 @@ pub fn perform_background_paste(backend: ClipboardBackend) {
             .map(|s| s.success())
             .unwrap_or(false)
         {
-            println!("✓ Pasted using wtype");
+            info!("Pasted using wtype");
             return;
         }
     }
@@ -52,12 +69,12 @@ pub fn perform_background_paste(backend: ClipboardBackend) {
         .map(|s| s.success())
         .unwrap_or(false)
     {
-        println!("✓ Pasted using ydotool");
+        info!("Pasted using ydotool");
         return;
     }
 
-    // Try xdotool (X11)
-    if matches!(backend, ClipboardBackend::Arboard) {
+    // Try xdotool (X11, or XWayland windows)
+    if x11 {
         if Command::new("xdotool")
             .arg("key")
             .arg("ctrl+v")
@@ -66,16 +83,62 @@ pub fn perform_background_paste(backend: ClipboardBackend) {
             .map(|s| s.success())
             .unwrap_or(false)
         {
-            println!("✓ Pasted using xdotool");
+            info!("Pasted using xdotool");
             return;
         }
     }
 
     // No tools available - show helpful message
-    eprintln!("⚠ Auto-paste failed: No compatible input simulation tool found.");
-    eprintln!("  Install one of the following:");
-    eprintln!("    - wtype (Wayland/Hyprland): sudo pacman -S wtype");
-    eprintln!("    - ydotool (universal):      sudo pacman -S ydotool");
-    eprintln!("    - xdotool (X11 only):       sudo pacman -S xdotool");
+    warn!("Auto-paste failed: No compatible input simulation tool found.");
+    warn!("Install one of the following:");
+    warn!("  - wtype (Wayland/Hyprland): sudo pacman -S wtype");
+    warn!("  - ydotool (universal):      sudo pacman -S ydotool");
+    warn!("  - xdotool (X11 only):       sudo pacman -S xdotool");
+}
+
+/// Type `text` into the focused window instead of pasting it, for apps that
+/// don't support paste (some terminals, VNC viewers). Like the paste, it is
+/// meant to run in a detached process once the UI has closed.
+pub fn perform_background_type(text: &str) -> Result<(), String> {
+    thread::sleep(Duration::from_millis(300));
+
+    let wayland = env::var("WAYLAND_DISPLAY").is_ok()
+        || env::var("XDG_SESSION_TYPE").is_ok_and(|v| v == "wayland");
+    let x11 = env::var("DISPLAY").is_ok();
+
+    let mut tools: Vec<(&str, Vec<&str>)> = Vec::new();
+    if wayland {
+        tools.push(("wtype", vec!["--", text]));
+    }
+    tools.push(("ydotool", vec!["type", "--", text]));
+    if x11 {
+        tools.push(("xdotool", vec!["type", "--clearmodifiers", "--", text]));
+    }
+
+    for (tool, args) in tools {
+        if Command::new(tool)
+            .args(&args)
+            .status()
+            .is_ok_and(|s| s.success())
+        {
+            info!("Typed {} chars using {}", text.chars().count(), tool);
+            return Ok(());
+        }
+    }
+
+    Err("No compatible input simulation tool found (install wtype, ydotool or xdotool)".to_string())
 }
 
+/// Show a desktop notification with `notify-send`, if it's installed.
+/// The daemon has no window, so this is how it reports problems to the user.
+pub fn notify_desktop(summary: &str, body: &str) {
+    if let Err(e) = Command::new("notify-send")
+        .arg("--app-name=Clipboard Manager")
+        .arg(summary)
+        .arg(body)
+        .spawn()
+        .and_then(|mut c| c.wait())
+    {
+        warn!("Failed to send notification: {}", e);
+    }
+}